@@ -414,8 +414,19 @@ impl DirEntry {
             inode_number: 0,
         }
     }
-    /// Crate a directory entry from name and inode number
+    /// Cut a name to the longest prefix that fits in an entry,
+    /// this is the name actually stored by `new`
+    pub fn fit_name(name: &str) -> &str {
+        let mut len = name.len().min(NAME_LENGTH_LIMIT);
+        while !name.is_char_boundary(len) {
+            len -= 1;
+        }
+        &name[..len]
+    }
+    /// Crate a directory entry from name and inode number,
+    /// names longer than the limit are truncated
     pub fn new(name: &str, inode_number: u32) -> Self {
+        let name = Self::fit_name(name);
         let mut bytes = [0u8; NAME_LENGTH_LIMIT + 1];
         bytes[..name.len()].copy_from_slice(name.as_bytes());
         Self {
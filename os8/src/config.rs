@@ -1,12 +1,40 @@
 //! Constants used in rCore
 
 pub const USER_STACK_SIZE: usize = 4096 * 2;
+pub const MAX_USER_STACK_SIZE: usize = 4096 * 64;
 pub const KERNEL_STACK_SIZE: usize = 4096 * 2;
 pub const KERNEL_HEAP_SIZE: usize = 0x30_0000;
 pub const MEMORY_END: usize = 0x88000000;
 pub const PAGE_SIZE: usize = 0x1000;
 pub const PAGE_SIZE_BITS: usize = 0xc;
 pub const MAX_SYSCALL_NUM: usize = 500;
+pub const TLS_SLOTS: usize = 8;
+pub const BIG_STRIDE: usize = 0x10_0000;
+pub const DEFAULT_PRIORITY: usize = 16;
+pub const DEFAULT_TIMESLICE_MS: usize = 10;
+pub const MIN_TIMESLICE_MS: usize = 1;
+pub const MAX_TIMESLICE_MS: usize = 1000;
+pub const MUTEX_SPIN_LIMIT: usize = 8;
+pub const MAX_FD: usize = 256;
+pub const DEFAULT_AGING_TICKS: usize = 50;
+pub const SYNC_TRACE_LEN: usize = 256;
+pub const SCHED_TRACE_LEN: usize = 1024;
+/// Bytes of the wait reason a thread can set for logs
+pub const WAIT_REASON_LEN: usize = 32;
+pub const SEM_MAX_COUNT: usize = 0x7fff_ffff;
+pub const DEFAULT_SYNC_QUOTA: usize = 128;
+/// Sync object ids hold the slot index below this bit and the generation of
+/// the slot from it up
+pub const SYNC_ID_GEN_SHIFT: usize = 32;
+pub const MQ_MAX_CAPACITY: usize = 256;
+pub const MQ_MAX_MSG_SIZE: usize = 4096;
+/// Longest mapping `sys_mmap_file` makes, all of it copied into frames at once
+pub const MMAP_MAX_LEN: usize = 0x100_0000;
+/// End of the lower half of the Sv39 address space, where user mappings live
+pub const USER_SPACE_END: usize = 1 << 38;
+pub const DEADLOCK_REPORT_EVERY: usize = 16;
+/// Harts the scheduler runs tasks on, numbered from 0
+pub const CPU_NUM: usize = 1;
 
 pub const TRAMPOLINE: usize = usize::MAX - PAGE_SIZE + 1;
 pub const TRAP_CONTEXT: usize = TRAMPOLINE - PAGE_SIZE;
@@ -1,4 +1,5 @@
 use easy_fs::{
+    DirEntry,
     EasyFileSystem,
     Inode,
 };
@@ -8,7 +9,7 @@ use alloc::sync::Arc;
 use lazy_static::*;
 use bitflags::*;
 use alloc::vec::Vec;
-use super::File;
+use super::{File, Stat, StatMode};
 use crate::mm::UserBuffer;
 
 /// A wrapper around a filesystem inode
@@ -16,6 +17,8 @@ use crate::mm::UserBuffer;
 pub struct OSInode {
     readable: bool,
     writable: bool,
+    /// Every write goes to the end of the file
+    append: bool,
     inner: UPSafeCell<OSInodeInner>,
 }
 
@@ -30,11 +33,13 @@ impl OSInode {
     pub fn new(
         readable: bool,
         writable: bool,
+        append: bool,
         inode: Arc<Inode>,
     ) -> Self {
         Self {
             readable,
             writable,
+            append,
             inner: unsafe { UPSafeCell::new(OSInodeInner {
                 offset: 0,
                 inode,
@@ -75,6 +80,15 @@ pub fn list_apps() {
     println!("**************/");
 }
 
+/// Read the entries of directory `path`, `None` if it is not a directory.
+/// The filesystem is flat, so the root is the only directory.
+pub fn read_dir(path: &str) -> Option<Vec<DirEntry>> {
+    match path {
+        "" | "." | "/" => Some(ROOT_INODE.dirents()),
+        _ => None,
+    }
+}
+
 bitflags! {
     /// Flags for opening files
     pub struct OpenFlags: u32 {
@@ -83,6 +97,11 @@ bitflags! {
         const RDWR = 1 << 1;
         const CREATE = 1 << 9;
         const TRUNC = 1 << 10;
+        const APPEND = 1 << 11;
+        /// Only taken by `sys_pipe2`, ignored when opening files
+        const NONBLOCK = 1 << 12;
+        /// With `CREATE`, fail if the file already exists
+        const EXCL = 1 << 13;
     }
 }
 
@@ -102,15 +121,31 @@ impl OpenFlags {
 }
 
 /// Open a file by path
+///
+/// With `CREATE | EXCL` the file is only created, `None` is returned if it
+/// exists. Looking it up and creating it is a single step under the file
+/// system lock, so of several such opens exactly one succeeds.
 pub fn open_file(name: &str, flags: OpenFlags) -> Option<Arc<OSInode>> {
     let (readable, writable) = flags.read_write();
-    if flags.contains(OpenFlags::CREATE) {
+    let append = flags.contains(OpenFlags::APPEND);
+    if flags.contains(OpenFlags::CREATE | OpenFlags::EXCL) {
+        ROOT_INODE.create(name)
+            .map(|inode| {
+                Arc::new(OSInode::new(
+                    readable,
+                    writable,
+                    append,
+                    inode,
+                ))
+            })
+    } else if flags.contains(OpenFlags::CREATE) {
         if let Some(inode) = ROOT_INODE.find(name) {
             // clear size
             inode.clear();
             Some(Arc::new(OSInode::new(
                 readable,
                 writable,
+                append,
                 inode,
             )))
         } else {
@@ -120,6 +155,7 @@ pub fn open_file(name: &str, flags: OpenFlags) -> Option<Arc<OSInode>> {
                     Arc::new(OSInode::new(
                         readable,
                         writable,
+                        append,
                         inode,
                     ))
                 })
@@ -133,6 +169,7 @@ pub fn open_file(name: &str, flags: OpenFlags) -> Option<Arc<OSInode>> {
                 Arc::new(OSInode::new(
                     readable,
                     writable,
+                    append,
                     inode
                 ))
             })
@@ -157,6 +194,9 @@ impl File for OSInode {
     }
     fn write(&self, buf: UserBuffer) -> usize {
         let mut inner = self.inner.exclusive_access();
+        if self.append {
+            inner.offset = inner.inode.size();
+        }
         let mut total_write_size = 0usize;
         for slice in buf.buffers.iter() {
             let write_size = inner.inode.write_at(inner.offset, *slice);
@@ -166,4 +206,29 @@ impl File for OSInode {
         }
         total_write_size
     }
+    fn seekable(&self) -> bool {
+        true
+    }
+    fn size(&self) -> usize {
+        self.inner.exclusive_access().inode.size()
+    }
+    fn offset(&self) -> usize {
+        self.inner.exclusive_access().offset
+    }
+    fn seek(&self, offset: usize) {
+        self.inner.exclusive_access().offset = offset;
+    }
+    fn read_at(&self, offset: usize, buf: &mut [u8]) -> usize {
+        self.inner.exclusive_access().inode.read_at(offset, buf)
+    }
+    fn stat(&self) -> Stat {
+        let inner = self.inner.exclusive_access();
+        let mode = if inner.inode.is_dir() { StatMode::DIR } else { StatMode::FILE };
+        Stat::new(
+            inner.inode.inode_id() as u64,
+            mode,
+            inner.inode.nlink(),
+            inner.inode.size() as u64,
+        )
+    }
 }
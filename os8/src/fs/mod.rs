@@ -3,6 +3,18 @@ mod inode;
 mod pipe;
 
 use crate::mm::UserBuffer;
+use crate::task::TaskControlBlock;
+use alloc::sync::Arc;
+
+/// Events of [`File::poll`], the last three are reported even if not asked for
+pub const POLLIN: usize = 0x1;
+pub const POLLOUT: usize = 0x4;
+/// Writing end of a pipe without readers
+pub const POLLERR: usize = 0x8;
+/// Reading end of a pipe without writers
+pub const POLLHUP: usize = 0x10;
+/// Not an open fd
+pub const POLLNVAL: usize = 0x20;
 
 /// The common abstraction of all IO resources
 pub trait File : Send + Sync {
@@ -10,6 +22,54 @@ pub trait File : Send + Sync {
     fn writable(&self) -> bool;
     fn read(&self, buf: UserBuffer) -> usize;
     fn write(&self, buf: UserBuffer) -> usize;
+    /// Whether the cursor can be moved, false for pipes and the console
+    fn seekable(&self) -> bool {
+        false
+    }
+    /// Size of the file in bytes
+    fn size(&self) -> usize {
+        0
+    }
+    /// Current cursor position
+    fn offset(&self) -> usize {
+        0
+    }
+    /// Move the cursor to `offset`, only called on seekable files
+    fn seek(&self, _offset: usize) {}
+    /// Read from `offset` into a kernel buffer without moving the cursor,
+    /// only called on seekable files
+    fn read_at(&self, _offset: usize, _buf: &mut [u8]) -> usize {
+        0
+    }
+    /// Metadata of the file
+    fn stat(&self) -> Stat;
+    /// Whether writing is pointless because every reader is gone
+    fn broken_pipe(&self) -> bool {
+        false
+    }
+    /// Whether a read or write transferring nothing only stopped because it
+    /// would have had to block, for non-blocking files
+    fn would_block(&self) -> bool {
+        false
+    }
+    /// Wake the threads blocked on this file so that they notice a signal
+    fn interrupt(&self) {}
+    /// Which of the `POLLIN`/`POLLOUT` `events` would not block, plus any
+    /// error condition. Files that never block are always ready.
+    fn poll(&self, events: usize) -> usize {
+        let mut ready = 0;
+        if self.readable() {
+            ready |= POLLIN;
+        }
+        if self.writable() {
+            ready |= POLLOUT;
+        }
+        ready & events
+    }
+    /// Wake `task` with `wake_blocked` once the readiness may have changed
+    fn add_poller(&self, _task: Arc<TaskControlBlock>) {}
+    /// Forget `task` registered by `add_poller`
+    fn remove_poller(&self, _task: &Arc<TaskControlBlock>) {}
 }
 
 /// The stat of a inode
@@ -24,8 +84,23 @@ pub struct Stat {
     pub mode: StatMode,
     /// number of hard links
     pub nlink: u32,
+    /// size of the file in bytes
+    pub size: u64,
     /// unused pad
-    pad: [u64; 7],
+    pad: [u64; 6],
+}
+
+impl Stat {
+    pub fn new(ino: u64, mode: StatMode, nlink: u32, size: u64) -> Self {
+        Self {
+            dev: 0,
+            ino,
+            mode,
+            nlink,
+            size,
+            pad: [0; 6],
+        }
+    }
 }
 
 bitflags! {
@@ -37,9 +112,13 @@ bitflags! {
         const DIR   = 0o040000;
         /// ordinary regular file
         const FILE  = 0o100000;
+        /// pipe
+        const FIFO  = 0o010000;
+        /// character device, i.e. the console
+        const CHR   = 0o020000;
     }
 }    
 
 pub use stdio::{Stdin, Stdout};
-pub use inode::{OSInode, open_file, OpenFlags, list_apps};
+pub use inode::{OSInode, open_file, OpenFlags, list_apps, read_dir};
 pub use pipe::{Pipe, make_pipe};
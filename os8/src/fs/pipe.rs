@@ -1,31 +1,39 @@
-use super::File;
+use super::{File, Stat, StatMode, POLLERR, POLLHUP, POLLIN, POLLOUT};
+use alloc::collections::VecDeque;
 use alloc::sync::{Arc, Weak};
 use crate::sync::UPSafeCell;
 use crate::mm::UserBuffer;
 
-use crate::task::suspend_current_and_run_next;
+use crate::task::{
+    add_task, block_current_and_run_next, check_signals_of_current, current_task,
+    wake_blocked, TaskControlBlock,
+};
 
 /// One end of a pipe
 pub struct Pipe {
     readable: bool,
     writable: bool,
+    /// Return what is there instead of blocking on an empty or full buffer
+    nonblock: bool,
     buffer: Arc<UPSafeCell<PipeRingBuffer>>,
 }
 
 impl Pipe {
     /// Create the read end of a pipe from a ring buffer
-    pub fn read_end_with_buffer(buffer: Arc<UPSafeCell<PipeRingBuffer>>) -> Self {
+    pub fn read_end_with_buffer(buffer: Arc<UPSafeCell<PipeRingBuffer>>, nonblock: bool) -> Self {
         Self {
             readable: true,
             writable: false,
+            nonblock,
             buffer,
         }
     }
     /// Create the write end of a pipe with a ring buffer
-    pub fn write_end_with_buffer(buffer: Arc<UPSafeCell<PipeRingBuffer>>) -> Self {
+    pub fn write_end_with_buffer(buffer: Arc<UPSafeCell<PipeRingBuffer>>, nonblock: bool) -> Self {
         Self {
             readable: false,
             writable: true,
+            nonblock,
             buffer,
         }
     }
@@ -46,7 +54,14 @@ pub struct PipeRingBuffer {
     head: usize,
     tail: usize,
     status: RingBufferStatus,
+    read_end: Option<Weak<Pipe>>,
     write_end: Option<Weak<Pipe>>,
+    /// Readers blocked on an empty buffer
+    read_waiters: VecDeque<Arc<TaskControlBlock>>,
+    /// Writers blocked on a full buffer
+    write_waiters: VecDeque<Arc<TaskControlBlock>>,
+    /// Threads polling either end, woken on any change
+    pollers: VecDeque<Arc<TaskControlBlock>>,
 }
 
 impl PipeRingBuffer {
@@ -56,9 +71,17 @@ impl PipeRingBuffer {
             head: 0,
             tail: 0,
             status: RingBufferStatus::EMPTY,
+            read_end: None,
             write_end: None,
+            read_waiters: VecDeque::new(),
+            write_waiters: VecDeque::new(),
+            pollers: VecDeque::new(),
         }
     }
+    /// Set the read end bound to this buffer
+    pub fn set_read_end(&mut self, read_end: &Arc<Pipe>) {
+        self.read_end = Some(Arc::downgrade(read_end));
+    }
     /// Set the write end bound to this buffer
     pub fn set_write_end(&mut self, write_end: &Arc<Pipe>) {
         self.write_end = Some(Arc::downgrade(write_end));
@@ -106,20 +129,45 @@ impl PipeRingBuffer {
     pub fn all_write_ends_closed(&self) -> bool {
         self.write_end.as_ref().unwrap().upgrade().is_none()
     }
+    /// Check if all read ends bounded to this buffer are closed
+    pub fn all_read_ends_closed(&self) -> bool {
+        self.read_end.as_ref().unwrap().upgrade().is_none()
+    }
+    /// Wake all readers, they check the buffer again
+    fn wake_readers(&mut self) {
+        while let Some(task) = self.read_waiters.pop_front() {
+            add_task(task);
+        }
+        self.wake_pollers();
+    }
+    /// Wake all writers, they check the buffer again
+    fn wake_writers(&mut self) {
+        while let Some(task) = self.write_waiters.pop_front() {
+            add_task(task);
+        }
+        self.wake_pollers();
+    }
+    /// Wake all pollers, they scan their fds again
+    fn wake_pollers(&mut self) {
+        while let Some(task) = self.pollers.pop_front() {
+            wake_blocked(task);
+        }
+    }
 }
 
-/// Crate a pipe
+/// Crate a pipe, both ends non-blocking if `nonblock` is set
 /// return (read_end, write_end)
-pub fn make_pipe() -> (Arc<Pipe>, Arc<Pipe>) {
+pub fn make_pipe(nonblock: bool) -> (Arc<Pipe>, Arc<Pipe>) {
     let buffer = Arc::new(unsafe {
         UPSafeCell::new(PipeRingBuffer::new())
     });
     let read_end = Arc::new(
-        Pipe::read_end_with_buffer(buffer.clone())
+        Pipe::read_end_with_buffer(buffer.clone(), nonblock)
     );
     let write_end = Arc::new(
-        Pipe::write_end_with_buffer(buffer.clone())
+        Pipe::write_end_with_buffer(buffer.clone(), nonblock)
     );
+    buffer.exclusive_access().set_read_end(&read_end);
     buffer.exclusive_access().set_write_end(&write_end);
     (read_end, write_end)
 }
@@ -135,11 +183,16 @@ impl File for Pipe {
             let mut ring_buffer = self.buffer.exclusive_access();
             let loop_read = ring_buffer.available_read();
             if loop_read == 0 {
-                if ring_buffer.all_write_ends_closed() {
+                if ring_buffer.all_write_ends_closed() || self.nonblock {
                     return read_size;
                 }
+                // nobody would wake us up once the process is being killed
+                if check_signals_of_current().is_some() {
+                    return read_size;
+                }
+                ring_buffer.read_waiters.push_back(current_task().unwrap());
                 drop(ring_buffer);
-                suspend_current_and_run_next();
+                block_current_and_run_next();
                 continue;
             }
             // read at most loop_read bytes
@@ -148,9 +201,11 @@ impl File for Pipe {
                     unsafe { *byte_ref = ring_buffer.read_byte(); }
                     read_size += 1;
                 } else {
+                    ring_buffer.wake_writers();
                     return read_size;
                 }
             }
+            ring_buffer.wake_writers();
         }
     }
     fn write(&self, buf: UserBuffer) -> usize {
@@ -159,10 +214,18 @@ impl File for Pipe {
         let mut write_size = 0usize;
         loop {
             let mut ring_buffer = self.buffer.exclusive_access();
+            // nobody will ever read what is left
+            if ring_buffer.all_read_ends_closed() {
+                return write_size;
+            }
             let loop_write = ring_buffer.available_write();
             if loop_write == 0 {
+                if self.nonblock || check_signals_of_current().is_some() {
+                    return write_size;
+                }
+                ring_buffer.write_waiters.push_back(current_task().unwrap());
                 drop(ring_buffer);
-                suspend_current_and_run_next();
+                block_current_and_run_next();
                 continue;
             }
             // write at most loop_write bytes
@@ -171,9 +234,62 @@ impl File for Pipe {
                     ring_buffer.write_byte(unsafe { *byte_ref });
                     write_size += 1;
                 } else {
+                    ring_buffer.wake_readers();
                     return write_size;
                 }
             }
+            ring_buffer.wake_readers();
+        }
+    }
+    fn broken_pipe(&self) -> bool {
+        self.writable && self.buffer.exclusive_access().all_read_ends_closed()
+    }
+    fn poll(&self, events: usize) -> usize {
+        let ring_buffer = self.buffer.exclusive_access();
+        if self.readable {
+            let hup = if ring_buffer.all_write_ends_closed() { POLLHUP } else { 0 };
+            let data = if ring_buffer.available_read() > 0 { POLLIN } else { 0 };
+            hup | (data & events)
+        } else {
+            let err = if ring_buffer.all_read_ends_closed() { POLLERR } else { 0 };
+            let space = if ring_buffer.available_write() > 0 { POLLOUT } else { 0 };
+            err | (space & events)
+        }
+    }
+    fn add_poller(&self, task: Arc<TaskControlBlock>) {
+        self.buffer.exclusive_access().pollers.push_back(task);
+    }
+    fn remove_poller(&self, task: &Arc<TaskControlBlock>) {
+        self.buffer.exclusive_access().pollers.retain(|t| !Arc::ptr_eq(t, task));
+    }
+    fn would_block(&self) -> bool {
+        let ring_buffer = self.buffer.exclusive_access();
+        if !self.nonblock {
+            false
+        } else if self.readable {
+            ring_buffer.available_read() == 0 && !ring_buffer.all_write_ends_closed()
+        } else {
+            ring_buffer.available_write() == 0 && !ring_buffer.all_read_ends_closed()
+        }
+    }
+    fn interrupt(&self) {
+        let mut ring_buffer = self.buffer.exclusive_access();
+        ring_buffer.wake_readers();
+        ring_buffer.wake_writers();
+    }
+    fn stat(&self) -> Stat {
+        Stat::new(0, StatMode::FIFO, 1, 0)
+    }
+}
+
+impl Drop for Pipe {
+    /// Wake the other side so that it sees EOF or a broken pipe
+    fn drop(&mut self) {
+        let mut ring_buffer = self.buffer.exclusive_access();
+        if self.readable {
+            ring_buffer.wake_writers();
+        } else {
+            ring_buffer.wake_readers();
         }
     }
 }
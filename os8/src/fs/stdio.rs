@@ -1,4 +1,4 @@
-use super::File;
+use super::{File, Stat, StatMode};
 use crate::mm::{UserBuffer};
 use crate::sbi::console_getchar;
 use crate::task::suspend_current_and_run_next;
@@ -31,6 +31,9 @@ impl File for Stdin {
     fn write(&self, _user_buf: UserBuffer) -> usize {
         panic!("Cannot write to stdin!");
     }
+    fn stat(&self) -> Stat {
+        Stat::new(0, StatMode::CHR, 1, 0)
+    }
 }
 
 impl File for Stdout {
@@ -45,4 +48,7 @@ impl File for Stdout {
         }
         user_buf.len()
     }
+    fn stat(&self) -> Stat {
+        Stat::new(0, StatMode::CHR, 1, 0)
+    }
 }
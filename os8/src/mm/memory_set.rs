@@ -40,6 +40,8 @@ pub fn kernel_token() -> usize {
 pub struct MemorySet {
     page_table: PageTable,
     areas: Vec<MapArea>,
+    /// Most frames the areas have held at once
+    peak_frames: usize,
 }
 
 impl MemorySet {
@@ -47,6 +49,7 @@ impl MemorySet {
         Self {
             page_table: PageTable::new(),
             areas: Vec::new(),
+            peak_frames: 0,
         }
     }
     pub fn token(&self) -> usize {
@@ -64,6 +67,26 @@ impl MemorySet {
             None,
         );
     }
+    /// Map a framed area filled with `data`, the rest of it is zeroed.
+    /// Assume that no conflicts and `data` fits in the area.
+    pub fn insert_framed_area_with_data(
+        &mut self,
+        start_va: VirtAddr,
+        end_va: VirtAddr,
+        permission: MapPermission,
+        data: &[u8],
+    ) {
+        self.push(
+            MapArea::new(start_va, end_va, MapType::Framed, permission),
+            Some(data),
+        );
+    }
+    /// Whether no page in `[start_vpn, end_vpn)` is mapped
+    pub fn is_unmapped(&self, start_vpn: VirtPageNum, end_vpn: VirtPageNum) -> bool {
+        VPNRange::new(start_vpn, end_vpn)
+            .into_iter()
+            .all(|vpn| self.translate(vpn).map_or(true, |pte| !pte.is_valid()))
+    }
     pub fn remove_area_with_start_vpn(&mut self, start_vpn: VirtPageNum) {
         if let Some((idx, area)) = self
             .areas
@@ -81,6 +104,14 @@ impl MemorySet {
             map_area.copy_data(&mut self.page_table, data);
         }
         self.areas.push(map_area);
+        self.peak_frames = self.peak_frames.max(self.frame_count());
+    }
+    /// Number of frames held by the areas, page tables excluded
+    pub fn frame_count(&self) -> usize {
+        self.areas.iter().map(|area| area.data_frames.len()).sum()
+    }
+    pub fn peak_frames(&self) -> usize {
+        self.peak_frames
     }
     /// Mention that trampoline is not collected by areas.
     fn map_trampoline(&mut self) {
@@ -253,6 +284,7 @@ impl MemorySet {
         Self {
             page_table: PageTable::from_token(kernel_token()),
             areas: areas,
+            peak_frames: 0,
         }
     }
 }
@@ -1,39 +1,139 @@
 use crate::sync::{Mutex, UPSafeCell};
-use crate::task::{add_task, block_current_and_run_next, current_task, TaskControlBlock};
+use crate::task::{
+    block_current_and_run_next, check_signals_of_current, current_task, wake_blocked,
+    TaskControlBlock,
+};
+use crate::timer::add_timer;
 use alloc::{collections::VecDeque, sync::Arc};
 
+/// Kinds of condvar: a signal without waiters is lost for a classic one,
+/// a sticky one remembers it until the next wait
+pub const CONDVAR_CLASSIC: usize = 0;
+pub const CONDVAR_STICKY: usize = 1;
+
+/// Why a wait on a condvar ended
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum CondvarWake {
+    /// Woken by a signal, or a pending signal of a sticky condvar consumed
+    Signalled,
+    /// The timeout expired first
+    TimedOut,
+    /// A fatal signal is pending, the mutex is not held
+    Interrupted,
+}
+
 pub struct Condvar {
+    /// Remember at most one signal sent while nobody waits
+    sticky: bool,
     pub inner: UPSafeCell<CondvarInner>,
 }
 
 pub struct CondvarInner {
     pub wait_queue: VecDeque<Arc<TaskControlBlock>>,
+    /// A signal is kept for the next waiter, only for a sticky condvar
+    pub pending: bool,
 }
 
 impl Condvar {
-    pub fn new() -> Self {
+    pub fn new(sticky: bool) -> Self {
         Self {
+            sticky,
             inner: unsafe {
                 UPSafeCell::new(CondvarInner {
                     wait_queue: VecDeque::new(),
+                    pending: false,
                 })
             },
         }
     }
 
     pub fn signal(&self) {
+        self.signal_n(1);
+    }
+
+    /// Wake the first `n` waiters, or all of them if there are fewer, and
+    /// return how many were woken. A sticky condvar without waiters keeps
+    /// a single signal pending whatever `n` is.
+    pub fn signal_n(&self, n: usize) -> usize {
+        let mut inner = self.inner.exclusive_access();
+        if n > 0 && inner.wait_queue.is_empty() && self.sticky {
+            inner.pending = true;
+        }
+        let woken = n.min(inner.wait_queue.len());
+        for task in inner.wait_queue.drain(..woken) {
+            wake_blocked(task);
+        }
+        woken
+    }
+
+    /// Wake `task` if it waits on this condvar, return whether it did
+    pub fn signal_task(&self, task: &Arc<TaskControlBlock>) -> bool {
         let mut inner = self.inner.exclusive_access();
-        if let Some(task) = inner.wait_queue.pop_front() {
-            add_task(task);
+        match inner.wait_queue.iter().position(|t| Arc::ptr_eq(t, task)) {
+            Some(pos) => {
+                let task = inner.wait_queue.remove(pos).unwrap();
+                wake_blocked(task);
+                true
+            }
+            None => false,
         }
     }
 
-    pub fn wait(&self, mutex: Arc<dyn Mutex>) {
-        mutex.unlock();
+    /// Wait for a signal with `mutex` released, giving up at `expire_ms` if
+    /// it is set. `mutex` is not re-acquired if the wait is interrupted by a
+    /// fatal signal. A pending signal of a sticky condvar is consumed at
+    /// once, `mutex` staying held. Otherwise `released` is called once
+    /// `mutex` is unlocked, with the thread it was handed to, if any.
+    ///
+    /// The reason is decided before `mutex` is locked again: a waiter taken
+    /// off the queue by a signal was signalled even if its timer expired
+    /// meanwhile, so that the signal is not lost.
+    pub fn wait(
+        &self,
+        mutex: Arc<dyn Mutex>,
+        expire_ms: Option<usize>,
+        released: impl FnOnce(Option<Arc<TaskControlBlock>>),
+    ) -> CondvarWake {
+        let mut inner = self.inner.exclusive_access();
+        if inner.pending {
+            inner.pending = false;
+            return CondvarWake::Signalled;
+        }
+        drop(inner);
+        released(mutex.unlock());
+        if check_signals_of_current().is_some() {
+            return CondvarWake::Interrupted;
+        }
+        let task = current_task().unwrap();
         let mut inner = self.inner.exclusive_access();
-        inner.wait_queue.push_back(current_task().unwrap());
+        inner.wait_queue.push_back(Arc::clone(&task));
         drop(inner);
+        if let Some(expire_ms) = expire_ms {
+            add_timer(expire_ms, Arc::clone(&task));
+        }
         block_current_and_run_next();
+        // still queued: woken by the timer or to notice a fatal signal
+        let mut inner = self.inner.exclusive_access();
+        let queued = inner.wait_queue.iter().position(|t| Arc::ptr_eq(t, &task));
+        if let Some(pos) = queued {
+            inner.wait_queue.remove(pos);
+        }
+        drop(inner);
+        if check_signals_of_current().is_some() {
+            return CondvarWake::Interrupted;
+        }
         mutex.lock();
+        match queued {
+            Some(_) => CondvarWake::TimedOut,
+            None => CondvarWake::Signalled,
+        }
+    }
+
+    /// Wake all waiters, they will find the fatal signal pending
+    pub fn interrupt(&self) {
+        let mut inner = self.inner.exclusive_access();
+        while let Some(task) = inner.wait_queue.pop_front() {
+            wake_blocked(task);
+        }
     }
 }
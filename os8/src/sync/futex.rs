@@ -0,0 +1,82 @@
+//! Wait queues on user words, keyed by physical address
+
+use crate::sync::UPSafeCell;
+use crate::task::{add_task, TaskControlBlock};
+use alloc::collections::{BTreeMap, VecDeque};
+use alloc::sync::Arc;
+use lazy_static::*;
+
+/// Blocked tasks of every futex, a queue exists only while it has waiters.
+///
+/// Keying by physical address rather than by virtual address lets threads
+/// of different processes mapping the same frame wait on the same word.
+pub struct Futexes {
+    queues: BTreeMap<usize, VecDeque<Arc<TaskControlBlock>>>,
+}
+
+impl Futexes {
+    pub fn new() -> Self {
+        Self {
+            queues: BTreeMap::new(),
+        }
+    }
+
+    pub fn push(&mut self, pa: usize, task: Arc<TaskControlBlock>) {
+        self.queues.entry(pa).or_default().push_back(task);
+    }
+
+    /// Take up to `n` waiters of `pa`, oldest first
+    pub fn pop_n(&mut self, pa: usize, n: usize) -> VecDeque<Arc<TaskControlBlock>> {
+        let queue = match self.queues.get_mut(&pa) {
+            Some(queue) => queue,
+            None => return VecDeque::new(),
+        };
+        let woken = queue.drain(..n.min(queue.len())).collect();
+        if queue.is_empty() {
+            self.queues.remove(&pa);
+        }
+        woken
+    }
+
+    /// Remove `task` from the queue it waits in, return whether it waited
+    pub fn cancel(&mut self, task: &Arc<TaskControlBlock>) -> bool {
+        let found = self.queues.iter_mut().find_map(|(&pa, queue)| {
+            let pos = queue.iter().position(|t| Arc::ptr_eq(t, task))?;
+            queue.remove(pos);
+            Some(pa)
+        });
+        match found {
+            Some(pa) => {
+                if self.queues[&pa].is_empty() {
+                    self.queues.remove(&pa);
+                }
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+lazy_static! {
+    static ref FUTEXES: UPSafeCell<Futexes> = unsafe { UPSafeCell::new(Futexes::new()) };
+}
+
+/// Queue `task` on the word at `pa`, the caller blocks it right after
+pub fn futex_push(pa: usize, task: Arc<TaskControlBlock>) {
+    FUTEXES.exclusive_access().push(pa, task);
+}
+
+/// Wake up to `n` waiters of the word at `pa`, return how many were woken
+pub fn futex_wake(pa: usize, n: usize) -> usize {
+    let woken = FUTEXES.exclusive_access().pop_n(pa, n);
+    let count = woken.len();
+    for task in woken {
+        add_task(task);
+    }
+    count
+}
+
+/// Drop `task` from the futex it waits on, return whether it was waiting
+pub fn futex_cancel(task: &Arc<TaskControlBlock>) -> bool {
+    FUTEXES.exclusive_access().cancel(task)
+}
@@ -1,11 +1,19 @@
 //! Synchronization and interior mutability primitives
 
 mod condvar;
+mod futex;
+mod msgqueue;
 mod mutex;
 mod semaphore;
+mod trace;
 mod up;
 
-pub use condvar::Condvar;
-pub use mutex::{Mutex, MutexBlocking, MutexSpin};
+pub use condvar::{Condvar, CondvarWake, CONDVAR_CLASSIC, CONDVAR_STICKY};
+pub use futex::{futex_cancel, futex_push, futex_wake};
+pub use msgqueue::MsgQueue;
+pub use mutex::{Mutex, MutexAdaptive, MutexBlocking, MutexSpin};
+pub use mutex::{MUTEX_ADAPTIVE, MUTEX_BARGING, MUTEX_BLOCKING, MUTEX_SPIN};
 pub use semaphore::Semaphore;
+pub use trace::{sync_trace_drain, sync_trace_record, SyncEvent};
+pub use trace::{SYNC_DOWN, SYNC_LOCK, SYNC_SIGNAL, SYNC_UNLOCK, SYNC_UP, SYNC_WAIT};
 pub use up::UPSafeCell;
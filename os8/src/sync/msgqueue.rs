@@ -0,0 +1,91 @@
+use crate::sync::UPSafeCell;
+use crate::task::{
+    add_task, block_current_and_run_next, check_signals_of_current, current_task,
+    TaskControlBlock,
+};
+use alloc::{collections::VecDeque, sync::Arc, vec::Vec};
+
+/// A bounded FIFO of fixed-size messages. Senders block while it is full,
+/// receivers while it is empty.
+pub struct MsgQueue {
+    pub capacity: usize,
+    pub msg_size: usize,
+    pub inner: UPSafeCell<MsgQueueInner>,
+}
+
+pub struct MsgQueueInner {
+    /// Messages not received yet, oldest at the front
+    pub msgs: VecDeque<Vec<u8>>,
+    pub send_waiters: VecDeque<Arc<TaskControlBlock>>,
+    pub recv_waiters: VecDeque<Arc<TaskControlBlock>>,
+}
+
+impl MsgQueue {
+    pub fn new(capacity: usize, msg_size: usize) -> Self {
+        Self {
+            capacity,
+            msg_size,
+            inner: unsafe {
+                UPSafeCell::new(MsgQueueInner {
+                    msgs: VecDeque::new(),
+                    send_waiters: VecDeque::new(),
+                    recv_waiters: VecDeque::new(),
+                })
+            },
+        }
+    }
+
+    /// Queue `msg`, waiting for room if the queue is full. Return false,
+    /// `msg` being dropped, if the wait was interrupted.
+    pub fn send(&self, msg: Vec<u8>) -> bool {
+        loop {
+            let mut inner = self.inner.exclusive_access();
+            if inner.msgs.len() < self.capacity {
+                inner.msgs.push_back(msg);
+                if let Some(task) = inner.recv_waiters.pop_front() {
+                    add_task(task);
+                }
+                return true;
+            }
+            // nobody would wake us up once the process is being killed
+            if check_signals_of_current().is_some() {
+                return false;
+            }
+            inner.send_waiters.push_back(current_task().unwrap());
+            drop(inner);
+            block_current_and_run_next();
+        }
+    }
+
+    /// Take the oldest message, waiting for one if the queue is empty.
+    /// Return `None` if the wait was interrupted.
+    pub fn recv(&self) -> Option<Vec<u8>> {
+        loop {
+            let mut inner = self.inner.exclusive_access();
+            if let Some(msg) = inner.msgs.pop_front() {
+                if let Some(task) = inner.send_waiters.pop_front() {
+                    add_task(task);
+                }
+                return Some(msg);
+            }
+            // nobody would wake us up once the process is being killed
+            if check_signals_of_current().is_some() {
+                return None;
+            }
+            inner.recv_waiters.push_back(current_task().unwrap());
+            drop(inner);
+            block_current_and_run_next();
+        }
+    }
+
+    /// Wake all waiters, they check for a fatal signal before waiting again
+    pub fn interrupt(&self) {
+        let mut inner = self.inner.exclusive_access();
+        while let Some(task) = inner.send_waiters.pop_front() {
+            add_task(task);
+        }
+        while let Some(task) = inner.recv_waiters.pop_front() {
+            add_task(task);
+        }
+    }
+}
@@ -1,12 +1,33 @@
 use super::UPSafeCell;
+use crate::config::MUTEX_SPIN_LIMIT;
 use crate::task::TaskControlBlock;
-use crate::task::{add_task, current_task};
+use crate::task::{add_task, check_signals_of_current, current_task};
 use crate::task::{block_current_and_run_next, suspend_current_and_run_next};
 use alloc::{collections::VecDeque, sync::Arc};
 
+/// Kinds of mutex, as reported by [`Mutex::kind`]
+pub const MUTEX_SPIN: usize = 0;
+pub const MUTEX_BLOCKING: usize = 1;
+pub const MUTEX_ADAPTIVE: usize = 2;
+pub const MUTEX_BARGING: usize = 3;
+
 pub trait Mutex: Sync + Send {
+    /// Acquire the lock, or give up early once a fatal signal is pending
     fn lock(&self);
-    fn unlock(&self);
+    /// Take the lock if nobody holds it, without waiting. Return whether it
+    /// was taken.
+    fn try_lock(&self) -> bool;
+    /// Release the lock, return the waiter it was handed to, if any
+    fn unlock(&self) -> Option<Arc<TaskControlBlock>>;
+    /// Release the lock, handing it to `task` if it is waiting, otherwise
+    /// like `unlock`
+    fn unlock_to(&self, _task: &Arc<TaskControlBlock>) -> Option<Arc<TaskControlBlock>> {
+        self.unlock()
+    }
+    /// Wake all waiters without handing over the lock
+    fn interrupt(&self);
+    /// Which implementation this is, one of the `MUTEX_*` kinds
+    fn kind(&self) -> usize;
 }
 
 pub struct MutexSpin {
@@ -27,6 +48,9 @@ impl Mutex for MutexSpin {
             let mut locked = self.locked.exclusive_access();
             if *locked {
                 drop(locked);
+                if check_signals_of_current().is_some() {
+                    return;
+                }
                 suspend_current_and_run_next();
                 continue;
             } else {
@@ -36,38 +60,204 @@ impl Mutex for MutexSpin {
         }
     }
 
-    fn unlock(&self) {
+    fn try_lock(&self) -> bool {
+        let mut locked = self.locked.exclusive_access();
+        !core::mem::replace(&mut *locked, true)
+    }
+
+    fn unlock(&self) -> Option<Arc<TaskControlBlock>> {
         let mut locked = self.locked.exclusive_access();
         *locked = false;
+        None
+    }
+
+    fn interrupt(&self) {
+        // spinning waiters check for signals by themselves
+    }
+    fn kind(&self) -> usize {
+        MUTEX_SPIN
     }
 }
 
+/// Blocks while the lock is held.
+///
+/// By default `unlock` hands the lock straight to the front waiter, so
+/// waiters get it in FIFO order. A barging mutex releases it and lets the
+/// woken waiter contend again, a thread arriving in between may take it
+/// first: better throughput, but no fairness.
 pub struct MutexBlocking {
     inner: UPSafeCell<MutexBlockingInner>,
+    handoff: bool,
 }
 
 pub struct MutexBlockingInner {
     locked: bool,
     wait_queue: VecDeque<Arc<TaskControlBlock>>,
+    /// Waiter the lock was handed to by `unlock_to`, for a barging mutex
+    granted: Option<Arc<TaskControlBlock>>,
+}
+
+impl MutexBlockingInner {
+    fn new() -> Self {
+        Self {
+            locked: false,
+            wait_queue: VecDeque::new(),
+            granted: None,
+        }
+    }
+
+    /// Take the lock if it is free. A lock handed over to a waiter stays
+    /// locked, so only waiters of a barging mutex can be overtaken, as by
+    /// `lock`.
+    fn try_lock(&mut self) -> bool {
+        !core::mem::replace(&mut self.locked, true)
+    }
+
+    /// Wake `task` with the lock still held if it is waiting
+    fn grant(&mut self, task: &Arc<TaskControlBlock>) -> bool {
+        match self.wait_queue.iter().position(|t| Arc::ptr_eq(t, task)) {
+            Some(pos) => {
+                add_task(self.wait_queue.remove(pos).unwrap());
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Whether the lock was granted to the current task, which then owns it
+    fn take_grant(&mut self) -> bool {
+        let current = current_task().unwrap();
+        match &self.granted {
+            Some(task) if Arc::ptr_eq(task, &current) => {
+                self.granted = None;
+                true
+            }
+            _ => false,
+        }
+    }
 }
 
 impl MutexBlocking {
     pub fn new() -> Self {
+        Self::with_handoff(true)
+    }
+
+    pub fn barging() -> Self {
+        Self::with_handoff(false)
+    }
+
+    fn with_handoff(handoff: bool) -> Self {
         Self {
-            inner: unsafe {
-                UPSafeCell::new(MutexBlockingInner {
-                    locked: false,
-                    wait_queue: VecDeque::new(),
-                })
-            },
+            inner: unsafe { UPSafeCell::new(MutexBlockingInner::new()) },
+            handoff,
         }
     }
 }
 
 impl Mutex for MutexBlocking {
     fn lock(&self) {
+        loop {
+            let mut mutex_inner = self.inner.exclusive_access();
+            if !mutex_inner.locked {
+                mutex_inner.locked = true;
+                return;
+            }
+            // nobody would wake us up once the process is being killed
+            if check_signals_of_current().is_some() {
+                return;
+            }
+            mutex_inner.wait_queue.push_back(current_task().unwrap());
+            drop(mutex_inner);
+            block_current_and_run_next();
+            if self.handoff || self.inner.exclusive_access().take_grant() {
+                return;
+            }
+        }
+    }
+
+    fn try_lock(&self) -> bool {
+        self.inner.exclusive_access().try_lock()
+    }
+
+    fn unlock(&self) -> Option<Arc<TaskControlBlock>> {
+        let mut mutex_inner = self.inner.exclusive_access();
+        assert!(mutex_inner.locked);
+        let waking_task = mutex_inner.wait_queue.pop_front();
+        if let Some(task) = &waking_task {
+            add_task(Arc::clone(task));
+        }
+        if self.handoff && waking_task.is_some() {
+            waking_task
+        } else {
+            mutex_inner.locked = false;
+            None
+        }
+    }
+
+    fn unlock_to(&self, task: &Arc<TaskControlBlock>) -> Option<Arc<TaskControlBlock>> {
+        let mut mutex_inner = self.inner.exclusive_access();
+        assert!(mutex_inner.locked);
+        if mutex_inner.grant(task) {
+            if !self.handoff {
+                mutex_inner.granted = Some(Arc::clone(task));
+            }
+            return Some(Arc::clone(task));
+        }
+        drop(mutex_inner);
+        self.unlock()
+    }
+
+    fn interrupt(&self) {
+        let mut mutex_inner = self.inner.exclusive_access();
+        while let Some(task) = mutex_inner.wait_queue.pop_front() {
+            add_task(task);
+        }
+    }
+    fn kind(&self) -> usize {
+        if self.handoff {
+            MUTEX_BLOCKING
+        } else {
+            MUTEX_BARGING
+        }
+    }
+}
+
+/// Yields up to `MUTEX_SPIN_LIMIT` times while the lock is held, then
+/// blocks like [`MutexBlocking`]
+///
+/// Short critical sections are usually over after a few yields, so the
+/// waiter never has to block; long ones do not keep it busy for long.
+pub struct MutexAdaptive {
+    inner: UPSafeCell<MutexBlockingInner>,
+}
+
+impl MutexAdaptive {
+    pub fn new() -> Self {
+        Self {
+            inner: unsafe { UPSafeCell::new(MutexBlockingInner::new()) },
+        }
+    }
+}
+
+impl Mutex for MutexAdaptive {
+    fn lock(&self) {
+        for _ in 0..MUTEX_SPIN_LIMIT {
+            let mut mutex_inner = self.inner.exclusive_access();
+            if !mutex_inner.locked {
+                mutex_inner.locked = true;
+                return;
+            }
+            drop(mutex_inner);
+            if check_signals_of_current().is_some() {
+                return;
+            }
+            suspend_current_and_run_next();
+        }
         let mut mutex_inner = self.inner.exclusive_access();
         if mutex_inner.locked {
+            if check_signals_of_current().is_some() {
+                return;
+            }
             mutex_inner.wait_queue.push_back(current_task().unwrap());
             drop(mutex_inner);
             block_current_and_run_next();
@@ -76,13 +266,39 @@ impl Mutex for MutexBlocking {
         }
     }
 
-    fn unlock(&self) {
+    fn try_lock(&self) -> bool {
+        self.inner.exclusive_access().try_lock()
+    }
+
+    fn unlock(&self) -> Option<Arc<TaskControlBlock>> {
         let mut mutex_inner = self.inner.exclusive_access();
         assert!(mutex_inner.locked);
         if let Some(waking_task) = mutex_inner.wait_queue.pop_front() {
-            add_task(waking_task);
+            add_task(Arc::clone(&waking_task));
+            Some(waking_task)
         } else {
             mutex_inner.locked = false;
+            None
+        }
+    }
+
+    fn unlock_to(&self, task: &Arc<TaskControlBlock>) -> Option<Arc<TaskControlBlock>> {
+        let mut mutex_inner = self.inner.exclusive_access();
+        assert!(mutex_inner.locked);
+        if mutex_inner.grant(task) {
+            return Some(Arc::clone(task));
         }
+        drop(mutex_inner);
+        self.unlock()
+    }
+
+    fn interrupt(&self) {
+        let mut mutex_inner = self.inner.exclusive_access();
+        while let Some(task) = mutex_inner.wait_queue.pop_front() {
+            add_task(task);
+        }
+    }
+    fn kind(&self) -> usize {
+        MUTEX_ADAPTIVE
     }
 }
@@ -1,14 +1,26 @@
 use crate::sync::UPSafeCell;
-use crate::task::{add_task, block_current_and_run_next, current_task, TaskControlBlock};
-use alloc::{collections::VecDeque, sync::Arc};
+use crate::task::{
+    add_task, block_current_and_run_next, check_signals_of_current, current_task,
+    TaskControlBlock,
+};
+use alloc::{collections::VecDeque, sync::Arc, vec::Vec};
 
+/// A counting semaphore, waiters are granted resources in arrival order.
+///
+/// A waiter may ask for several resources at once. Releasing resources wakes
+/// as many waiters from the front of the queue as they can satisfy, and stops
+/// at the first one asking for more than is left: a large request is never
+/// overtaken by smaller ones behind it, so it cannot starve.
 pub struct Semaphore {
     pub inner: UPSafeCell<SemaphoreInner>,
 }
 
 pub struct SemaphoreInner {
-    pub count: isize,
-    pub wait_queue: VecDeque<Arc<TaskControlBlock>>,
+    /// Resources not handed out yet
+    pub avail: usize,
+    /// Blocked tasks with the number of resources they ask for, oldest at
+    /// the front
+    pub wait_queue: VecDeque<(Arc<TaskControlBlock>, usize)>,
 }
 
 impl Semaphore {
@@ -16,30 +28,80 @@ impl Semaphore {
         Self {
             inner: unsafe {
                 UPSafeCell::new(SemaphoreInner {
-                    count: res_count as isize,
+                    avail: res_count,
                     wait_queue: VecDeque::new(),
                 })
             },
         }
     }
 
-    pub fn up(&self) {
+    /// Release `n` resources and hand them to the waiters at the front of
+    /// the queue, in order, as long as their requests can be met. Return the
+    /// woken waiters with the number of resources each was handed.
+    pub fn up_n(&self, n: usize) -> Vec<(Arc<TaskControlBlock>, usize)> {
         let mut inner = self.inner.exclusive_access();
-        inner.count += 1;
-        if inner.count <= 0 {
-            if let Some(task) = inner.wait_queue.pop_front() {
-                add_task(task);
+        inner.avail += n;
+        let mut granted = Vec::new();
+        while let Some(&(_, need)) = inner.wait_queue.front() {
+            if need > inner.avail {
+                break;
             }
+            inner.avail -= need;
+            let (task, _) = inner.wait_queue.pop_front().unwrap();
+            add_task(Arc::clone(&task));
+            granted.push((task, need));
         }
+        granted
     }
 
-    pub fn down(&self) {
+    /// Whether `n` resources can be taken right away, nobody waiting before
+    pub fn can_down_n(&self, n: usize) -> bool {
+        let inner = self.inner.exclusive_access();
+        inner.wait_queue.is_empty() && inner.avail >= n
+    }
+
+    /// Acquire `n` resources at once. Return whether they were taken
+    /// without blocking; when woken up they have been handed over by
+    /// [`Self::up_n`], unless the wait was interrupted.
+    pub fn down_n(&self, n: usize) -> bool {
+        if self.can_down_n(n) {
+            self.inner.exclusive_access().avail -= n;
+            return true;
+        }
+        let mut inner = self.inner.exclusive_access();
+        // nobody would wake us up once the process is being killed
+        if check_signals_of_current().is_some() {
+            return false;
+        }
+        inner.wait_queue.push_back((current_task().unwrap(), n));
+        drop(inner);
+        block_current_and_run_next();
+        false
+    }
+
+    /// Available resources minus those the waiters still ask for, so minus
+    /// the number of waiters when they all ask for one and none is left
+    pub fn value(&self) -> isize {
+        let inner = self.inner.exclusive_access();
+        let wanted: usize = inner.wait_queue.iter().map(|(_, need)| need).sum();
+        inner.avail as isize - wanted as isize
+    }
+
+    /// The tasks blocked on the semaphore, oldest first
+    pub fn waiters(&self) -> Vec<Arc<TaskControlBlock>> {
+        let inner = self.inner.exclusive_access();
+        inner
+            .wait_queue
+            .iter()
+            .map(|(task, _)| Arc::clone(task))
+            .collect()
+    }
+
+    /// Wake all waiters without handing them a resource
+    pub fn interrupt(&self) {
         let mut inner = self.inner.exclusive_access();
-        inner.count -= 1;
-        if inner.count < 0 {
-            inner.wait_queue.push_back(current_task().unwrap());
-            drop(inner);
-            block_current_and_run_next();
+        while let Some((task, _)) = inner.wait_queue.pop_front() {
+            add_task(task);
         }
     }
 }
@@ -0,0 +1,68 @@
+//! Ring buffer of lock events, for postmortem debugging
+
+use super::UPSafeCell;
+use crate::config::SYNC_TRACE_LEN;
+use crate::timer::get_time_us;
+use alloc::collections::VecDeque;
+use alloc::vec::Vec;
+use lazy_static::*;
+
+/// Operations recorded in a [`SyncEvent`]
+pub const SYNC_LOCK: usize = 1;
+pub const SYNC_UNLOCK: usize = 2;
+pub const SYNC_DOWN: usize = 3;
+pub const SYNC_UP: usize = 4;
+pub const SYNC_SIGNAL: usize = 5;
+pub const SYNC_WAIT: usize = 6;
+
+/// One traced operation on a mutex, semaphore or condvar, tids and ids
+/// being only unique within their process
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct SyncEvent {
+    pub time_us: usize,
+    pub pid: usize,
+    pub tid: usize,
+    /// One of the `SYNC_*` operations
+    pub op: usize,
+    /// Id of the mutex, semaphore or condvar
+    pub id: usize,
+}
+
+/// The last `SYNC_TRACE_LEN` events of all processes, the oldest is
+/// overwritten when full
+pub struct SyncTrace {
+    events: VecDeque<SyncEvent>,
+}
+
+impl SyncTrace {
+    pub fn new() -> Self {
+        Self {
+            events: VecDeque::new(),
+        }
+    }
+}
+
+lazy_static! {
+    static ref SYNC_TRACE: UPSafeCell<SyncTrace> = unsafe { UPSafeCell::new(SyncTrace::new()) };
+}
+
+/// Record operation `op` of thread `tid` of process `pid` on object `id`
+pub fn sync_trace_record(pid: usize, tid: usize, op: usize, id: usize) {
+    let mut trace = SYNC_TRACE.exclusive_access();
+    if trace.events.len() == SYNC_TRACE_LEN {
+        trace.events.pop_front();
+    }
+    trace.events.push_back(SyncEvent {
+        time_us: get_time_us(),
+        pid,
+        tid,
+        op,
+        id,
+    });
+}
+
+/// Empty the buffer, returning its events from the oldest
+pub fn sync_trace_drain() -> Vec<SyncEvent> {
+    SYNC_TRACE.exclusive_access().events.drain(..).collect()
+}
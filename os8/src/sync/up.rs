@@ -1,6 +1,10 @@
 //! Uniprocessor interior mutability primitives
 
+#[cfg(debug_assertions)]
+use core::cell::Cell;
 use core::cell::{RefCell, RefMut};
+#[cfg(debug_assertions)]
+use core::panic::Location;
 
 /// Wrap a static data structure inside it so that we are
 /// able to access it without any `unsafe`.
@@ -9,9 +13,15 @@ use core::cell::{RefCell, RefMut};
 ///
 /// In order to get mutable reference of inner data, call
 /// `exclusive_access`.
+///
+/// Debug builds remember where the data was last borrowed, so that a
+/// double borrow reports the call site still holding it.
 pub struct UPSafeCell<T> {
     /// inner data
     inner: RefCell<T>,
+    /// call site of the last successful borrow
+    #[cfg(debug_assertions)]
+    borrowed_at: Cell<Option<&'static Location<'static>>>,
 }
 
 unsafe impl<T> Sync for UPSafeCell<T> {}
@@ -22,10 +32,31 @@ impl<T> UPSafeCell<T> {
     pub unsafe fn new(value: T) -> Self {
         Self {
             inner: RefCell::new(value),
+            #[cfg(debug_assertions)]
+            borrowed_at: Cell::new(None),
         }
     }
     /// Panic if the data has been borrowed.
+    #[track_caller]
     pub fn exclusive_access(&self) -> RefMut<'_, T> {
+        #[cfg(debug_assertions)]
+        {
+            // nothing can borrow it in between, so the last borrower still holds it
+            let caller = Location::caller();
+            match self.inner.try_borrow_mut() {
+                Ok(inner) => {
+                    self.borrowed_at.set(Some(caller));
+                    inner
+                }
+                Err(_) => match self.borrowed_at.get() {
+                    Some(holder) => {
+                        panic!("{} borrows data already borrowed at {}", caller, holder)
+                    }
+                    None => panic!("{} borrows data already borrowed", caller),
+                },
+            }
+        }
+        #[cfg(not(debug_assertions))]
         self.inner.borrow_mut()
     }
 }
@@ -0,0 +1,45 @@
+//! Error numbers returned (negated) by syscalls
+
+/// Operation not permitted, e.g. waiting without holding the mutex
+pub const EPERM: isize = 1;
+/// No such file or directory
+pub const ENOENT: isize = 2;
+/// Interrupted by a signal
+pub const EINTR: isize = 4;
+/// Bad file descriptor
+pub const EBADF: isize = 9;
+/// Try again, e.g. a futex word no longer holding the expected value
+pub const EAGAIN: isize = 11;
+/// Permission denied, e.g. mapping a file not open for reading
+pub const EACCES: isize = 13;
+/// Bad address, e.g. an unmapped user pointer
+pub const EFAULT: isize = 14;
+/// Resource busy, e.g. other threads still running
+pub const EBUSY: isize = 16;
+/// File exists, e.g. an exclusive create of a present file
+pub const EEXIST: isize = 17;
+/// Invalid argument
+pub const EINVAL: isize = 22;
+/// Illegal seek, e.g. on a pipe
+pub const ESPIPE: isize = 29;
+/// Write to a pipe with no reader left
+pub const EPIPE: isize = 32;
+/// Resource deadlock would occur: locking would break the lock order, or a
+/// condvar wait would leave every thread blocked for good
+pub const EDEADLK: isize = 35;
+/// Timed out, e.g. a condvar wait not signalled in time
+pub const ETIMEDOUT: isize = 110;
+/// State not recoverable, e.g. the deadlock detector's bookkeeping
+/// contradicting itself
+pub const ENOTRECOVERABLE: isize = 131;
+
+// Results of the deadlock checks, also returned negated. The lab interface
+// fixes `-0xdead` for both mutexes and semaphores, semaphores only report
+// their own code once `DEADLOCK_CODES` is enabled. A lock order violation
+// and a condvar wait stalling the process under `CONDVAR_STALL` return
+// `-EDEADLK`.
+
+/// Locking the mutex would close a cycle of waiting threads
+pub const EDEADMUTEX: isize = 0xdead;
+/// Granting the semaphore request would leave an unsafe state
+pub const EDEADSEM: isize = 0xdeae;
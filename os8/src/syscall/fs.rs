@@ -1,83 +1,193 @@
 //! File and filesystem-related syscalls
 
+use crate::config::MAX_FD;
 use crate::fs::make_pipe;
 use crate::fs::open_file;
+use crate::fs::read_dir;
 use crate::fs::OpenFlags;
 use crate::fs::Stat;
+use crate::fs::{File, POLLNVAL};
 use crate::mm::translated_byte_buffer;
+use crate::mm::translated_ref;
 use crate::mm::translated_refmut;
 use crate::mm::translated_str;
 use crate::mm::UserBuffer;
+use crate::task::block_current_and_run_next;
 use crate::task::current_process;
+use crate::task::current_task;
 use crate::task::current_user_token;
+use crate::timer::{add_timer, get_time_ms};
 use alloc::sync::Arc;
+use alloc::vec::Vec;
+use easy_fs::DIRENT_SZ;
 
+use super::errno::{EAGAIN, EBADF, EEXIST, EINTR, EINVAL, ENOENT, EPIPE, ESPIPE};
+
+/// Reference points of [`sys_lseek`]
+pub const SEEK_SET: usize = 0;
+pub const SEEK_CUR: usize = 1;
+pub const SEEK_END: usize = 2;
+
+/// Write up to `len` bytes from `buf` to `fd`, return how many were written.
+/// It can be fewer, e.g. for a non-blocking pipe with little room left or
+/// when the read end is closed midway, so callers loop for the rest.
+/// Return -EBADF if `fd` is not open or not open for writing.
 pub fn sys_write(fd: usize, buf: *const u8, len: usize) -> isize {
     let token = current_user_token();
     let process = current_process();
     let inner = process.inner_exclusive_access();
-    if fd >= inner.fd_table.len() {
-        return -1;
+    let file = match inner.fd_table.get(fd) {
+        Some(Some(file)) => file.clone(),
+        _ => return -EBADF,
+    };
+    // release current process TCB manually to avoid multi-borrow
+    drop(inner);
+    if !file.writable() {
+        return -EBADF;
     }
-    if let Some(file) = &inner.fd_table[fd] {
-        let file = file.clone();
-        // release current process TCB manually to avoid multi-borrow
-        drop(inner);
-        file.write(UserBuffer::new(translated_byte_buffer(token, buf, len))) as isize
-    } else {
-        -1
+    let written = file.write(UserBuffer::new(translated_byte_buffer(token, buf, len)));
+    if written == 0 && len > 0 && file.broken_pipe() {
+        return -EPIPE;
     }
+    if written == 0 && len > 0 && file.would_block() {
+        return -EAGAIN;
+    }
+    written as isize
 }
 
+/// Read up to `len` bytes of `fd` to `buf`, return how many were read.
+/// It can be fewer, e.g. near the end of a file or for a non-blocking pipe
+/// holding less, and 0 at the end of a file or of a pipe whose write ends
+/// are all closed. Return -EBADF if `fd` is not open or not open for reading.
 pub fn sys_read(fd: usize, buf: *const u8, len: usize) -> isize {
     let token = current_user_token();
     let process = current_process();
     let inner = process.inner_exclusive_access();
-    if fd >= inner.fd_table.len() {
-        return -1;
+    let file = match inner.fd_table.get(fd) {
+        Some(Some(file)) => file.clone(),
+        _ => return -EBADF,
+    };
+    // release current process TCB manually to avoid multi-borrow
+    drop(inner);
+    if !file.readable() {
+        return -EBADF;
     }
-    if let Some(file) = &inner.fd_table[fd] {
-        let file = file.clone();
-        // release current process TCB manually to avoid multi-borrow
-        drop(inner);
-        file.read(UserBuffer::new(translated_byte_buffer(token, buf, len))) as isize
-    } else {
-        -1
+    let read = file.read(UserBuffer::new(translated_byte_buffer(token, buf, len)));
+    if read == 0 && len > 0 && file.would_block() {
+        return -EAGAIN;
     }
+    read as isize
 }
 
+/// Open a file, return its fd or -1 if it cannot be opened.
+/// `APPEND` needs write access, -EINVAL is returned for a read-only open.
+/// With `CREATE | EXCL`, return -EEXIST if the file already exists.
 pub fn sys_open(path: *const u8, flags: u32) -> isize {
     let process = current_process();
     let token = current_user_token();
     let path = translated_str(token, path);
-    if let Some(inode) = open_file(path.as_str(), OpenFlags::from_bits(flags).unwrap()) {
+    let flags = OpenFlags::from_bits(flags).unwrap();
+    if flags.contains(OpenFlags::APPEND) && !flags.intersects(OpenFlags::WRONLY | OpenFlags::RDWR) {
+        return -EINVAL;
+    }
+    if let Some(inode) = open_file(path.as_str(), flags) {
         let mut inner = process.inner_exclusive_access();
         let fd = inner.alloc_fd();
         inner.fd_table[fd] = Some(inode);
         fd as isize
+    } else if flags.contains(OpenFlags::CREATE | OpenFlags::EXCL) {
+        // creating fails only when the file is there
+        -EEXIST
     } else {
         -1
     }
 }
 
-pub fn sys_close(fd: usize) -> isize {
+/// Move the cursor of `fd` to `offset` relative to `whence`.
+/// Return the new absolute offset, -ESPIPE if the file is not seekable
+/// and -EINVAL for a bad `whence` or a result that is negative or does not
+/// fit in an `isize`.
+pub fn sys_lseek(fd: usize, offset: isize, whence: usize) -> isize {
     let process = current_process();
-    let mut inner = process.inner_exclusive_access();
-    if fd >= inner.fd_table.len() {
-        return -1;
+    let inner = process.inner_exclusive_access();
+    let file = match inner.fd_table.get(fd) {
+        Some(Some(file)) => file.clone(),
+        _ => return -EBADF,
+    };
+    // release current process TCB manually to avoid multi-borrow
+    drop(inner);
+    if !file.seekable() {
+        return -ESPIPE;
     }
-    if inner.fd_table[fd].is_none() {
-        return -1;
+    let base = match whence {
+        SEEK_SET => 0,
+        SEEK_CUR => file.offset(),
+        SEEK_END => file.size(),
+        _ => return -EINVAL,
+    };
+    let new_offset = match (base as isize).checked_add(offset) {
+        Some(new_offset) if new_offset >= 0 => new_offset,
+        _ => return -EINVAL,
+    };
+    file.seek(new_offset as usize);
+    new_offset
+}
+
+/// Copy at most `count` entries of directory `path` to `buf`, each one laid
+/// out as a 32-byte easy-fs `DirEntry`: a nul-padded name and an inode number.
+/// Return the number of entries copied, or -ENOENT if `path` is not a directory.
+pub fn sys_readdir(path: *const u8, buf: *mut u8, count: usize) -> isize {
+    let token = current_user_token();
+    let path = translated_str(token, path);
+    let entries = match read_dir(path.as_str()) {
+        Some(entries) => entries,
+        None => return -ENOENT,
+    };
+    let n = entries.len().min(count);
+    let user_buf = UserBuffer::new(translated_byte_buffer(token, buf, n * DIRENT_SZ));
+    let bytes = entries[..n].iter().flat_map(|entry| entry.as_bytes().iter());
+    for (byte_ref, byte) in user_buf.into_iter().zip(bytes) {
+        unsafe {
+            *byte_ref = *byte;
+        }
     }
-    inner.fd_table[fd].take();
+    n as isize
+}
+
+/// Close `fd`, return -EBADF if it is not open, so closing twice fails.
+/// The file itself is closed once its last fd is, e.g. a pipe end then
+/// lets the other side see EOF or a broken pipe.
+pub fn sys_close(fd: usize) -> isize {
+    let process = current_process();
+    let mut inner = process.inner_exclusive_access();
+    let file = match inner.fd_table.get_mut(fd).and_then(|slot| slot.take()) {
+        Some(file) => file,
+        None => return -EBADF,
+    };
+    // a pipe end wakes the other side when dropped, not under our borrow
+    drop(inner);
+    drop(file);
     0
 }
 
 pub fn sys_pipe(pipe: *mut usize) -> isize {
+    sys_pipe2(pipe, 0)
+}
+
+/// Create a pipe like [`sys_pipe`]. With `OpenFlags::NONBLOCK` both ends are
+/// non-blocking: reads on an empty pipe and writes on a full one transfer
+/// what they can, -EAGAIN if that is nothing. Dup'd fds share the flag of
+/// their end. Return -EINVAL for any other flag.
+pub fn sys_pipe2(pipe: *mut usize, flags: u32) -> isize {
+    let nonblock = match OpenFlags::from_bits(flags) {
+        Some(OpenFlags::NONBLOCK) => true,
+        Some(flags) if flags.is_empty() => false,
+        _ => return -EINVAL,
+    };
     let process = current_process();
     let token = current_user_token();
     let mut inner = process.inner_exclusive_access();
-    let (pipe_read, pipe_write) = make_pipe();
+    let (pipe_read, pipe_write) = make_pipe(nonblock);
     let read_fd = inner.alloc_fd();
     inner.fd_table[read_fd] = Some(pipe_read);
     let write_fd = inner.alloc_fd();
@@ -87,22 +197,117 @@ pub fn sys_pipe(pipe: *mut usize) -> isize {
     0
 }
 
+/// One fd of [`sys_poll`]
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct PollFd {
+    pub fd: usize,
+    /// `POLLIN` and/or `POLLOUT`
+    pub events: usize,
+    /// Filled in with the events that are ready
+    pub revents: usize,
+}
+
+/// Wait until one of the `nfds` fds at `fds` is ready for its events, or
+/// `timeout_ms` has elapsed, forever if it is negative and not at all if it
+/// is 0. A pipe end is ready once data or space is available, other files
+/// always are; a closed other end and a bad fd are reported as ready too.
+/// Return how many fds have `revents` filled in, 0 on timeout, or -EINTR
+/// when interrupted by a fatal signal.
+pub fn sys_poll(fds: *mut PollFd, nfds: usize, timeout_ms: isize) -> isize {
+    let token = current_user_token();
+    let task = current_task().unwrap();
+    let deadline = get_time_ms().saturating_add(timeout_ms as usize);
+    loop {
+        let process = current_process();
+        let inner = process.inner_exclusive_access();
+        let files: Vec<Option<Arc<dyn File + Send + Sync>>> = (0..nfds)
+            .map(|i| {
+                let fd = translated_ref(token, unsafe { fds.add(i) }).fd;
+                inner.fd_table.get(fd).cloned().flatten()
+            })
+            .collect();
+        drop(inner);
+        let mut ready = 0;
+        for (i, file) in files.iter().enumerate() {
+            let poll_fd = translated_refmut(token, unsafe { fds.add(i) });
+            poll_fd.revents = match file {
+                Some(file) => file.poll(poll_fd.events),
+                None => POLLNVAL,
+            };
+            ready += (poll_fd.revents != 0) as usize;
+        }
+        if ready > 0 || timeout_ms == 0 || (timeout_ms > 0 && get_time_ms() >= deadline) {
+            return ready as isize;
+        }
+        if process.inner_exclusive_access().signals.is_fatal() {
+            return -EINTR;
+        }
+        // any file or the timer wakes us up through `wake_blocked`, which
+        // cancels the timer so that it cannot wake us up a second time
+        for file in files.iter().flatten() {
+            file.add_poller(Arc::clone(&task));
+        }
+        if timeout_ms > 0 {
+            add_timer(deadline, Arc::clone(&task));
+        }
+        block_current_and_run_next();
+        for file in files.iter().flatten() {
+            file.remove_poller(&task);
+        }
+    }
+}
+
+/// Duplicate `fd` to the lowest free fd, both share the file and its offset.
+/// Return the new fd or -EBADF if `fd` is not open.
 pub fn sys_dup(fd: usize) -> isize {
     let process = current_process();
     let mut inner = process.inner_exclusive_access();
     if fd >= inner.fd_table.len() {
-        return -1;
+        return -EBADF;
     }
     if inner.fd_table[fd].is_none() {
-        return -1;
+        return -EBADF;
     }
     let new_fd = inner.alloc_fd();
     inner.fd_table[new_fd] = Some(Arc::clone(inner.fd_table[fd].as_ref().unwrap()));
     new_fd as isize
 }
 
-pub fn sys_fstat(_fd: usize, _st: *mut Stat) -> isize {
-    -1
+/// Make `new_fd` refer to the file of `old_fd`, closing `new_fd` first if it
+/// is open. Return `new_fd`, or -EBADF if `old_fd` is not open or `new_fd`
+/// is out of range.
+pub fn sys_dup2(old_fd: usize, new_fd: usize) -> isize {
+    let process = current_process();
+    let mut inner = process.inner_exclusive_access();
+    let file = match inner.fd_table.get(old_fd) {
+        Some(Some(file)) => Arc::clone(file),
+        _ => return -EBADF,
+    };
+    if new_fd >= MAX_FD {
+        return -EBADF;
+    }
+    if inner.fd_table.len() <= new_fd {
+        inner.fd_table.resize(new_fd + 1, None);
+    }
+    // the old file of `new_fd` is dropped, i.e. closed, here
+    inner.fd_table[new_fd] = Some(file);
+    new_fd as isize
+}
+
+/// Copy the metadata of `fd` to `st`, return -EBADF if `fd` is not open
+pub fn sys_fstat(fd: usize, st: *mut Stat) -> isize {
+    let token = current_user_token();
+    let process = current_process();
+    let inner = process.inner_exclusive_access();
+    let file = match inner.fd_table.get(fd) {
+        Some(Some(file)) => file.clone(),
+        _ => return -EBADF,
+    };
+    // release current process TCB manually to avoid multi-borrow
+    drop(inner);
+    *translated_refmut(token, st) = file.stat();
+    0
 }
 
 pub fn sys_linkat(_old_name: *const u8, _new_name: *const u8) -> isize {
@@ -16,14 +16,19 @@ const SYSCALL_LINKAT: usize = 37;
 const SYSCALL_OPEN: usize = 56;
 const SYSCALL_CLOSE: usize = 57;
 const SYSCALL_PIPE: usize = 59;
+const SYSCALL_LSEEK: usize = 62;
 const SYSCALL_READ: usize = 63;
 const SYSCALL_WRITE: usize = 64;
 const SYSCALL_FSTAT: usize = 80;
 const SYSCALL_EXIT: usize = 93;
+const SYSCALL_EXIT_GROUP: usize = 94;
 const SYSCALL_SLEEP: usize = 101;
 const SYSCALL_YIELD: usize = 124;
+const SYSCALL_KILL: usize = 129;
+const SYSCALL_SIGPENDING: usize = 136;
 const SYSCALL_GET_TIME: usize = 169;
 const SYSCALL_GETPID: usize = 172;
+const SYSCALL_GETPPID: usize = 173;
 const SYSCALL_GETTID: usize = 178;
 const SYSCALL_FORK: usize = 220;
 const SYSCALL_EXEC: usize = 221;
@@ -45,20 +50,88 @@ const SYSCALL_SEMAPHORE_DOWN: usize = 470;
 const SYSCALL_CONDVAR_CREATE: usize = 471;
 const SYSCALL_CONDVAR_SIGNAL: usize = 472;
 const SYSCALL_CONDVAR_WAIT: usize = 473;
+const SYSCALL_TLS_SET: usize = 474;
+const SYSCALL_TLS_GET: usize = 475;
+const SYSCALL_THREAD_BLOCKED_ON: usize = 476;
+const SYSCALL_SLEEP_UNTIL: usize = 477;
+const SYSCALL_SET_TIMESLICE: usize = 478;
+const SYSCALL_TASK_STATS: usize = 479;
+const SYSCALL_THREAD_COUNT: usize = 480;
+const SYSCALL_THREAD_LIST: usize = 481;
+const SYSCALL_YIELD_TO: usize = 482;
+const SYSCALL_SEMAPHORE_GETVALUE: usize = 483;
+const SYSCALL_READDIR: usize = 484;
+const SYSCALL_MUTEX_SET_RANK: usize = 485;
+const SYSCALL_DUP2: usize = 486;
+const SYSCALL_SET_AGING_INTERVAL: usize = 487;
+const SYSCALL_SYNC_CHECKPOINT: usize = 488;
+const SYSCALL_SYNC_RESTORE: usize = 489;
+const SYSCALL_MUTEX_KIND: usize = 490;
+const SYSCALL_MMAP_FILE: usize = 491;
+const SYSCALL_SEMAPHORE_UP_N: usize = 492;
+const SYSCALL_SEMAPHORE_DOWN_N: usize = 493;
+const SYSCALL_GETRUSAGE: usize = 494;
+const SYSCALL_SEMAPHORE_OP: usize = 495;
+const SYSCALL_THREAD_SLEEP_REMAINING: usize = 496;
+const SYSCALL_SYNC_TRACE_READ: usize = 497;
+const SYSCALL_CONDVAR_WAIT_WHILE: usize = 498;
+const SYSCALL_SET_CPU_LIMIT: usize = 499;
+const SYSCALL_CLEAR_CPU_LIMIT: usize = 500;
+const SYSCALL_CONDVAR_SIGNAL_N: usize = 501;
+const SYSCALL_FUTEX_WAIT: usize = 502;
+const SYSCALL_FUTEX_WAKE: usize = 503;
+const SYSCALL_SET_SYNC_QUOTA: usize = 504;
+const SYSCALL_DEADLOCK_STATS: usize = 505;
+const SYSCALL_CONDVAR_SIGNAL_TID: usize = 506;
+const SYSCALL_ENABLE_SCHED_TRACE: usize = 507;
+const SYSCALL_SCHED_TRACE: usize = 508;
+const SYSCALL_MUTEX_UNLOCK_TO: usize = 509;
+const SYSCALL_PIPE2: usize = 510;
+const SYSCALL_SCHED_SETPOLICY: usize = 511;
+const SYSCALL_POLL: usize = 512;
+const SYSCALL_MUTEX_SET_CEILING: usize = 513;
+const SYSCALL_SEMAPHORE_WAITERS: usize = 514;
+const SYSCALL_PROC_THREADS: usize = 515;
+const SYSCALL_SCHED_INFO: usize = 516;
+const SYSCALL_SEMAPHORE_AUDIT: usize = 517;
+const SYSCALL_THREAD_CREATE_SUSPENDED: usize = 518;
+const SYSCALL_THREAD_RESUME: usize = 519;
+const SYSCALL_CONDVAR_DESTROY: usize = 520;
+const SYSCALL_MQ_CREATE: usize = 521;
+const SYSCALL_MQ_SEND: usize = 522;
+const SYSCALL_MQ_RECV: usize = 523;
+const SYSCALL_GET_TICKS: usize = 524;
+const SYSCALL_SYNC_WAIT_TIME: usize = 525;
+const SYSCALL_MUTEX_SPINLOCK: usize = 526;
+const SYSCALL_MUTEX_STATS: usize = 527;
+const SYSCALL_SET_AFFINITY: usize = 528;
+const SYSCALL_GET_AFFINITY: usize = 529;
+const SYSCALL_MQ_DESTROY: usize = 530;
+const SYSCALL_CHECK_DEADLOCK: usize = 531;
+const SYSCALL_SCHED_SET_SEED: usize = 532;
+const SYSCALL_CONDVAR_TIMEDWAIT: usize = 533;
+const SYSCALL_MEMBARRIER: usize = 534;
+const SYSCALL_MUTEX_TRYLOCK_CHECKED: usize = 535;
+const SYSCALL_THREAD_SET_WAITREASON: usize = 536;
+const SYSCALL_THREAD_SUSPEND: usize = 537;
+const SYSCALL_CAS: usize = 538;
 
+mod errno;
 mod fs;
 pub mod process;
 mod sync;
 mod thread;
 
 use crate::fs::Stat;
+use crate::sync::SyncEvent;
+use crate::task::{DeadlockStats, MutexStats, SchedEvent};
 use fs::*;
 use process::*;
 use sync::*;
 use thread::*;
 
 /// handle syscall exception with `syscall_id` and other arguments
-pub fn syscall(syscall_id: usize, args: [usize; 4]) -> isize {
+pub fn syscall(syscall_id: usize, args: [usize; 6]) -> isize {
     match syscall_id {
         SYSCALL_DUP => sys_dup(args[0]),
         SYSCALL_LINKAT => sys_linkat(args[1] as *const u8, args[3] as *const u8),
@@ -66,13 +139,18 @@ pub fn syscall(syscall_id: usize, args: [usize; 4]) -> isize {
         SYSCALL_OPEN => sys_open(args[1] as *const u8, args[2] as u32),
         SYSCALL_CLOSE => sys_close(args[0]),
         SYSCALL_PIPE => sys_pipe(args[0] as *mut usize),
+        SYSCALL_LSEEK => sys_lseek(args[0], args[1] as isize, args[2]),
         SYSCALL_READ => sys_read(args[0], args[1] as *const u8, args[2]),
         SYSCALL_WRITE => sys_write(args[0], args[1] as *const u8, args[2]),
         SYSCALL_FSTAT => sys_fstat(args[0], args[1] as *mut Stat),
         SYSCALL_EXIT => sys_exit(args[0] as i32),
+        SYSCALL_EXIT_GROUP => sys_exit_group(args[0] as i32),
         SYSCALL_SLEEP => sys_sleep(args[0]),
         SYSCALL_YIELD => sys_yield(),
+        SYSCALL_KILL => sys_kill(args[0], args[1]),
+        SYSCALL_SIGPENDING => sys_sigpending(),
         SYSCALL_GETPID => sys_getpid(),
+        SYSCALL_GETPPID => sys_getppid(),
         SYSCALL_GETTID => sys_gettid(),
         SYSCALL_FORK => sys_fork(),
         SYSCALL_EXEC => sys_exec(args[0] as *const u8, args[1] as *const usize),
@@ -83,9 +161,9 @@ pub fn syscall(syscall_id: usize, args: [usize; 4]) -> isize {
         SYSCALL_SET_PRIORITY => sys_set_priority(args[0] as isize),
         SYSCALL_TASK_INFO => sys_task_info(args[0] as *mut TaskInfo),
         SYSCALL_SPAWN => sys_spawn(args[0] as *const u8),
-        SYSCALL_THREAD_CREATE => sys_thread_create(args[0], args[1]),
+        SYSCALL_THREAD_CREATE => sys_thread_create(args[0], args[1], args[2]),
         SYSCALL_WAITTID => sys_waittid(args[0]) as isize,
-        SYSCALL_MUTEX_CREATE => sys_mutex_create(args[0] == 1),
+        SYSCALL_MUTEX_CREATE => sys_mutex_create(args[0]),
         SYSCALL_MUTEX_LOCK => sys_mutex_lock(args[0]),
         SYSCALL_MUTEX_UNLOCK => sys_mutex_unlock(args[0]),
         SYSCALL_SEMAPHORE_CREATE => sys_semaphore_create(args[0]),
@@ -95,6 +173,73 @@ pub fn syscall(syscall_id: usize, args: [usize; 4]) -> isize {
         SYSCALL_CONDVAR_CREATE => sys_condvar_create(args[0]),
         SYSCALL_CONDVAR_SIGNAL => sys_condvar_signal(args[0]),
         SYSCALL_CONDVAR_WAIT => sys_condvar_wait(args[0], args[1]),
+        SYSCALL_TLS_SET => sys_tls_set(args[0], args[1]),
+        SYSCALL_TLS_GET => sys_tls_get(args[0]),
+        SYSCALL_THREAD_BLOCKED_ON => sys_thread_blocked_on(args[0]),
+        SYSCALL_SLEEP_UNTIL => sys_sleep_until(args[0]),
+        SYSCALL_SET_TIMESLICE => sys_set_timeslice(args[0]),
+        SYSCALL_TASK_STATS => sys_task_stats(args[0], args[1] as *mut TaskStats),
+        SYSCALL_THREAD_COUNT => sys_thread_count(),
+        SYSCALL_THREAD_LIST => sys_thread_list(args[0] as *mut usize, args[1]),
+        SYSCALL_YIELD_TO => sys_yield_to(args[0]),
+        SYSCALL_SEMAPHORE_GETVALUE => sys_semaphore_getvalue(args[0]),
+        SYSCALL_READDIR => sys_readdir(args[0] as *const u8, args[1] as *mut u8, args[2]),
+        SYSCALL_MUTEX_SET_RANK => sys_mutex_set_rank(args[0], args[1]),
+        SYSCALL_DUP2 => sys_dup2(args[0], args[1]),
+        SYSCALL_SET_AGING_INTERVAL => sys_set_aging_interval(args[0]),
+        SYSCALL_SYNC_CHECKPOINT => sys_sync_checkpoint(args[0] as *mut u8, args[1]),
+        SYSCALL_SYNC_RESTORE => sys_sync_restore(args[0] as *const u8, args[1]),
+        SYSCALL_MUTEX_KIND => sys_mutex_kind(args[0]),
+        SYSCALL_MMAP_FILE => sys_mmap_file(args[0], args[1], args[2], args[3], args[4]),
+        SYSCALL_SEMAPHORE_UP_N => sys_semaphore_up_n(args[0], args[1]),
+        SYSCALL_SEMAPHORE_DOWN_N => sys_semaphore_down_n(args[0], args[1]),
+        SYSCALL_GETRUSAGE => sys_getrusage(args[0] as *mut RUsage),
+        SYSCALL_SEMAPHORE_OP => sys_semaphore_op(args[0] as *const SemOp, args[1]),
+        SYSCALL_THREAD_SLEEP_REMAINING => sys_thread_sleep_remaining(args[0]),
+        SYSCALL_SYNC_TRACE_READ => sys_sync_trace_read(args[0] as *mut SyncEvent, args[1]),
+        SYSCALL_CONDVAR_WAIT_WHILE => {
+            sys_condvar_wait_while(args[0], args[1], args[2] as *const usize, args[3])
+        }
+        SYSCALL_SET_CPU_LIMIT => sys_set_cpu_limit(args[0], args[1]),
+        SYSCALL_CLEAR_CPU_LIMIT => sys_clear_cpu_limit(args[0]),
+        SYSCALL_CONDVAR_SIGNAL_N => sys_condvar_signal_n(args[0], args[1]),
+        SYSCALL_FUTEX_WAIT => sys_futex_wait(args[0], args[1] as u32),
+        SYSCALL_FUTEX_WAKE => sys_futex_wake(args[0], args[1]),
+        SYSCALL_SET_SYNC_QUOTA => sys_set_sync_quota(args[0], args[1]),
+        SYSCALL_DEADLOCK_STATS => sys_deadlock_stats(args[0] as *mut DeadlockStats),
+        SYSCALL_CONDVAR_SIGNAL_TID => sys_condvar_signal_tid(args[0], args[1]),
+        SYSCALL_ENABLE_SCHED_TRACE => sys_enable_sched_trace(args[0]),
+        SYSCALL_SCHED_TRACE => sys_sched_trace(args[0] as *mut SchedEvent, args[1]),
+        SYSCALL_MUTEX_UNLOCK_TO => sys_mutex_unlock_to(args[0], args[1]),
+        SYSCALL_PIPE2 => sys_pipe2(args[0] as *mut usize, args[1] as u32),
+        SYSCALL_SCHED_SETPOLICY => sys_sched_setpolicy(args[0]),
+        SYSCALL_POLL => sys_poll(args[0] as *mut PollFd, args[1], args[2] as isize),
+        SYSCALL_MUTEX_SET_CEILING => sys_mutex_set_ceiling(args[0], args[1]),
+        SYSCALL_SEMAPHORE_WAITERS => sys_semaphore_waiters(args[0], args[1] as *mut usize, args[2]),
+        SYSCALL_PROC_THREADS => sys_proc_threads(args[0], args[1] as *mut ThreadEntry, args[2]),
+        SYSCALL_SCHED_INFO => sys_sched_info(args[0] as *mut SchedInfo),
+        SYSCALL_SEMAPHORE_AUDIT => sys_semaphore_audit(args[0], args[1] as *mut usize, args[2]),
+        SYSCALL_THREAD_CREATE_SUSPENDED => sys_thread_create_suspended(args[0], args[1], args[2]),
+        SYSCALL_THREAD_RESUME => sys_thread_resume(args[0]),
+        SYSCALL_CONDVAR_DESTROY => sys_condvar_destroy(args[0]),
+        SYSCALL_MQ_CREATE => sys_mq_create(args[0], args[1]),
+        SYSCALL_MQ_SEND => sys_mq_send(args[0], args[1] as *const u8, args[2]),
+        SYSCALL_MQ_RECV => sys_mq_recv(args[0], args[1] as *mut u8, args[2]),
+        SYSCALL_GET_TICKS => sys_get_ticks(),
+        SYSCALL_SYNC_WAIT_TIME => sys_sync_wait_time(args[0], args[1]),
+        SYSCALL_MUTEX_SPINLOCK => sys_mutex_spinlock(args[0], args[1]),
+        SYSCALL_MUTEX_STATS => sys_mutex_stats(args[0], args[1] as *mut MutexStats, args[2]),
+        SYSCALL_SET_AFFINITY => sys_set_affinity(args[0], args[1]),
+        SYSCALL_GET_AFFINITY => sys_get_affinity(args[0]),
+        SYSCALL_MQ_DESTROY => sys_mq_destroy(args[0]),
+        SYSCALL_CHECK_DEADLOCK => sys_check_deadlock(),
+        SYSCALL_SCHED_SET_SEED => sys_sched_set_seed(args[0]),
+        SYSCALL_CONDVAR_TIMEDWAIT => sys_condvar_timedwait(args[0], args[1], args[2]),
+        SYSCALL_MEMBARRIER => sys_membarrier(args[0]),
+        SYSCALL_MUTEX_TRYLOCK_CHECKED => sys_mutex_trylock_checked(args[0]),
+        SYSCALL_THREAD_SET_WAITREASON => sys_thread_set_waitreason(args[0] as *const u8, args[1]),
+        SYSCALL_THREAD_SUSPEND => sys_thread_suspend(args[0]),
+        SYSCALL_CAS => sys_cas(args[0], args[1] as u32, args[2] as u32),
         _ => panic!("Unsupported syscall_id: {}", syscall_id),
     }
 }
@@ -1,17 +1,24 @@
 //! Process management syscalls
 
-use crate::config::MAX_SYSCALL_NUM;
+use crate::config::{MAX_SYSCALL_NUM, MAX_TIMESLICE_MS, MIN_TIMESLICE_MS, PAGE_SIZE};
+use crate::config::{MMAP_MAX_LEN, USER_SPACE_END};
 use crate::fs::{open_file, OpenFlags};
 use crate::mm::{translated_ref, translated_refmut, translated_str, PageTable, VirtAddr};
+use crate::mm::{MapPermission, VirtPageNum};
 use crate::task::{
-    current_process, current_task, current_user_token, exit_current_and_run_next,
-    suspend_current_and_run_next, TaskStatus,
+    blocked_task_count, current_process, current_task, current_user_token,
+    exit_current_and_run_next, interrupt_blocked_tasks, pid2process, ready_task_count,
+    sched_trace_enable, sched_trace_recent, set_aging_interval, set_sched_policy, set_sched_seed,
+    suspend_current_and_run_next, SchedEvent, SchedPolicy, SignalFlags, TaskStatus, INITPROC,
 };
-use crate::timer::get_time_us;
+use crate::timer::{get_time_us, idle_time_us, pending_timers, timer_ticks};
 use alloc::string::String;
 use alloc::sync::Arc;
+use alloc::vec;
 use alloc::vec::Vec;
 
+use super::errno::{EACCES, EBADF, EINVAL, EPERM};
+
 #[repr(C)]
 #[derive(Debug)]
 pub struct TimeVal {
@@ -26,12 +33,62 @@ pub struct TaskInfo {
     pub time: usize,
 }
 
+/// System-wide scheduler load, filled by [`sys_sched_info`]
+#[repr(C)]
+#[derive(Clone, Copy, Default)]
+pub struct SchedInfo {
+    /// Threads in the ready queue, the running one not included
+    pub ready: usize,
+    /// Threads blocked, for whatever reason
+    pub blocked: usize,
+    /// Blocked threads that a timer will wake, sleeping or waiting with a
+    /// timeout
+    pub sleeping: usize,
+    /// Microseconds since boot the CPU spent idle, the only runnable thread
+    /// having yielded while others wait for a timer
+    pub idle_us: usize,
+}
+
+/// Resource usage of a process, filled by [`sys_getrusage`]
+#[repr(C)]
+#[derive(Clone, Copy, Default)]
+pub struct RUsage {
+    /// CPU time of all its threads in microseconds, time spent in the
+    /// kernel on their behalf included
+    pub utime_us: usize,
+    /// Switches away from a thread that blocked or gave up the CPU
+    pub nvcsw: usize,
+    /// Switches away from a thread whose time slice was used up
+    pub nivcsw: usize,
+    /// Page faults taken, each one still kills the faulting thread
+    pub page_faults: usize,
+    /// Peak number of frames backing its user memory
+    pub max_frames: usize,
+}
+
 pub fn sys_exit(exit_code: i32) -> ! {
     // debug!("[kernel] Application exited with code {}", exit_code);
     exit_current_and_run_next(exit_code);
     panic!("Unreachable in sys_exit!");
 }
 
+/// Terminate every thread of the current process with `exit_code`, unlike
+/// `sys_exit` which only ends the calling thread unless it is the main one.
+/// Blocked threads are woken as by a fatal signal, their waits fail and they
+/// exit on their way back to user mode, releasing what they hold. The main
+/// thread exiting reaps the process as usual.
+pub fn sys_exit_group(exit_code: i32) -> ! {
+    let process = current_process();
+    let mut inner = process.inner_exclusive_access();
+    inner.group_exit = Some(exit_code);
+    inner.signals.insert(SignalFlags::SIGKILL);
+    drop(inner);
+    interrupt_blocked_tasks(&process);
+    drop(process);
+    exit_current_and_run_next(exit_code);
+    panic!("Unreachable in sys_exit_group!");
+}
+
 /// current task gives up resources for other tasks
 pub fn sys_yield() -> isize {
     suspend_current_and_run_next();
@@ -42,6 +99,18 @@ pub fn sys_getpid() -> isize {
     current_task().unwrap().process.upgrade().unwrap().getpid() as isize
 }
 
+/// Return the pid of the parent process, -1 for initproc which has none.
+/// Orphans are adopted by initproc, pid 0, when their parent exits.
+pub fn sys_getppid() -> isize {
+    let process = current_process();
+    let inner = process.inner_exclusive_access();
+    inner
+        .parent
+        .as_ref()
+        .and_then(|parent| parent.upgrade())
+        .map_or(-1, |parent| parent.getpid() as isize)
+}
+
 /// Syscall Fork which returns 0 for child process and child_pid for parent process
 pub fn sys_fork() -> isize {
     let current_process = current_process();
@@ -57,12 +126,14 @@ pub fn sys_fork() -> isize {
     new_pid as isize
 }
 
-/// Syscall Exec which accepts the elf path
+/// Syscall Exec which accepts the elf path and a null-terminated array of
+/// argument strings (a null array means no arguments), return argc
 pub fn sys_exec(path: *const u8, mut args: *const usize) -> isize {
     let token = current_user_token();
     let path = translated_str(token, path);
+    // copy arguments out through the old page table before it is replaced
     let mut args_vec: Vec<String> = Vec::new();
-    loop {
+    while !args.is_null() {
         let arg_str_ptr = *translated_ref(token, args);
         if arg_str_ptr == 0 {
             break;
@@ -83,8 +154,11 @@ pub fn sys_exec(path: *const u8, mut args: *const usize) -> isize {
     }
 }
 
+/// Reap the exited child `pid` (any child if `pid` is -1), write its exit code
+/// to `exit_code_ptr` unless it is null and return its pid.
 /// If there is not a child process whose pid is same as given, return -1.
 /// Else if there is a child process but it is still running, return -2.
+/// A reaped child is no longer a child, so waiting for it again returns -1.
 pub fn sys_waitpid(pid: isize, exit_code_ptr: *mut i32) -> isize {
     let process = current_process();
     // find a child process
@@ -112,7 +186,9 @@ pub fn sys_waitpid(pid: isize, exit_code_ptr: *mut i32) -> isize {
         // ++++ temporarily access child TCB exclusively
         let exit_code = child.inner_exclusive_access().exit_code;
         // ++++ release child PCB
-        *translated_refmut(inner.memory_set.token(), exit_code_ptr) = exit_code;
+        if !exit_code_ptr.is_null() {
+            *translated_refmut(inner.memory_set.token(), exit_code_ptr) = exit_code;
+        }
         found_pid as isize
     } else {
         -2
@@ -120,6 +196,40 @@ pub fn sys_waitpid(pid: isize, exit_code_ptr: *mut i32) -> isize {
     // ---- release current PCB lock automatically
 }
 
+/// Send signal `signum` to process `pid`.
+/// Return -1 if there is no such process or the signal is not supported.
+pub fn sys_kill(pid: usize, signum: usize) -> isize {
+    let process = match pid2process(pid) {
+        Some(process) => process,
+        None => return -1,
+    };
+    let flag = match SignalFlags::from_signum(signum) {
+        Some(flag) => flag,
+        None => return -1,
+    };
+    // initproc adopts orphans, it must outlive every other process
+    if Arc::ptr_eq(&process, &INITPROC) && flag.is_fatal() {
+        return -1;
+    }
+    let mut inner = process.inner_exclusive_access();
+    inner.signals.insert(flag);
+    drop(inner);
+    if flag.is_fatal() {
+        interrupt_blocked_tasks(&process);
+    }
+    0
+}
+
+/// Return the pending signals of the current process as a bitmask,
+/// non-fatal ones are cleared once reported.
+pub fn sys_sigpending() -> isize {
+    let process = current_process();
+    let mut inner = process.inner_exclusive_access();
+    let pending = inner.signals;
+    inner.signals.remove(SignalFlags::SIGUSR1);
+    pending.bits() as isize
+}
+
 pub fn sys_get_time(_ts: *mut TimeVal, _tz: usize) -> isize {
     let _us = get_time_us();
     // unsafe {
@@ -135,12 +245,124 @@ pub fn sys_get_time(_ts: *mut TimeVal, _tz: usize) -> isize {
     0
 }
 
+/// Return the number of timer interrupts taken since boot, see
+/// [`timer_ticks`] for how they relate to `TICKS_PER_SEC`
+pub fn sys_get_ticks() -> isize {
+    timer_ticks() as isize
+}
+
 pub fn sys_task_info(_ti: *mut TaskInfo) -> isize {
     -1
 }
 
-pub fn sys_set_priority(_prio: isize) -> isize {
-    -1
+/// Set the stride priority of the current thread, it must be at least 2.
+/// Return the new priority, or -1 if it is out of range.
+pub fn sys_set_priority(prio: isize) -> isize {
+    if prio < 2 {
+        return -1;
+    }
+    let task = current_task().unwrap();
+    task.inner_exclusive_access().set_base_priority(prio as usize);
+    prio
+}
+
+/// Switch the system-wide scheduling policy, one of the [`SchedPolicy`] ids,
+/// the ready threads being kept. Return the previous policy, or -1 for an
+/// unknown one or `Seeded`, which [`sys_sched_set_seed`] switches to.
+///
+/// There is no privilege check: any process may call it and the policy
+/// stays in force for every process after it exits, so a caller is
+/// expected to switch back to `Stride` before it returns.
+pub fn sys_sched_setpolicy(policy: usize) -> isize {
+    match SchedPolicy::from_id(policy) {
+        Some(policy) => set_sched_policy(policy) as isize,
+        None => -1,
+    }
+}
+
+/// Switch the system to the seeded test scheduler: among the ready threads
+/// of the highest priority, the next to run is drawn from an LCG started at
+/// `seed`. Running the same program again with the same seed replays the
+/// interleaving at yields and blocking calls, preemption still depending
+/// on timing. Return the previous policy, switched back to with
+/// `sys_sched_setpolicy`.
+///
+/// The policy is system-wide, so the call is only there in kernels built
+/// with the `sched-seed` feature and returns -EPERM otherwise.
+pub fn sys_sched_set_seed(seed: usize) -> isize {
+    if !cfg!(feature = "sched-seed") {
+        return -EPERM;
+    }
+    set_sched_seed(seed as u64) as isize
+}
+
+/// Write the number of ready, blocked and sleeping threads of the whole
+/// system and the time the CPU idled to `info`
+pub fn sys_sched_info(info: *mut SchedInfo) -> isize {
+    let sched_info = SchedInfo {
+        ready: ready_task_count(),
+        blocked: blocked_task_count(),
+        sleeping: pending_timers(),
+        idle_us: idle_time_us(),
+    };
+    *translated_refmut(current_user_token(), info) = sched_info;
+    0
+}
+
+/// Set how many ticks a ready thread may wait before it is boosted ahead of
+/// all others, 0 disables aging. The setting is system-wide.
+/// Return the previous interval.
+pub fn sys_set_aging_interval(ticks: usize) -> isize {
+    set_aging_interval(ticks) as isize
+}
+
+/// Set the scheduling quantum of the current thread in milliseconds.
+/// The value is clamped to `[MIN_TIMESLICE_MS, MAX_TIMESLICE_MS]`,
+/// return the quantum actually used.
+pub fn sys_set_timeslice(ms: usize) -> isize {
+    let ms = ms.clamp(MIN_TIMESLICE_MS, MAX_TIMESLICE_MS);
+    let task = current_task().unwrap();
+    task.inner_exclusive_access().timeslice_ms = ms;
+    ms as isize
+}
+
+/// Turn the system-wide trace of context switches on or off, turning it on
+/// starts an empty trace
+pub fn sys_enable_sched_trace(enabled: usize) -> isize {
+    sched_trace_enable(enabled != 0);
+    0
+}
+
+/// Copy the most recent `len` context switches at most to `buf`, oldest
+/// first, return how many were copied
+pub fn sys_sched_trace(buf: *mut SchedEvent, len: usize) -> isize {
+    let token = current_user_token();
+    let events = sched_trace_recent(len);
+    for (i, event) in events.iter().enumerate() {
+        *translated_refmut(token, unsafe { buf.add(i) }) = *event;
+    }
+    events.len() as isize
+}
+
+/// Write the resource usage of the current process to `usage`
+pub fn sys_getrusage(usage: *mut RUsage) -> isize {
+    let process = current_process();
+    let inner = process.inner_exclusive_access();
+    let now_us = get_time_us();
+    let mut rusage = inner.rusage;
+    // exited threads are already in the counters
+    for task in inner.tasks.iter().flatten() {
+        let task_inner = task.inner_exclusive_access();
+        if task_inner.exit_code.is_none() {
+            rusage.utime_us += task_inner.run_time_us;
+            if task_inner.task_status == TaskStatus::Running {
+                rusage.utime_us += now_us - task_inner.status_since_us;
+            }
+        }
+    }
+    rusage.max_frames = inner.memory_set.peak_frames();
+    *translated_refmut(inner.memory_set.token(), usage) = rusage;
+    0
 }
 
 pub fn sys_mmap(_start: usize, _len: usize, _port: usize) -> isize {
@@ -151,6 +373,63 @@ pub fn sys_munmap(_start: usize, _len: usize) -> isize {
     -1
 }
 
+/// Map `len` bytes of file `fd` from `offset` at `start`, `prot` taking the
+/// bits of `sys_mmap` (1 = R, 2 = W, 4 = X).
+///
+/// Only private read-only mappings are supported: the file pages are copied
+/// into fresh frames at map time, so later writes to the file are not seen
+/// and `prot` must not ask for W. The part past EOF reads as zeros.
+/// Return 0 on success, -EBADF for a bad fd, -EACCES if the file is not open
+/// for reading, and -EINVAL for an unaligned `start` or `offset`, an offset
+/// past EOF, a file that cannot be seeked, a bad `prot`, a `len` above
+/// `MMAP_MAX_LEN`, a range past `USER_SPACE_END` or one overlapping a mapped
+/// page.
+pub fn sys_mmap_file(start: usize, len: usize, prot: usize, fd: usize, offset: usize) -> isize {
+    let process = current_process();
+    let mut inner = process.inner_exclusive_access();
+    let file = match inner.fd_table.get(fd) {
+        Some(Some(file)) => file.clone(),
+        _ => return -EBADF,
+    };
+    if !file.readable() {
+        return -EACCES;
+    }
+    if !file.seekable() || offset > file.size() {
+        return -EINVAL;
+    }
+    if start % PAGE_SIZE != 0 || offset % PAGE_SIZE != 0 || len == 0 || len > MMAP_MAX_LEN {
+        return -EINVAL;
+    }
+    if prot & !0x5 != 0 || prot & 0x1 == 0 {
+        return -EINVAL;
+    }
+    let end = match start.checked_add(len) {
+        Some(end) if end <= USER_SPACE_END => end,
+        _ => return -EINVAL,
+    };
+    let start_vpn: VirtPageNum = VirtAddr::from(start).floor();
+    let end_vpn: VirtPageNum = VirtAddr::from(end).ceil();
+    if !inner.memory_set.is_unmapped(start_vpn, end_vpn) {
+        return -EINVAL;
+    }
+    let mut data = vec![0u8; len.min(file.size() - offset)];
+    let mut read = 0;
+    while read < data.len() {
+        match file.read_at(offset + read, &mut data[read..]) {
+            0 => break,
+            n => read += n,
+        }
+    }
+    let permission = MapPermission::from_bits((prot << 1) as u8).unwrap() | MapPermission::U;
+    inner.memory_set.insert_framed_area_with_data(
+        start.into(),
+        end.into(),
+        permission,
+        &data[..read],
+    );
+    0
+}
+
 //
 // ALERT: 注意在实现 SPAWN 时不需要复制父进程地址空间，SPAWN != FORK + EXEC
 pub fn sys_spawn(_path: *const u8) -> isize {
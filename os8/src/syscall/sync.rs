@@ -1,28 +1,165 @@
-use crate::sync::{Condvar, Mutex, MutexBlocking, MutexSpin, Semaphore};
-use crate::task::{block_current_and_run_next, current_process, current_task};
+//! Synchronization syscalls
+//!
+//! A mutex, semaphore or condvar id that is out of range or was freed is
+//! refused with -EINVAL, unless a syscall documents another result for it.
+//! Condvar and message queue ids also carry the generation of their slot
+//! above `SYNC_ID_GEN_SHIFT`, so one kept past a destroy is refused too
+//! rather than naming the object later created in the same slot.
+
+use crate::config::{MQ_MAX_CAPACITY, MQ_MAX_MSG_SIZE, PAGE_SIZE, SEM_MAX_COUNT};
+use crate::mm::{translated_byte_buffer, translated_ref, translated_refmut, UserBuffer};
+use crate::mm::{PTEFlags, PageTable, PhysAddr, VirtAddr};
+use crate::sync::{futex_push, futex_wake};
+use crate::sync::{sync_trace_drain, SyncEvent};
+use crate::sync::{Condvar, MsgQueue, Mutex, MutexAdaptive, MutexBlocking, MutexSpin, Semaphore};
+use crate::sync::{CondvarWake, CONDVAR_CLASSIC, CONDVAR_STICKY};
+use crate::sync::{MUTEX_ADAPTIVE, MUTEX_BARGING, MUTEX_BLOCKING, MUTEX_SPIN};
+use crate::sync::{SYNC_DOWN, SYNC_SIGNAL, SYNC_UNLOCK, SYNC_UP, SYNC_WAIT};
+use crate::task::{
+    block_current_and_run_next, check_signals_of_current, current_process, current_task,
+    current_user_token, next_sync_gen, suspend_current_and_run_next, sync_id, sync_slot,
+    DeadlockRequest, DeadlockStats, MutexStats,
+};
 use crate::timer::{add_timer, get_time_ms};
-use alloc::collections::BTreeSet;
 use alloc::sync::Arc;
+use alloc::vec;
 use alloc::vec::Vec;
+use core::convert::TryInto;
+use core::mem::size_of;
+use core::sync::atomic::{fence, AtomicU32, Ordering};
 
+use super::errno::{EAGAIN, EBUSY, EDEADLK, EDEADMUTEX, EDEADSEM, EFAULT, EINTR, EINVAL};
+use super::errno::{ENOTRECOVERABLE, EPERM, ETIMEDOUT};
 use super::thread::sys_gettid;
 
+/// Time the current thread has spent blocked so far, in microseconds. Read
+/// before and after a wait, the difference is the time blocked in it.
+fn blocked_time_us() -> usize {
+    current_task()
+        .unwrap()
+        .inner_exclusive_access()
+        .blocked_time_us
+}
+
+/// Charge the current thread for the time blocked since `since_us`, read
+/// from [`blocked_time_us`], waiting for `object`: `(kind, id)` with a
+/// `QUOTA_*` kind, or `None` for a wait on several objects at once
+fn charge_sync_wait(object: Option<(usize, usize)>, since_us: usize) {
+    let task = current_task().unwrap();
+    let mut task_inner = task.inner_exclusive_access();
+    let waited_us = task_inner.blocked_time_us - since_us;
+    task_inner.sync_wait_us += waited_us;
+    drop(task_inner);
+    if let Some(object) = object {
+        let process = current_process();
+        let mut process_inner = process.inner_exclusive_access();
+        *process_inner.sync_wait_us.entry(object).or_insert(0) += waited_us;
+    }
+}
+
+/// Charge the current thread for the time blocked since `since_us` in a sleep
+fn charge_sleep(since_us: usize) {
+    let task = current_task().unwrap();
+    let mut task_inner = task.inner_exclusive_access();
+    task_inner.sleep_us += task_inner.blocked_time_us - since_us;
+}
+
+/// Block for `ms` milliseconds. Sleeping 0 only yields, without arming a
+/// timer that could not fire before the next tick.
 pub fn sys_sleep(ms: usize) -> isize {
-    let expire_ms = get_time_ms() + ms;
+    if ms == 0 {
+        suspend_current_and_run_next();
+        return 0;
+    }
+    let expire_ms = get_time_ms().saturating_add(ms);
     let task = current_task().unwrap();
     add_timer(expire_ms, task);
+    let since_us = blocked_time_us();
+    block_current_and_run_next();
+    charge_sleep(since_us);
+    // woken early by a fatal signal
+    if current_process().inner_exclusive_access().signals.is_fatal() {
+        return -EINTR;
+    }
+    0
+}
+
+/// Block until `get_time_ms() >= abs_ms`, so that periodic sleeps do not drift.
+/// A deadline already in the past returns 0 at once without blocking.
+pub fn sys_sleep_until(abs_ms: usize) -> isize {
+    if get_time_ms() >= abs_ms {
+        return 0;
+    }
+    let task = current_task().unwrap();
+    add_timer(abs_ms, task);
+    let since_us = blocked_time_us();
     block_current_and_run_next();
+    charge_sleep(since_us);
+    // woken early by a fatal signal
+    if current_process().inner_exclusive_access().signals.is_fatal() {
+        return -EINTR;
+    }
     0
 }
 
-pub fn sys_mutex_create(blocking: bool) -> isize {
+/// Kinds of sync objects of [`sys_set_sync_quota`]
+pub const QUOTA_MUTEX: usize = 0;
+pub const QUOTA_SEMAPHORE: usize = 1;
+pub const QUOTA_CONDVAR: usize = 2;
+pub const QUOTA_MSGQUEUE: usize = 3;
+
+/// Allow the current process at most `n` live sync objects of `kind`, one
+/// of the `QUOTA_*` kinds. Objects beyond a lowered quota are kept, only new
+/// ones are refused. Return -1 for an unknown kind.
+pub fn sys_set_sync_quota(kind: usize, n: usize) -> isize {
     let process = current_process();
-    let mutex: Option<Arc<dyn Mutex>> = if !blocking {
-        Some(Arc::new(MutexSpin::new()))
-    } else {
-        Some(Arc::new(MutexBlocking::new()))
+    let mut process_inner = process.inner_exclusive_access();
+    match process_inner.sync_quota.get_mut(kind) {
+        Some(quota) => {
+            *quota = n;
+            0
+        }
+        None => -1,
+    }
+}
+
+/// Return the microseconds threads of the current process have spent blocked
+/// waiting for sync object `id` of `kind`, one of `QUOTA_MUTEX`,
+/// `QUOTA_SEMAPHORE` and `QUOTA_CONDVAR`, in waits that are over.
+/// Return -1 for another kind or a bad id.
+pub fn sys_sync_wait_time(kind: usize, id: usize) -> isize {
+    let process = current_process();
+    let process_inner = process.inner_exclusive_access();
+    let exists = match kind {
+        QUOTA_MUTEX => process_inner.mutex(id).is_some(),
+        QUOTA_SEMAPHORE => process_inner.semaphore(id).is_some(),
+        QUOTA_CONDVAR => process_inner.condvar(id).is_some(),
+        _ => false,
+    };
+    if !exists {
+        return -1;
+    }
+    let key = (kind, sync_slot(id));
+    let waited_us = process_inner.sync_wait_us.get(&key).copied();
+    waited_us.unwrap_or(0) as isize
+}
+
+/// Create a mutex of one of the `MUTEX_*` kinds, return its id, or -1 for an
+/// unknown kind or when the quota of mutexes is used up
+pub fn sys_mutex_create(kind: usize) -> isize {
+    let process = current_process();
+    let mutex: Option<Arc<dyn Mutex>> = match kind {
+        MUTEX_SPIN => Some(Arc::new(MutexSpin::new())),
+        MUTEX_BLOCKING => Some(Arc::new(MutexBlocking::new())),
+        MUTEX_ADAPTIVE => Some(Arc::new(MutexAdaptive::new())),
+        MUTEX_BARGING => Some(Arc::new(MutexBlocking::barging())),
+        _ => return -1,
     };
     let mut process_inner = process.inner_exclusive_access();
+    let live = process_inner.mutex_list.iter().flatten().count();
+    if live >= process_inner.sync_quota[QUOTA_MUTEX] {
+        return -1;
+    }
     if let Some(id) = process_inner
         .mutex_list
         .iter()
@@ -32,11 +169,17 @@ pub fn sys_mutex_create(blocking: bool) -> isize {
     {
         process_inner.mutex_list[id] = mutex;
         process_inner.mutex_alloc[id] = None;
+        process_inner.mutex_rank[id] = None;
+        process_inner.mutex_ceiling[id] = None;
+        process_inner.mutex_stats[id] = MutexStats::default();
 
         id as isize
     } else {
         process_inner.mutex_list.push(mutex);
         process_inner.mutex_alloc.push(None);
+        process_inner.mutex_rank.push(None);
+        process_inner.mutex_ceiling.push(None);
+        process_inner.mutex_stats.push(MutexStats::default());
         process_inner.mutex_list.len() as isize - 1
     }
 }
@@ -44,7 +187,10 @@ pub fn sys_mutex_create(blocking: bool) -> isize {
 pub fn sys_mutex_lock(mutex_id: usize) -> isize {
     let process = current_process();
     let mut process_inner = process.inner_exclusive_access();
-    let mutex = Arc::clone(process_inner.mutex_list[mutex_id].as_ref().unwrap());
+    let mutex = match process_inner.mutex(mutex_id) {
+        Some(mutex) => mutex,
+        None => return -EINVAL,
+    };
     let det = process_inner.deadlock_det_enabled;
     let tid = current_task()
         .unwrap()
@@ -53,50 +199,251 @@ pub fn sys_mutex_lock(mutex_id: usize) -> isize {
         .as_ref()
         .unwrap()
         .tid;
-    process_inner.mutex_request[tid] = Some(mutex_id);
-    if det {
-        let mut visited = BTreeSet::<usize>::new();
-        visited.insert(tid);
-        let mut mid = mutex_id;
-        while let Some(tid2) = process_inner.mutex_alloc[mid] {
-            if visited.contains(&tid2) {
-                println!(
-                    " ----- deadlock! pid: {}, tid: {}, mutex_id: {} ------",
-                    process.pid.0, tid, mutex_id
-                );
-                return -0xdead;
-            } else {
-                visited.insert(tid2);
-                if let Some(mid2) = process_inner.mutex_request[tid2] {
-                    mid = mid2;
-                } else {
-                    break;
+    if !process_inner.check_lock_order(process.pid.0, tid, mutex_id) {
+        return -EDEADLK;
+    }
+    // uncontended with detection off: the request would be cleared before
+    // anything looked at it, so it is not recorded at all
+    let mut acquired = !det && mutex.try_lock();
+    if !acquired {
+        process_inner.mutex_request[tid] = Some(mutex_id);
+        if det {
+            match process_inner.detect_deadlock() {
+                Ok(stuck) if stuck.is_empty() => {}
+                Ok(stuck) => {
+                    process_inner.report_deadlock(
+                        process.pid.0,
+                        tid,
+                        DeadlockRequest::Mutex(mutex_id),
+                        &stuck,
+                    );
+                    process_inner.mutex_request[tid] = None;
+                    return deadlock_abort(process_inner.deadlock_abort_enabled, -EDEADMUTEX);
+                }
+                Err(_) => {
+                    process_inner.mutex_request[tid] = None;
+                    return -ENOTRECOVERABLE;
                 }
             }
+            // the detector had to see the request, the mutex may be free all the same
+            acquired = mutex.try_lock();
+        }
+    }
+    if acquired {
+        process_inner.mutex_request[tid] = None;
+        process_inner.mutex_stats[mutex_id].immediate += 1;
+    } else {
+        process_inner.mutex_stats[mutex_id].blocked += 1;
+        drop(process_inner);
+        let since_us = blocked_time_us();
+        mutex.lock();
+        charge_sync_wait(Some((QUOTA_MUTEX, mutex_id)), since_us);
+        process_inner = process.inner_exclusive_access();
+        process_inner.mutex_request[tid] = None;
+        // woken by a fatal signal, the mutex was not acquired
+        if process_inner.signals.is_fatal() {
+            return -EINTR;
         }
     }
+    process_inner.mutex_acquired(tid, mutex_id);
+    0
+}
+
+/// Flag of [`sys_mutex_stats`]: zero the counters once they are copied
+pub const MUTEX_STATS_RESET: usize = 1;
+
+/// Write the contention counters of `mutex_id` to `stats`, with the number
+/// of threads waiting for it now. Locks of [`sys_mutex_spinlock`] are not
+/// counted. Return -EINVAL for a bad id or unknown `flags`.
+pub fn sys_mutex_stats(mutex_id: usize, stats: *mut MutexStats, flags: usize) -> isize {
+    if flags & !MUTEX_STATS_RESET != 0 {
+        return -EINVAL;
+    }
+    let process = current_process();
+    let mut process_inner = process.inner_exclusive_access();
+    if process_inner.mutex(mutex_id).is_none() {
+        return -EINVAL;
+    }
+    let waiters = process_inner
+        .mutex_request
+        .iter()
+        .filter(|&&request| request == Some(mutex_id))
+        .count();
+    let counters = process_inner.mutex_stats[mutex_id];
+    if flags & MUTEX_STATS_RESET != 0 {
+        process_inner.mutex_stats[mutex_id] = MutexStats::default();
+    }
     drop(process_inner);
-    drop(process);
-    mutex.lock();
+    *translated_refmut(current_user_token(), stats) = MutexStats {
+        waiters,
+        ..counters
+    };
+    0
+}
+
+/// Try to lock `mutex_id` at most `max_spins + 1` times, yielding between
+/// attempts so that its holder can run and release it. It never blocks:
+/// return 0 once the mutex is locked, 1 if it is still held by another
+/// thread after the last attempt, and -EINTR if a fatal signal arrives in
+/// the meantime. Not waiting for good, it cannot deadlock, so the deadlock
+/// and lock order checks are skipped.
+pub fn sys_mutex_spinlock(mutex_id: usize, max_spins: usize) -> isize {
+    let tid = sys_gettid() as usize;
+    let process = current_process();
+    let mutex = match process.inner_exclusive_access().mutex(mutex_id) {
+        Some(mutex) => mutex,
+        None => return -EINVAL,
+    };
+    for spin in 0..=max_spins {
+        if spin > 0 {
+            suspend_current_and_run_next();
+            if check_signals_of_current().is_some() {
+                return -EINTR;
+            }
+        }
+        if mutex.try_lock() {
+            process
+                .inner_exclusive_access()
+                .mutex_acquired(tid, mutex_id);
+            return 0;
+        }
+    }
+    1
+}
+
+/// Lock `mutex_id` only if that can be done right away, never blocking.
+/// Return 0 once it is locked and 1 if another thread holds it. With
+/// deadlock detection enabled, a held mutex whose wait would complete a
+/// cycle gives `-0xdead` instead, so that the caller can back off and
+/// release its own locks before the deadlock forms. The thread is not made
+/// to exit and nothing is reported, as no thread is stuck yet, and the
+/// look is not counted in the detector's stats. The rank and lockdep
+/// checks of [`sys_mutex_lock`] apply as they do there.
+pub fn sys_mutex_trylock_checked(mutex_id: usize) -> isize {
+    let tid = sys_gettid() as usize;
     let process = current_process();
     let mut process_inner = process.inner_exclusive_access();
-    process_inner.mutex_alloc[mutex_id] = Some(tid);
+    let mutex = match process_inner.mutex(mutex_id) {
+        Some(mutex) => mutex,
+        None => return -EINVAL,
+    };
+    if !process_inner.check_lock_order(process.pid.0, tid, mutex_id) {
+        return -EDEADLK;
+    }
+    if mutex.try_lock() {
+        process_inner.mutex_stats[mutex_id].immediate += 1;
+        process_inner.mutex_acquired(tid, mutex_id);
+        return 0;
+    }
+    if !process_inner.deadlock_det_enabled {
+        return 1;
+    }
+    // the same walk as a blocking lock, on a request withdrawn right after
+    process_inner.mutex_request[tid] = Some(mutex_id);
+    let stuck = process_inner.stuck_threads();
     process_inner.mutex_request[tid] = None;
-    0
+    match stuck {
+        Ok(stuck) if stuck.is_empty() => 1,
+        Ok(_) => -EDEADMUTEX,
+        Err(_) => -ENOTRECOVERABLE,
+    }
 }
 
 pub fn sys_mutex_unlock(mutex_id: usize) -> isize {
+    mutex_unlock_to(mutex_id, None)
+}
+
+/// Unlock `mutex_id` handing it directly to thread `tid` if it is waiting
+/// for it, so that no other thread can take it first. Otherwise it is a
+/// plain unlock.
+pub fn sys_mutex_unlock_to(mutex_id: usize, tid: usize) -> isize {
+    mutex_unlock_to(mutex_id, Some(tid))
+}
+
+fn mutex_unlock_to(mutex_id: usize, target: Option<usize>) -> isize {
     let process = current_process();
     let mut process_inner = process.inner_exclusive_access();
-    let mutex = Arc::clone(process_inner.mutex_list[mutex_id].as_ref().unwrap());
-    mutex.unlock();
-    process_inner.mutex_alloc[mutex_id] = None;
+    let mutex = match process_inner.mutex(mutex_id) {
+        Some(mutex) => mutex,
+        None => return -EINVAL,
+    };
+    let target = target.and_then(|tid| process_inner.tasks.get(tid).cloned().flatten());
+    // unlocked by another thread, the rank and ceiling to drop are the holder's
+    let holder = process_inner.mutex_alloc[mutex_id]
+        .and_then(|tid| process_inner.tasks.get(tid).cloned().flatten())
+        .unwrap_or_else(|| current_task().unwrap());
+    let owner = match target {
+        Some(task) => mutex.unlock_to(&task),
+        None => mutex.unlock(),
+    };
+    // still under the process borrow, nobody sees the mutex half handed over
+    process_inner.mutex_released(mutex_id, owner);
+    process_inner.trace_sync(sys_gettid() as usize, SYNC_UNLOCK, mutex_id);
+    let mut task_inner = holder.inner_exclusive_access();
+    task_inner.held_ranks.retain(|&(id, _)| id != mutex_id);
+    task_inner.drop_ceiling(mutex_id);
     0
 }
 
+/// Give `mutex_id` a rank for lock order checking. Once enabled with
+/// [`LOCK_ORDER_CHECK`], a thread holding a mutex of some rank can no longer
+/// lock one of a lower rank. Unranked mutexes are never checked.
+/// Return -1 for a bad id.
+pub fn sys_mutex_set_rank(mutex_id: usize, rank: usize) -> isize {
+    let process = current_process();
+    let mut process_inner = process.inner_exclusive_access();
+    match process_inner.mutex_list.get(mutex_id) {
+        Some(Some(_)) => {
+            process_inner.mutex_rank[mutex_id] = Some(rank);
+            0
+        }
+        _ => -1,
+    }
+}
+
+/// Give `mutex_id` a priority ceiling, the highest priority of the threads
+/// using it. A thread holding it runs at least at that priority, so no
+/// thread of a priority up to the ceiling preempts it in its critical
+/// section. A ceiling set while the mutex is held applies from the next
+/// lock on. Return -1 for a bad id or a priority below 2.
+pub fn sys_mutex_set_ceiling(mutex_id: usize, prio: usize) -> isize {
+    if prio < 2 {
+        return -1;
+    }
+    let process = current_process();
+    let mut process_inner = process.inner_exclusive_access();
+    match process_inner.mutex_list.get(mutex_id) {
+        Some(Some(_)) => {
+            process_inner.mutex_ceiling[mutex_id] = Some(prio);
+            0
+        }
+        _ => -1,
+    }
+}
+
+/// Return the kind `mutex_id` was created with, or -1 for a bad id
+pub fn sys_mutex_kind(mutex_id: usize) -> isize {
+    let process = current_process();
+    let process_inner = process.inner_exclusive_access();
+    match process_inner.mutex_list.get(mutex_id) {
+        Some(Some(mutex)) => mutex.kind() as isize,
+        _ => -1,
+    }
+}
+
+/// Create a semaphore holding `res_count` resources, return its id, or -1
+/// if `res_count` is above `SEM_MAX_COUNT` or the quota of semaphores is
+/// used up
 pub fn sys_semaphore_create(res_count: usize) -> isize {
+    if res_count > SEM_MAX_COUNT {
+        return -1;
+    }
     let process = current_process();
     let mut process_inner = process.inner_exclusive_access();
+    let live = process_inner.semaphore_list.iter().flatten().count();
+    if live >= process_inner.sync_quota[QUOTA_SEMAPHORE] {
+        return -1;
+    }
     let id = if let Some(id) = process_inner
         .semaphore_list
         .iter()
@@ -109,6 +456,9 @@ pub fn sys_semaphore_create(res_count: usize) -> isize {
         for t_alloc in process_inner.sem_alloc.iter_mut() {
             t_alloc[id] = 0;
         }
+        for t_request in process_inner.sem_request.iter_mut() {
+            t_request[id] = 0;
+        }
         id
     } else {
         process_inner
@@ -118,87 +468,306 @@ pub fn sys_semaphore_create(res_count: usize) -> isize {
         for t_alloc in process_inner.sem_alloc.iter_mut() {
             t_alloc.push(0);
         }
+        for t_request in process_inner.sem_request.iter_mut() {
+            t_request.push(0);
+        }
         process_inner.semaphore_list.len() - 1
     };
     id as isize
 }
 
+/// Release one resource of `sem_id`, which goes to the thread that has
+/// waited longest. The caller does not need to hold one: the count is
+/// raised anyway and only a resource it does hold is returned.
 pub fn sys_semaphore_up(sem_id: usize) -> isize {
+    sys_semaphore_up_n(sem_id, 1)
+}
+
+/// Release `n` resources of `sem_id` at once, waking the waiters at the
+/// front of the queue in order while their requests can be met.
+/// Return -EINVAL if `n` is 0 or would take the free count above
+/// `SEM_MAX_COUNT`.
+pub fn sys_semaphore_up_n(sem_id: usize, n: usize) -> isize {
+    if n == 0 {
+        return -EINVAL;
+    }
     let process = current_process();
     let mut process_inner = process.inner_exclusive_access();
-    let sem = Arc::clone(process_inner.semaphore_list[sem_id].as_ref().unwrap());
-    sem.up();
-    process_inner.sem_avail[sem_id] += 1;
+    let sem = match process_inner.semaphore(sem_id) {
+        Some(sem) => sem,
+        None => return -EINVAL,
+    };
+    // sem_avail is the free count of the semaphore
+    match process_inner.sem_avail[sem_id].checked_add(n) {
+        Some(avail) if avail <= SEM_MAX_COUNT => {}
+        _ => return -EINVAL,
+    }
+    let granted = sem.up_n(n);
     let tid = sys_gettid() as usize;
-    process_inner.sem_alloc[tid][sem_id] -= 1;
+    let held = &mut process_inner.sem_alloc[tid][sem_id];
+    *held -= n.min(*held);
+    process_inner.sem_released(sem_id, n, granted);
+    process_inner.trace_sync(tid, SYNC_UP, sem_id);
     0
 }
 
+/// Result `code` of a request that would deadlock. With `abort`, the
+/// requesting thread is also made to exit with it before going back to user
+/// mode, which releases what it holds so that the other threads of the cycle
+/// can go on. The main thread exiting ends the whole process.
+fn deadlock_abort(abort: bool, code: isize) -> isize {
+    if abort {
+        current_task().unwrap().inner_exclusive_access().abort_code = Some(code as i32);
+    }
+    code
+}
+
+/// Result of a detected semaphore deadlock, `-EDEADSEM` only when `distinct`
+/// so that the lab's `-0xdead` is kept by default
+fn sem_deadlock_code(distinct: bool) -> isize {
+    if distinct {
+        -EDEADSEM
+    } else {
+        -EDEADMUTEX
+    }
+}
+
 pub fn sys_semaphore_down(sem_id: usize) -> isize {
+    sys_semaphore_down_n(sem_id, 1)
+}
+
+/// Acquire `n` resources of `sem_id` at once, waiting behind earlier
+/// waiters until all of them are available. Return -EINVAL if `n` is 0.
+pub fn sys_semaphore_down_n(sem_id: usize, n: usize) -> isize {
+    if n == 0 {
+        return -EINVAL;
+    }
     let process = current_process();
     let mut process_inner = process.inner_exclusive_access();
-    let sem = Arc::clone(process_inner.semaphore_list[sem_id].as_ref().unwrap());
+    let sem = match process_inner.semaphore(sem_id) {
+        Some(sem) => sem,
+        None => return -EINVAL,
+    };
     let tid = sys_gettid() as usize;
-    process_inner.sem_request[tid] = Some(sem_id);
+    process_inner.sem_request[tid][sem_id] = n;
     let det = process_inner.deadlock_det_enabled;
     if det {
-        // deadlock detection
-        // init
-        let mut work = process_inner.sem_avail.clone();
-        let mut not_finished = BTreeSet::<usize>::new();
-        for (tid2, t_alloc) in process_inner.sem_alloc.iter().enumerate() {
-            if !t_alloc.is_empty() {
-                not_finished.insert(tid2);
+        match process_inner.detect_deadlock() {
+            Ok(stuck) if stuck.is_empty() => {}
+            Ok(stuck) => {
+                process_inner.report_deadlock(
+                    process.pid.0,
+                    tid,
+                    DeadlockRequest::Semaphore(sem_id),
+                    &stuck,
+                );
+                process_inner.sem_request[tid][sem_id] = 0;
+                let code = sem_deadlock_code(process_inner.deadlock_codes_enabled);
+                return deadlock_abort(process_inner.deadlock_abort_enabled, code);
+            }
+            Err(_) => {
+                process_inner.sem_request[tid][sem_id] = 0;
+                return -ENOTRECOVERABLE;
             }
         }
+    }
+    drop(process_inner);
+    let since_us = blocked_time_us();
+    let taken = sem.down_n(n);
+    charge_sync_wait(Some((QUOTA_SEMAPHORE, sem_id)), since_us);
+    let process = current_process();
+    let mut process_inner = process.inner_exclusive_access();
+    if taken {
+        process_inner.sem_request[tid][sem_id] = 0;
+        process_inner.sem_avail[sem_id] -= n;
+        process_inner.sem_alloc[tid][sem_id] += n;
+    } else if process_inner.sem_request[tid][sem_id] != 0 {
+        // woken by a fatal signal, nothing was handed over
+        process_inner.sem_request[tid][sem_id] = 0;
+        return -EINTR;
+    }
+    process_inner.trace_sync(tid, SYNC_DOWN, sem_id);
+    0
+}
 
-        let mut all_released = false;
-        let mut all_finished = not_finished.is_empty();
-        while !all_finished && !all_released {
-            all_released = true;
-            let mut finished = Vec::<usize>::new();
-            for tid2 in not_finished.iter() {
-                // step 2
-                if let Some(sid) = process_inner.sem_request[*tid2] {
-                    if work[sid] == 0 {
-                        continue;
-                    }
-                }
-                all_released = false;
-                // step 3
-                finished.push(*tid2);
-                for (sid, num) in process_inner.sem_alloc[*tid2].iter().enumerate() {
-                    work[sid] += num;
-                }
+/// One operation of [`sys_semaphore_op`]
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct SemOp {
+    pub sem_id: usize,
+    /// Resources to acquire if negative, to release if positive
+    pub delta: isize,
+}
+
+/// Apply the `nops` operations at `ops` as a whole. All the acquisitions are
+/// made together once every semaphore can serve its part, nothing being held
+/// in the meantime, then the releases are made. The deadlock detector sees
+/// the waiting thread as asking for all of them at once.
+///
+/// Waiters are not queued on the semaphores but retry on every release, so
+/// a steady stream of single `down`s can keep them waiting.
+/// Return -EINVAL for a bad id or deltas of one semaphore summing past
+/// `usize::MAX`, the semaphore deadlock code for a deadlock,
+/// -ENOTRECOVERABLE if the detector finds its bookkeeping corrupt and -EINTR
+/// when interrupted by a fatal signal.
+pub fn sys_semaphore_op(ops: *const SemOp, nops: usize) -> isize {
+    let token = current_user_token();
+    let process = current_process();
+    let mut process_inner = process.inner_exclusive_access();
+    let sem_n = process_inner.semaphore_list.len();
+    let mut downs = vec![0usize; sem_n];
+    let mut ups = vec![0usize; sem_n];
+    for i in 0..nops {
+        let op = *translated_ref(token, unsafe { ops.add(i) });
+        if !matches!(process_inner.semaphore_list.get(op.sem_id), Some(Some(_))) {
+            return -EINVAL;
+        }
+        let total = if op.delta < 0 {
+            &mut downs[op.sem_id]
+        } else {
+            &mut ups[op.sem_id]
+        };
+        *total = match total.checked_add(op.delta.unsigned_abs()) {
+            Some(total) => total,
+            None => return -EINVAL,
+        };
+    }
+    let sems: Vec<_> = process_inner.semaphore_list.iter().cloned().collect();
+    let tid = sys_gettid() as usize;
+    process_inner.sem_request[tid] = downs.clone();
+    if process_inner.deadlock_det_enabled {
+        match process_inner.detect_deadlock() {
+            Ok(stuck) if stuck.is_empty() => {}
+            Ok(stuck) => {
+                process_inner.report_deadlock(process.pid.0, tid, DeadlockRequest::SemOp, &stuck);
+                process_inner.sem_request[tid].fill(0);
+                let code = sem_deadlock_code(process_inner.deadlock_codes_enabled);
+                return deadlock_abort(process_inner.deadlock_abort_enabled, code);
             }
-            for tid2 in finished.iter() {
-                not_finished.remove(tid2);
+            Err(_) => {
+                process_inner.sem_request[tid].fill(0);
+                return -ENOTRECOVERABLE;
             }
-            // not_finished = not_finished.difference(&finished).collect();
-            all_finished = not_finished.is_empty();
-        }
-
-        if !not_finished.is_empty() {
-            println!(
-                "--- deadlock! pid: {}, tid: {}, sem_id: {}",
-                process.pid.0, tid, sem_id
-            );
-            return -0xdead;
         }
     }
     drop(process_inner);
-    sem.down();
-    let process = current_process();
+    let wanted: Vec<_> = (0..sem_n).filter(|&sid| downs[sid] > 0).collect();
+    let since_us = blocked_time_us();
+    loop {
+        let ready = wanted
+            .iter()
+            .all(|&sid| sems[sid].as_ref().unwrap().can_down_n(downs[sid]));
+        if ready {
+            for &sid in wanted.iter() {
+                sems[sid].as_ref().unwrap().down_n(downs[sid]);
+            }
+            break;
+        }
+        // nobody would wake us up once the process is being killed
+        if check_signals_of_current().is_some() {
+            process.inner_exclusive_access().sem_request[tid].fill(0);
+            return -EINTR;
+        }
+        let task = current_task().unwrap();
+        process
+            .inner_exclusive_access()
+            .semop_waiters
+            .push_back(task);
+        block_current_and_run_next();
+    }
+    charge_sync_wait(None, since_us);
     let mut process_inner = process.inner_exclusive_access();
-    process_inner.sem_request[tid] = None;
-    process_inner.sem_avail[sem_id] -= 1;
-    process_inner.sem_alloc[tid][sem_id] += 1;
+    process_inner.sem_request[tid].fill(0);
+    for &sid in wanted.iter() {
+        process_inner.sem_avail[sid] -= downs[sid];
+        process_inner.sem_alloc[tid][sid] += downs[sid];
+        process_inner.trace_sync(tid, SYNC_DOWN, sid);
+    }
+    drop(process_inner);
+    for (sid, &n) in ups.iter().enumerate() {
+        if n > 0 {
+            sys_semaphore_up_n(sid, n);
+        }
+    }
     0
 }
 
-pub fn sys_condvar_create(_arg: usize) -> isize {
+/// Return the number of available resources of `sem_id` minus those its
+/// waiters still ask for. Return `isize::MIN` for a bad id.
+pub fn sys_semaphore_getvalue(sem_id: usize) -> isize {
+    let process = current_process();
+    let process_inner = process.inner_exclusive_access();
+    match process_inner.semaphore_list.get(sem_id) {
+        // the kernel is not preemptible, so the count is a consistent snapshot
+        Some(Some(sem)) => sem.value(),
+        _ => isize::MIN,
+    }
+}
+
+/// Write the tids of the threads blocked on `sem_id` into `buf` in the order
+/// they will be served, at most `len` of them. Return the number of tids
+/// written, or -1 for a bad id.
+pub fn sys_semaphore_waiters(sem_id: usize, buf: *mut usize, len: usize) -> isize {
+    let token = current_user_token();
+    let process = current_process();
+    let process_inner = process.inner_exclusive_access();
+    let sem = match process_inner.semaphore_list.get(sem_id) {
+        Some(Some(sem)) => Arc::clone(sem),
+        _ => return -1,
+    };
+    drop(process_inner);
+    let tids: Vec<usize> = sem
+        .waiters()
+        .iter()
+        .filter_map(|task| task.inner_exclusive_access().res.as_ref().map(|r| r.tid))
+        .collect();
+    let n = tids.len().min(len);
+    for (i, tid) in tids.into_iter().take(n).enumerate() {
+        *translated_refmut(token, unsafe { buf.add(i) }) = tid;
+    }
+    n as isize
+}
+
+/// Write into `buf[tid]` how many permits of `sem_id` thread `tid` holds,
+/// as counted for deadlock detection, for the first `len` tids at most.
+/// Slots of exited threads hold 0. Once a workload is over, every permit
+/// should be back: all zero here and the initial count in
+/// [`sys_semaphore_getvalue`]. Return the number of entries written, or
+/// -EINVAL for a bad id.
+pub fn sys_semaphore_audit(sem_id: usize, buf: *mut usize, len: usize) -> isize {
+    let token = current_user_token();
+    let process = current_process();
+    let process_inner = process.inner_exclusive_access();
+    if !matches!(process_inner.semaphore_list.get(sem_id), Some(Some(_))) {
+        return -EINVAL;
+    }
+    let held: Vec<usize> = process_inner
+        .sem_alloc
+        .iter()
+        .take(len)
+        .map(|row| row[sem_id])
+        .collect();
+    drop(process_inner);
+    for (i, &n) in held.iter().enumerate() {
+        *translated_refmut(token, unsafe { buf.add(i) }) = n;
+    }
+    held.len() as isize
+}
+
+/// Create a condvar of one of the `CONDVAR_*` kinds, return its id, or -1
+/// for an unknown kind or when the quota of condvars is used up
+pub fn sys_condvar_create(kind: usize) -> isize {
+    let sticky = match kind {
+        CONDVAR_CLASSIC => false,
+        CONDVAR_STICKY => true,
+        _ => return -1,
+    };
     let process = current_process();
     let mut process_inner = process.inner_exclusive_access();
+    let live = process_inner.condvar_list.iter().flatten().count();
+    if live >= process_inner.sync_quota[QUOTA_CONDVAR] {
+        return -1;
+    }
     let id = if let Some(id) = process_inner
         .condvar_list
         .iter()
@@ -206,49 +775,548 @@ pub fn sys_condvar_create(_arg: usize) -> isize {
         .find(|(_, item)| item.is_none())
         .map(|(id, _)| id)
     {
-        process_inner.condvar_list[id] = Some(Arc::new(Condvar::new()));
+        process_inner.condvar_list[id] = Some(Arc::new(Condvar::new(sticky)));
         id
     } else {
         process_inner
             .condvar_list
-            .push(Some(Arc::new(Condvar::new())));
+            .push(Some(Arc::new(Condvar::new(sticky))));
+        process_inner.condvar_gen.push(0);
         process_inner.condvar_list.len() - 1
     };
-    id as isize
+    sync_id(id, process_inner.condvar_gen[id]) as isize
+}
+
+/// Free `condvar_id` so that its slot can be reused by a new condvar, which
+/// gets another id. Return -1 for a bad id or while some thread waits on
+/// it, including a waiter that was signalled but has not returned yet.
+pub fn sys_condvar_destroy(condvar_id: usize) -> isize {
+    let process = current_process();
+    let mut process_inner = process.inner_exclusive_access();
+    if process_inner.condvar(condvar_id).is_none() {
+        return -1;
+    }
+    let condvar_id = sync_slot(condvar_id);
+    if process_inner
+        .condvar_request
+        .iter()
+        .any(|&request| request == Some(condvar_id))
+    {
+        return -1;
+    }
+    process_inner.condvar_list[condvar_id] = None;
+    process_inner.condvar_gen[condvar_id] = next_sync_gen(process_inner.condvar_gen[condvar_id]);
+    process_inner
+        .sync_wait_us
+        .remove(&(QUOTA_CONDVAR, condvar_id));
+    0
 }
 
 pub fn sys_condvar_signal(condvar_id: usize) -> isize {
     let process = current_process();
-    let process_inner = process.inner_exclusive_access();
-    let condvar = Arc::clone(process_inner.condvar_list[condvar_id].as_ref().unwrap());
+    let mut process_inner = process.inner_exclusive_access();
+    let condvar = match process_inner.condvar(condvar_id) {
+        Some(condvar) => condvar,
+        None => return -EINVAL,
+    };
+    let condvar_id = sync_slot(condvar_id);
+    process_inner.trace_sync(sys_gettid() as usize, SYNC_SIGNAL, condvar_id);
     drop(process_inner);
     condvar.signal();
     0
 }
 
+/// Wake up to `n` waiters of `condvar_id`, return how many were woken
+pub fn sys_condvar_signal_n(condvar_id: usize, n: usize) -> isize {
+    let process = current_process();
+    let mut process_inner = process.inner_exclusive_access();
+    let condvar = match process_inner.condvar(condvar_id) {
+        Some(condvar) => condvar,
+        None => return -EINVAL,
+    };
+    let condvar_id = sync_slot(condvar_id);
+    process_inner.trace_sync(sys_gettid() as usize, SYNC_SIGNAL, condvar_id);
+    drop(process_inner);
+    condvar.signal_n(n) as isize
+}
+
+/// Wake thread `tid` of the current process if it waits on `condvar_id`,
+/// return -1 if it does not. Like any woken waiter, it locks the mutex
+/// again before returning from its wait.
+pub fn sys_condvar_signal_tid(condvar_id: usize, tid: usize) -> isize {
+    let process = current_process();
+    let mut process_inner = process.inner_exclusive_access();
+    let condvar = match process_inner.condvar(condvar_id) {
+        Some(condvar) => condvar,
+        None => return -EINVAL,
+    };
+    let condvar_id = sync_slot(condvar_id);
+    let task = match process_inner.tasks.get(tid) {
+        Some(Some(task)) => Arc::clone(task),
+        _ => return -1,
+    };
+    process_inner.trace_sync(sys_gettid() as usize, SYNC_SIGNAL, condvar_id);
+    drop(process_inner);
+    if condvar.signal_task(&task) {
+        0
+    } else {
+        -1
+    }
+}
+
+/// Release `mutex_id`, wait for a signal on `condvar_id` and lock the mutex
+/// again. The caller must hold the mutex, otherwise return -EPERM without
+/// waiting. Return -EINTR, the mutex not being held, when interrupted by a
+/// fatal signal.
+///
+/// With `CONDVAR_STALL`, return -EDEADLK, still holding the mutex, when no
+/// thread would be left able to send the signal.
 pub fn sys_condvar_wait(condvar_id: usize, mutex_id: usize) -> isize {
+    condvar_wait(condvar_id, mutex_id, None)
+}
+
+/// [`sys_condvar_wait`] for at most `timeout_ms` milliseconds. Return 0 if
+/// signalled, -ETIMEDOUT if the timeout expired first, the mutex being held
+/// again in both cases, and -EINTR when interrupted by a fatal signal. The
+/// timer can always end the wait, so `CONDVAR_STALL` does not apply.
+pub fn sys_condvar_timedwait(condvar_id: usize, mutex_id: usize, timeout_ms: usize) -> isize {
+    let expire_ms = get_time_ms().saturating_add(timeout_ms);
+    condvar_wait(condvar_id, mutex_id, Some(expire_ms))
+}
+
+fn condvar_wait(condvar_id: usize, mutex_id: usize, expire_ms: Option<usize>) -> isize {
+    let tid = sys_gettid() as usize;
     let process = current_process();
-    let process_inner = process.inner_exclusive_access();
-    let condvar = Arc::clone(process_inner.condvar_list[condvar_id].as_ref().unwrap());
-    let mutex = Arc::clone(process_inner.mutex_list[mutex_id].as_ref().unwrap());
+    let mut process_inner = process.inner_exclusive_access();
+    let condvar = match process_inner.condvar(condvar_id) {
+        Some(condvar) => condvar,
+        None => return -EINVAL,
+    };
+    let condvar_id = sync_slot(condvar_id);
+    let mutex = match process_inner.mutex(mutex_id) {
+        Some(mutex) => mutex,
+        None => return -EINVAL,
+    };
+    if process_inner.mutex_alloc[mutex_id] != Some(tid) {
+        return -EPERM;
+    }
+    process_inner.mutex_alloc[mutex_id] = None;
+    process_inner.condvar_request[tid] = Some(condvar_id);
+    if process_inner.condvar_stall_enabled && expire_ms.is_none() {
+        let stuck = process_inner.stuck_threads();
+        if !matches!(&stuck, Ok(stuck) if stuck.is_empty()) {
+            process_inner.mutex_alloc[mutex_id] = Some(tid);
+            process_inner.condvar_request[tid] = None;
+            return match stuck {
+                Ok(stuck) => {
+                    println!(
+                        "--- condvar stall! pid: {}, tid: {}, condvar_id: {}, stuck: {:?}",
+                        process.pid.0, tid, condvar_id, stuck
+                    );
+                    -EDEADLK
+                }
+                Err(_) => -ENOTRECOVERABLE,
+            };
+        }
+    }
+    process_inner.trace_sync(tid, SYNC_WAIT, condvar_id);
     drop(process_inner);
-    condvar.wait(mutex);
+    let since_us = blocked_time_us();
+    let wake = condvar.wait(mutex, expire_ms, |owner| {
+        // a waiter the mutex is handed to owns it now, as in sys_mutex_unlock
+        process
+            .inner_exclusive_access()
+            .mutex_released(mutex_id, owner)
+    });
+    charge_sync_wait(Some((QUOTA_CONDVAR, condvar_id)), since_us);
+    let mut process_inner = process.inner_exclusive_access();
+    process_inner.condvar_request[tid] = None;
+    // locking the mutex again may have been interrupted too
+    if wake == CondvarWake::Interrupted || process_inner.signals.is_fatal() {
+        return -EINTR;
+    }
+    process_inner.mutex_alloc[mutex_id] = Some(tid);
+    match wake {
+        CondvarWake::TimedOut => -ETIMEDOUT,
+        _ => 0,
+    }
+}
+
+/// Wait on `condvar_id` for as long as the word at `pred` equals `expected`,
+/// the caller holding `mutex_id`. The word is read with the mutex held before
+/// every wait, so it must be protected by that same mutex; spurious wakeups
+/// and stolen signals just wait again. Return -EINTR when interrupted by a
+/// fatal signal.
+pub fn sys_condvar_wait_while(
+    condvar_id: usize,
+    mutex_id: usize,
+    pred: *const usize,
+    expected: usize,
+) -> isize {
+    let token = current_user_token();
+    while *translated_ref(token, pred) == expected {
+        let ret = sys_condvar_wait(condvar_id, mutex_id);
+        if ret != 0 {
+            return ret;
+        }
+    }
     0
 }
 
-// YOUR JOB
-pub fn sys_enable_deadlock_detect(_enabled: usize) -> isize {
+/// Create a queue of at most `capacity` messages of `msg_size` bytes each,
+/// return its id. Return -EINVAL if either is 0 or above `MQ_MAX_CAPACITY`
+/// and `MQ_MAX_MSG_SIZE`, -1 when the quota of queues is used up.
+pub fn sys_mq_create(capacity: usize, msg_size: usize) -> isize {
+    if capacity == 0 || capacity > MQ_MAX_CAPACITY || msg_size == 0 || msg_size > MQ_MAX_MSG_SIZE {
+        return -EINVAL;
+    }
     let process = current_process();
     let mut process_inner = process.inner_exclusive_access();
-    match _enabled {
-        0 => {
-            process_inner.deadlock_det_enabled = false;
-            0
+    let live = process_inner.mq_list.iter().flatten().count();
+    if live >= process_inner.sync_quota[QUOTA_MSGQUEUE] {
+        return -1;
+    }
+    let mq = Some(Arc::new(MsgQueue::new(capacity, msg_size)));
+    let id = match process_inner.mq_list.iter().position(|item| item.is_none()) {
+        Some(id) => {
+            process_inner.mq_list[id] = mq;
+            id
         }
-        1 => {
-            process_inner.deadlock_det_enabled = true;
-            0
+        None => {
+            process_inner.mq_list.push(mq);
+            process_inner.mq_gen.push(0);
+            process_inner.mq_list.len() - 1
         }
-        _ => -1,
+    };
+    sync_id(id, process_inner.mq_gen[id]) as isize
+}
+
+/// Free `mq_id` so that its slot can be reused by a new queue, which gets
+/// another id. Messages still queued are dropped. Return -1 for a bad id
+/// or while some thread is sending or receiving on it, including one woken
+/// that has not returned yet.
+pub fn sys_mq_destroy(mq_id: usize) -> isize {
+    let process = current_process();
+    let mut process_inner = process.inner_exclusive_access();
+    let mq = match process_inner.mq(mq_id) {
+        Some(mq) => mq,
+        None => return -1,
+    };
+    // a thread in `sys_mq_send` or `sys_mq_recv` holds its own reference
+    if Arc::strong_count(&mq) > 2 {
+        return -1;
+    }
+    let mq_id = sync_slot(mq_id);
+    process_inner.mq_list[mq_id] = None;
+    process_inner.mq_gen[mq_id] = next_sync_gen(process_inner.mq_gen[mq_id]);
+    0
+}
+
+fn mq_of_current(mq_id: usize) -> Option<Arc<MsgQueue>> {
+    let process = current_process();
+    let process_inner = process.inner_exclusive_access();
+    process_inner.mq(mq_id)
+}
+
+/// Send the `len` bytes at `buf`, waiting while the queue is full. Messages
+/// are received in the order they were sent. Return -1 for a bad id,
+/// -EINVAL unless `len` is the queue's message size, -EFAULT if the message
+/// is not readable and -EINTR when interrupted by a fatal signal.
+pub fn sys_mq_send(mq_id: usize, buf: *const u8, len: usize) -> isize {
+    let mq = match mq_of_current(mq_id) {
+        Some(mq) => mq,
+        None => return -1,
+    };
+    if len != mq.msg_size {
+        return -EINVAL;
+    }
+    if !user_range_mapped(buf as usize, len, PTEFlags::R) {
+        return -EFAULT;
+    }
+    let mut msg = Vec::with_capacity(mq.msg_size);
+    for part in translated_byte_buffer(current_user_token(), buf, mq.msg_size) {
+        msg.extend_from_slice(part);
+    }
+    if mq.send(msg) {
+        0
+    } else {
+        -EINTR
+    }
+}
+
+/// Receive the oldest message into the `len` bytes at `buf`, waiting while
+/// the queue is empty. Return -1 for a bad id, -EINVAL unless `len` is the
+/// queue's message size, -EFAULT if `buf` is not writable and -EINTR when
+/// interrupted by a fatal signal. The checks come first, so a failed
+/// receive leaves the message queued.
+pub fn sys_mq_recv(mq_id: usize, buf: *mut u8, len: usize) -> isize {
+    let mq = match mq_of_current(mq_id) {
+        Some(mq) => mq,
+        None => return -1,
+    };
+    if len != mq.msg_size {
+        return -EINVAL;
+    }
+    if !user_range_mapped(buf as usize, len, PTEFlags::W) {
+        return -EFAULT;
+    }
+    let msg = match mq.recv() {
+        Some(msg) => msg,
+        None => return -EINTR,
+    };
+    let mut copied = 0;
+    for part in translated_byte_buffer(current_user_token(), buf, msg.len()) {
+        part.copy_from_slice(&msg[copied..copied + part.len()]);
+        copied += part.len();
+    }
+    0
+}
+
+/// Physical address of the user byte at `addr`, `None` unless its page is
+/// mapped with all of `flags` and accessible from user mode. Pages such as
+/// the trap contexts are mapped in the user page table without `U`.
+fn user_pa(addr: usize, flags: PTEFlags) -> Option<PhysAddr> {
+    let page_table = PageTable::from_token(current_user_token());
+    let va = VirtAddr::from(addr);
+    let flags = flags | PTEFlags::V | PTEFlags::U;
+    page_table
+        .translate(va.floor())
+        .filter(|pte| pte.flags().contains(flags))?;
+    page_table.translate_va(va)
+}
+
+/// Whether every page of the `len` bytes at `start` passes [`user_pa`]
+fn user_range_mapped(start: usize, len: usize, flags: PTEFlags) -> bool {
+    let end = match start.checked_add(len) {
+        Some(end) => end,
+        None => return false,
+    };
+    (start - start % PAGE_SIZE..end)
+        .step_by(PAGE_SIZE)
+        .all(|page| user_pa(page, flags).is_some())
+}
+
+/// Physical address of the aligned user word at `addr`, `None` if it is not
+/// mapped readable from user mode
+fn futex_word(addr: usize) -> Option<PhysAddr> {
+    user_pa(addr, PTEFlags::R)
+}
+
+/// Block on the `u32` at `addr` if it still holds `expected`, until a
+/// [`sys_futex_wake`] on the same word, which may be mapped at another
+/// address in another process. The kernel is not preemptible, so no wake
+/// can slip in between the check and the block.
+/// Return -EAGAIN if the word differs, -EINVAL if it is misaligned, -EFAULT
+/// if it is not readable from user mode and -EINTR when a fatal signal is
+/// pending or interrupts the wait.
+pub fn sys_futex_wait(addr: usize, expected: u32) -> isize {
+    if addr % core::mem::align_of::<u32>() != 0 {
+        return -EINVAL;
+    }
+    let pa = match futex_word(addr) {
+        Some(pa) => pa,
+        None => return -EFAULT,
+    };
+    if *pa.get_ref::<u32>() != expected {
+        return -EAGAIN;
+    }
+    // nothing would interrupt a wait started with the signal already there
+    if check_signals_of_current().is_some() {
+        return -EINTR;
+    }
+    futex_push(pa.0, current_task().unwrap());
+    block_current_and_run_next();
+    if check_signals_of_current().is_some() {
+        return -EINTR;
+    }
+    0
+}
+
+/// Wake up to `n` threads waiting on the `u32` at `addr`, return how many
+/// were woken, or the errors of [`sys_futex_wait`] for a bad address
+pub fn sys_futex_wake(addr: usize, n: usize) -> isize {
+    if addr % core::mem::align_of::<u32>() != 0 {
+        return -EINVAL;
+    }
+    match futex_word(addr) {
+        Some(pa) => futex_wake(pa.0, n) as isize,
+        None => -EFAULT,
+    }
+}
+
+/// Replace the `u32` at `addr` with `new` if it holds `expected`, in one
+/// atomic step, and return the value it held before: the swap happened if
+/// that is `expected`. With [`sys_futex_wait`] for the slow path, this is
+/// enough for a mutex in user space. `addr` must be aligned to 4 bytes.
+/// Return -EINVAL if it is not and -EFAULT if the word is not mapped
+/// readable and writable from user mode; a `u32` never looks like either.
+pub fn sys_cas(addr: usize, expected: u32, new: u32) -> isize {
+    if addr % core::mem::align_of::<u32>() != 0 {
+        return -EINVAL;
     }
+    let pa = match user_pa(addr, PTEFlags::R | PTEFlags::W) {
+        Some(pa) => pa,
+        None => return -EFAULT,
+    };
+    // the hardware atomic, on the word through the kernel's mapping of it
+    let word = unsafe { &*(pa.get_mut::<u32>() as *mut u32 as *const AtomicU32) };
+    match word.compare_exchange(expected, new, Ordering::SeqCst, Ordering::SeqCst) {
+        Ok(old) | Err(old) => old as isize,
+    }
+}
+
+/// Flag of [`sys_membarrier`]: also order the threads of the process
+/// running on other harts
+pub const MEMBARRIER_GLOBAL: usize = 1;
+
+/// Full memory barrier for the calling thread: every load and store it
+/// issued before the call is performed, and visible to the other threads
+/// of the process, before any it issues after the call returns. Return
+/// -EINVAL for unknown flags.
+///
+/// With `MEMBARRIER_GLOBAL` every other thread of the process has also gone
+/// through a full barrier by the time the call returns, so that its fast
+/// path may do without one. With a single hart, `CPU_NUM` being 1, no
+/// other thread runs while the caller does and the local fence is all it
+/// takes; several harts would need an IPI to make each of them fence.
+pub fn sys_membarrier(flags: usize) -> isize {
+    if flags & !MEMBARRIER_GLOBAL != 0 {
+        return -EINVAL;
+    }
+    // `fence rw, rw`, also keeping the compiler from moving accesses across
+    fence(Ordering::SeqCst);
+    0
+}
+
+/// Flags of [`sys_enable_deadlock_detect`]
+///
+/// With `DEADLOCK_DETECT`, a lock or down whose bookkeeping turns out
+/// inconsistent fails with -ENOTRECOVERABLE instead of being trusted.
+pub const DEADLOCK_DETECT: usize = 1;
+pub const LOCK_ORDER_CHECK: usize = 2;
+/// Warn, without failing, when two mutexes are locked in both orders
+pub const LOCKDEP: usize = 4;
+/// Record lock events in the log shared by all processes, read back with
+/// [`sys_sync_trace_read`]
+pub const SYNC_TRACE: usize = 8;
+/// Tell semaphore deadlocks apart from mutex ones by their result code
+pub const DEADLOCK_CODES: usize = 16;
+/// Fail a condvar wait after which every thread would be blocked for good
+pub const CONDVAR_STALL: usize = 32;
+/// Make the thread whose request was found to deadlock exit, instead of only
+/// failing the request
+pub const DEADLOCK_ABORT: usize = 64;
+
+/// Select the deadlock checks of the current process, `0` turns them all off.
+/// Return -1 for unknown flags.
+pub fn sys_enable_deadlock_detect(flags: usize) -> isize {
+    let known = DEADLOCK_DETECT
+        | LOCK_ORDER_CHECK
+        | LOCKDEP
+        | SYNC_TRACE
+        | DEADLOCK_CODES
+        | CONDVAR_STALL
+        | DEADLOCK_ABORT;
+    if flags & !known != 0 {
+        return -1;
+    }
+    let process = current_process();
+    let mut process_inner = process.inner_exclusive_access();
+    let detect = flags & DEADLOCK_DETECT != 0;
+    if detect && !process_inner.deadlock_det_enabled {
+        process_inner.deadlock_stats = DeadlockStats::default();
+        process_inner.last_deadlock = None;
+    }
+    process_inner.deadlock_det_enabled = detect;
+    process_inner.deadlock_codes_enabled = flags & DEADLOCK_CODES != 0;
+    process_inner.lock_order_enabled = flags & LOCK_ORDER_CHECK != 0;
+    process_inner.lockdep_enabled = flags & LOCKDEP != 0;
+    process_inner.condvar_stall_enabled = flags & CONDVAR_STALL != 0;
+    process_inner.deadlock_abort_enabled = flags & DEADLOCK_ABORT != 0;
+    process_inner.sync_trace_enabled = flags & SYNC_TRACE != 0;
+    0
+}
+
+/// Write the deadlock detector statistics of the current process to `stats`.
+/// They are reset each time detection is turned on.
+pub fn sys_deadlock_stats(stats: *mut DeadlockStats) -> isize {
+    let process = current_process();
+    let deadlock_stats = process.inner_exclusive_access().deadlock_stats;
+    *translated_refmut(current_user_token(), stats) = deadlock_stats;
+    0
+}
+
+/// Return the number of threads of the current process deadlocked right
+/// now, 0 if none, or -ENOTRECOVERABLE if the bookkeeping contradicts
+/// itself. A thread waiting on a condvar counts once no thread is left
+/// that could signal it. Works whether or not detection is enabled, so a
+/// monitor thread can poll for stalls nobody runs into by locking.
+pub fn sys_check_deadlock() -> isize {
+    let process = current_process();
+    let process_inner = process.inner_exclusive_access();
+    match process_inner.stuck_threads() {
+        Ok(stuck) => stuck.len() as isize,
+        Err(_) => -ENOTRECOVERABLE,
+    }
+}
+
+/// Drain the lock events traced in all processes and copy the most recent
+/// `len` of them to `buf`, oldest first. Older events are dropped.
+/// Return the number of events copied.
+pub fn sys_sync_trace_read(buf: *mut SyncEvent, len: usize) -> isize {
+    let token = current_user_token();
+    let events = sync_trace_drain();
+    let skip = events.len().saturating_sub(len);
+    for (i, event) in events[skip..].iter().enumerate() {
+        *translated_refmut(token, unsafe { buf.add(i) }) = *event;
+    }
+    (events.len() - skip) as isize
+}
+
+/// Copy the deadlock detector's bookkeeping of the current process to `buf`
+/// as words in the layout of `save_sync_state`. Return the number of bytes
+/// written, or -EINVAL if `len` is too small to hold them.
+pub fn sys_sync_checkpoint(buf: *mut u8, len: usize) -> isize {
+    let state = current_process().inner_exclusive_access().save_sync_state();
+    let size = state.len() * size_of::<usize>();
+    if len < size {
+        return -EINVAL;
+    }
+    let user_buf = UserBuffer::new(translated_byte_buffer(current_user_token(), buf, size));
+    let bytes = state.iter().flat_map(|word| word.to_ne_bytes());
+    for (byte_ref, byte) in user_buf.into_iter().zip(bytes) {
+        unsafe {
+            *byte_ref = byte;
+        }
+    }
+    size as isize
+}
+
+/// Roll the mutex and semaphore requests back to a state taken by
+/// [`sys_sync_checkpoint`].
+///
+/// This is a testing aid for the detector. It is only allowed while the
+/// process is paused, every other thread being blocked or exited, otherwise
+/// return -EBUSY. The numbers of mutexes, semaphores and threads, the mutex
+/// owners and the semaphore counts must all be the same as at the
+/// checkpoint, otherwise return -EINVAL: they are the ones exit releases.
+pub fn sys_sync_restore(buf: *const u8, len: usize) -> isize {
+    if len % size_of::<usize>() != 0 {
+        return -EINVAL;
+    }
+    let user_buf = UserBuffer::new(translated_byte_buffer(current_user_token(), buf, len));
+    let bytes: Vec<u8> = user_buf.into_iter().map(|byte| unsafe { *byte }).collect();
+    let state: Vec<usize> = bytes
+        .chunks(size_of::<usize>())
+        .map(|word| usize::from_ne_bytes(word.try_into().unwrap()))
+        .collect();
+    let process = current_process();
+    let mut process_inner = process.inner_exclusive_access();
+    if !process_inner.others_paused(sys_gettid() as usize) {
+        return -EBUSY;
+    }
+    if !process_inner.load_sync_state(&state) {
+        return -EINVAL;
+    }
+    0
 }
@@ -1,11 +1,115 @@
 use crate::{
-    mm::kernel_token,
-    task::{add_task, current_task, TaskControlBlock},
+    config::{
+        CPU_NUM, MAX_USER_STACK_SIZE, PAGE_SIZE, TLS_SLOTS, USER_STACK_SIZE, WAIT_REASON_LEN,
+    },
+    mm::{kernel_token, translated_byte_buffer, translated_refmut},
+    task::{
+        add_task, boost_task, current_task, current_user_token, pid2process,
+        suspend_current_and_run_next, suspend_task, TaskControlBlock, TaskStatus,
+    },
+    timer::{get_time_ms, get_time_us, timer_expiry},
     trap::{trap_handler, TrapContext},
 };
-use alloc::{sync::Arc, vec::Vec};
+use alloc::{sync::Arc, vec, vec::Vec};
 
-pub fn sys_thread_create(entry: usize, arg: usize) -> isize {
+use super::errno::{EINVAL, EPERM};
+
+/// Kind tags of [`sys_thread_blocked_on`], the resource id is in the low 32 bits
+pub const BLOCKED_ON_MUTEX: isize = 1 << 32;
+pub const BLOCKED_ON_SEMAPHORE: isize = 2 << 32;
+
+/// Scheduling statistics of a thread, filled by [`sys_task_stats`]
+#[repr(C)]
+#[derive(Debug)]
+pub struct TaskStats {
+    /// Number of times the thread has been switched in
+    pub switch_count: usize,
+    /// Time spent running, in microseconds
+    pub run_time_us: usize,
+    /// Time spent blocked, in microseconds
+    pub blocked_time_us: usize,
+    /// Part of `blocked_time_us` spent in mutex, semaphore and condvar waits
+    /// that are over
+    pub sync_wait_us: usize,
+    /// Part of `blocked_time_us` spent in sleeps that are over
+    pub sleep_us: usize,
+}
+
+/// States of [`ThreadEntry`]
+pub const THREAD_RUNNING: u8 = 0;
+pub const THREAD_READY: u8 = 1;
+pub const THREAD_BLOCKED: u8 = 2;
+
+/// A live thread of a process, filled by [`sys_proc_threads`]
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct ThreadEntry {
+    pub tid: usize,
+    /// One of the `THREAD_*` states
+    pub state: u8,
+}
+
+/// Create a thread running `entry(arg)` on a user stack of `stack_size`
+/// bytes rounded up to whole pages, `USER_STACK_SIZE` if 0. Return its tid,
+/// or -EINVAL for a stack larger than `MAX_USER_STACK_SIZE`.
+pub fn sys_thread_create(entry: usize, arg: usize, stack_size: usize) -> isize {
+    thread_create(entry, arg, stack_size, false)
+}
+
+/// Like [`sys_thread_create`], but the thread does not run before
+/// [`sys_thread_resume`], so that it can be set up first
+pub fn sys_thread_create_suspended(entry: usize, arg: usize, stack_size: usize) -> isize {
+    thread_create(entry, arg, stack_size, true)
+}
+
+/// Thread `tid` of the current process, `None` if there is no such thread
+fn task_of_current_process(tid: usize) -> Option<Arc<TaskControlBlock>> {
+    let process = current_task().unwrap().process.upgrade().unwrap();
+    let process_inner = process.inner_exclusive_access();
+    process_inner.tasks.get(tid).cloned().flatten()
+}
+
+/// Stop thread `tid` of the current process from running until
+/// [`sys_thread_resume`], for a debugger-like controller. It keeps holding
+/// its mutexes meanwhile, which is up to the caller. A blocked thread is
+/// suspended once woken, so that a resume before then leaves its wait as
+/// it was. Return -EINVAL if there is no such thread or it has exited.
+pub fn sys_thread_suspend(tid: usize) -> isize {
+    let task = match task_of_current_process(tid) {
+        Some(task) => task,
+        None => return -EINVAL,
+    };
+    if task.inner_exclusive_access().exit_code.is_some() {
+        return -EINVAL;
+    }
+    suspend_task(task);
+    0
+}
+
+/// Let thread `tid` of the current process run if it was created or put
+/// suspended, otherwise do nothing. Return -EINVAL if there is no such
+/// thread.
+pub fn sys_thread_resume(tid: usize) -> isize {
+    let task = match task_of_current_process(tid) {
+        Some(task) => task,
+        None => return -EINVAL,
+    };
+    let mut task_inner = task.inner_exclusive_access();
+    task_inner.suspend_pending = false;
+    if task_inner.suspended {
+        task_inner.suspended = false;
+        drop(task_inner);
+        add_task(task);
+    }
+    0
+}
+
+fn thread_create(entry: usize, arg: usize, stack_size: usize, suspended: bool) -> isize {
+    let ustack_size = match stack_size {
+        0 => USER_STACK_SIZE,
+        size if size <= MAX_USER_STACK_SIZE => (size + PAGE_SIZE - 1) / PAGE_SIZE * PAGE_SIZE,
+        _ => return -EINVAL,
+    };
     let task = current_task().unwrap();
     let process = task.process.upgrade().unwrap();
     // create a new thread
@@ -16,9 +120,12 @@ pub fn sys_thread_create(entry: usize, arg: usize) -> isize {
             .as_ref()
             .unwrap()
             .ustack_base,
+        ustack_size,
         true,
     ));
-    let new_task_inner = new_task.inner_exclusive_access();
+    let mut new_task_inner = new_task.inner_exclusive_access();
+    // start from the creator's pass so the new thread does not monopolize the CPU
+    new_task_inner.pass = task.inner_exclusive_access().pass;
     let new_task_res = new_task_inner.res.as_ref().unwrap();
     let new_task_tid = new_task_res.tid;
     let mut process_inner = process.inner_exclusive_access();
@@ -26,15 +133,17 @@ pub fn sys_thread_create(entry: usize, arg: usize) -> isize {
     while process_inner.tasks.len() < new_task_tid + 1 {
         process_inner.tasks.push(None);
         process_inner.mutex_request.push(None);
+        process_inner.condvar_request.push(None);
         process_inner.sem_alloc.push(Vec::new());
-        process_inner.sem_request.push(None);
+        process_inner.sem_request.push(Vec::new());
     }
     process_inner.tasks[new_task_tid] = Some(Arc::clone(&new_task));
     process_inner.mutex_request[new_task_tid] = None;
+    process_inner.condvar_request[new_task_tid] = None;
     process_inner.sem_alloc[new_task_tid] = Vec::new();
     let sem_len = process_inner.sem_alloc[0].len();
     process_inner.sem_alloc[new_task_tid].resize(sem_len, 0);
-    process_inner.sem_request[new_task_tid] = None;
+    process_inner.sem_request[new_task_tid] = vec![0; sem_len];
     let new_task_trap_cx = new_task_inner.get_trap_cx();
     *new_task_trap_cx = TrapContext::app_init_context(
         entry,
@@ -51,8 +160,16 @@ pub fn sys_thread_create(entry: usize, arg: usize) -> isize {
         tasks.push(None);
     }
     tasks[new_task_tid] = Some(Arc::clone(&new_task));
-    // add new task to scheduler
-    add_task(Arc::clone(&new_task));
+    if suspended {
+        // kept off the ready queue, blocked as far as anyone can tell
+        new_task_inner.set_status(TaskStatus::Blocking, get_time_us());
+        new_task_inner.suspended = true;
+        drop(new_task_inner);
+    } else {
+        drop(new_task_inner);
+        // add new task to scheduler
+        add_task(Arc::clone(&new_task));
+    }
     debug!("P {} thread create {}", process.pid.0, new_task_tid);
     new_task_tid as isize
 }
@@ -98,3 +215,262 @@ pub fn sys_waittid(tid: usize) -> i32 {
         -2
     }
 }
+
+/// Set the thread-local slot `key` of the current thread, return -1 if out of range
+pub fn sys_tls_set(key: usize, value: usize) -> isize {
+    if key >= TLS_SLOTS {
+        return -1;
+    }
+    current_task().unwrap().inner_exclusive_access().tls[key] = value;
+    0
+}
+
+/// Get the thread-local slot `key` of the current thread, return -1 if out of range
+pub fn sys_tls_get(key: usize) -> isize {
+    if key >= TLS_SLOTS {
+        return -1;
+    }
+    current_task().unwrap().inner_exclusive_access().tls[key] as isize
+}
+
+/// Set what the current thread is about to wait for, up to `WAIT_REASON_LEN`
+/// bytes of UTF-8 with no NUL, e.g. "waiting for DB lock". It is shown in
+/// the scheduler trace and deadlock reports and cleared once the thread is
+/// woken, an empty string clears it right away. Return -EINVAL for a string
+/// too long or not valid.
+pub fn sys_thread_set_waitreason(reason: *const u8, len: usize) -> isize {
+    if len > WAIT_REASON_LEN {
+        return -EINVAL;
+    }
+    let mut bytes = [0u8; WAIT_REASON_LEN];
+    let mut copied = 0;
+    for part in translated_byte_buffer(current_user_token(), reason, len) {
+        bytes[copied..copied + part.len()].copy_from_slice(part);
+        copied += part.len();
+    }
+    if bytes[..len].contains(&0) || core::str::from_utf8(&bytes[..len]).is_err() {
+        return -EINVAL;
+    }
+    current_task().unwrap().inner_exclusive_access().wait_reason = bytes;
+    0
+}
+
+/// Describe what thread `tid` of the current process is waiting for:
+/// `BLOCKED_ON_MUTEX | mutex_id` or `BLOCKED_ON_SEMAPHORE | sem_id`,
+/// -1 if it waits for nothing or does not exist
+pub fn sys_thread_blocked_on(tid: usize) -> isize {
+    let process = current_task().unwrap().process.upgrade().unwrap();
+    let process_inner = process.inner_exclusive_access();
+    if tid >= process_inner.tasks.len() || process_inner.tasks[tid].is_none() {
+        return -1;
+    }
+    if let Some(mutex_id) = process_inner.mutex_request[tid] {
+        BLOCKED_ON_MUTEX | mutex_id as isize
+    } else if let Some(sem_id) = process_inner.sem_request[tid]
+        .iter()
+        .position(|&num| num > 0)
+    {
+        BLOCKED_ON_SEMAPHORE | sem_id as isize
+    } else {
+        -1
+    }
+}
+
+/// Return the milliseconds until the pending timer of thread `tid` of the
+/// current process fires, 0 if it has expired but the thread has not run
+/// since, -1 if the thread is not sleeping or does not exist
+pub fn sys_thread_sleep_remaining(tid: usize) -> isize {
+    let task = match task_of_current_process(tid) {
+        Some(task) => task,
+        None => return -1,
+    };
+    match timer_expiry(&task) {
+        Some(expire_ms) => {
+            let remaining = expire_ms.saturating_sub(get_time_ms());
+            remaining.min(isize::MAX as usize) as isize
+        }
+        // popped already, but still on its way out of the sleep
+        None if task.inner_exclusive_access().timer_fired => 0,
+        None => -1,
+    }
+}
+
+/// Take thread `tid` of the current process off the CPU once it has run for
+/// `ms` milliseconds in total, until [`sys_clear_cpu_limit`]. The limit is
+/// checked on every timer tick. Return -1 if there is no such thread.
+pub fn sys_set_cpu_limit(tid: usize, ms: usize) -> isize {
+    let task = match task_of_current_process(tid) {
+        Some(task) => task,
+        None => return -1,
+    };
+    task.inner_exclusive_access().cpu_limit_us = Some(ms.saturating_mul(1000));
+    0
+}
+
+/// Remove the CPU limit of thread `tid` of the current process, resuming it
+/// if the limit had suspended it. Return -1 if there is no such thread.
+pub fn sys_clear_cpu_limit(tid: usize) -> isize {
+    let task = match task_of_current_process(tid) {
+        Some(task) => task,
+        None => return -1,
+    };
+    let mut task_inner = task.inner_exclusive_access();
+    task_inner.cpu_limit_us = None;
+    if task_inner.limited {
+        task_inner.limited = false;
+        drop(task_inner);
+        add_task(task);
+    }
+    0
+}
+
+/// Let thread `tid` of the current process run only on the harts whose bit
+/// is set in `cpu_mask`. Bits of harts beyond `CPU_NUM` are kept for when
+/// there are more, but the mask must include one of the present harts, or
+/// the thread could never run: return -EINVAL if it does not or if it has
+/// the top bit set, which [`sys_get_affinity`] could not return. Return -1
+/// for an empty mask or if there is no such thread.
+pub fn sys_set_affinity(tid: usize, cpu_mask: usize) -> isize {
+    if cpu_mask == 0 {
+        return -1;
+    }
+    if cpu_mask & ((1 << CPU_NUM) - 1) == 0 || cpu_mask > isize::MAX as usize {
+        return -EINVAL;
+    }
+    let task = match task_of_current_process(tid) {
+        Some(task) => task,
+        None => return -1,
+    };
+    task.inner_exclusive_access().cpu_mask = cpu_mask;
+    0
+}
+
+/// Return the mask of harts thread `tid` of the current process may run on,
+/// all present harts unless set by [`sys_set_affinity`]. Return -1 if there
+/// is no such thread.
+pub fn sys_get_affinity(tid: usize) -> isize {
+    let task = match task_of_current_process(tid) {
+        Some(task) => task,
+        None => return -1,
+    };
+    let cpu_mask = task.inner_exclusive_access().cpu_mask;
+    cpu_mask as isize
+}
+
+/// Write the scheduling statistics of thread `tid` of the current process
+/// to `stats`, the time spent in its current status included.
+/// Return -1 if there is no such thread.
+pub fn sys_task_stats(tid: usize, stats: *mut TaskStats) -> isize {
+    let task = match task_of_current_process(tid) {
+        Some(task) => task,
+        None => return -1,
+    };
+    let task_inner = task.inner_exclusive_access();
+    let elapsed = get_time_us() - task_inner.status_since_us;
+    let mut run_time_us = task_inner.run_time_us;
+    let mut blocked_time_us = task_inner.blocked_time_us;
+    match task_inner.task_status {
+        TaskStatus::Running => run_time_us += elapsed,
+        TaskStatus::Blocking => blocked_time_us += elapsed,
+        _ => {}
+    }
+    let switch_count = task_inner.switch_count;
+    let sync_wait_us = task_inner.sync_wait_us;
+    let sleep_us = task_inner.sleep_us;
+    drop(task_inner);
+    *translated_refmut(current_user_token(), stats) = TaskStats {
+        switch_count,
+        run_time_us,
+        blocked_time_us,
+        sync_wait_us,
+        sleep_us,
+    };
+    0
+}
+
+/// Tids of the threads of the current process that have not exited
+fn live_tids() -> Vec<usize> {
+    let process = current_task().unwrap().process.upgrade().unwrap();
+    let process_inner = process.inner_exclusive_access();
+    process_inner
+        .tasks
+        .iter()
+        .enumerate()
+        .filter_map(|(tid, task)| task.as_ref().map(|task| (tid, task)))
+        .filter(|(_, task)| task.inner_exclusive_access().exit_code.is_none())
+        .map(|(tid, _)| tid)
+        .collect()
+}
+
+/// Return the number of live threads in the current process
+pub fn sys_thread_count() -> isize {
+    live_tids().len() as isize
+}
+
+/// Write the tids of live threads into `buf`, at most `len` of them.
+/// Return the number of tids written.
+pub fn sys_thread_list(buf: *mut usize, len: usize) -> isize {
+    let token = current_user_token();
+    let tids = live_tids();
+    let n = tids.len().min(len);
+    for (i, tid) in tids.into_iter().take(n).enumerate() {
+        *translated_refmut(token, unsafe { buf.add(i) }) = tid;
+    }
+    n as isize
+}
+
+/// Write the live threads of process `pid` into `buf`, at most `len` of
+/// them, in tid order. Return the number of live threads, which is more than
+/// `len` when `buf` was too short. Only the current process can be listed:
+/// return -EPERM for another process and -1 for an unknown pid.
+pub fn sys_proc_threads(pid: usize, buf: *mut ThreadEntry, len: usize) -> isize {
+    let token = current_user_token();
+    let process = current_task().unwrap().process.upgrade().unwrap();
+    if process.getpid() != pid {
+        return match pid2process(pid) {
+            Some(_) => -EPERM,
+            None => -1,
+        };
+    }
+    let process_inner = process.inner_exclusive_access();
+    let threads: Vec<ThreadEntry> = process_inner
+        .tasks
+        .iter()
+        .enumerate()
+        .filter_map(|(tid, task)| {
+            let task_inner = task.as_ref()?.inner_exclusive_access();
+            if task_inner.exit_code.is_some() {
+                return None;
+            }
+            let state = match task_inner.task_status {
+                TaskStatus::Running => THREAD_RUNNING,
+                TaskStatus::Blocking => THREAD_BLOCKED,
+                _ => THREAD_READY,
+            };
+            Some(ThreadEntry { tid, state })
+        })
+        .collect();
+    drop(process_inner);
+    for (i, entry) in threads.iter().take(len).enumerate() {
+        *translated_refmut(token, unsafe { buf.add(i) }) = *entry;
+    }
+    threads.len() as isize
+}
+
+/// Yield the CPU, preferring thread `tid` of the current process as the next
+/// to run. The target only inherits the caller's pass, so this is a hint:
+/// a task with a smaller pass still runs first under stride scheduling.
+/// Return -1 and yield normally if the target is not ready to run.
+pub fn sys_yield_to(tid: usize) -> isize {
+    let task = current_task().unwrap();
+    let target = task_of_current_process(tid);
+    let pass = task.inner_exclusive_access().pass;
+    let boosted = target.map_or(false, |target| boost_task(&target, pass));
+    drop(task);
+    suspend_current_and_run_next();
+    if boosted {
+        0
+    } else {
+        -1
+    }
+}
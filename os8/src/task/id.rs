@@ -1,5 +1,5 @@
 use super::ProcessControlBlock;
-use crate::config::{KERNEL_STACK_SIZE, PAGE_SIZE, TRAMPOLINE, TRAP_CONTEXT, USER_STACK_SIZE};
+use crate::config::{KERNEL_STACK_SIZE, MAX_USER_STACK_SIZE, PAGE_SIZE, TRAMPOLINE, TRAP_CONTEXT};
 use crate::mm::{MapPermission, PhysPageNum, VirtAddr, KERNEL_SPACE};
 use crate::sync::UPSafeCell;
 use alloc::{
@@ -113,6 +113,8 @@ impl KernelStack {
 pub struct TaskUserRes {
     pub tid: usize,
     pub ustack_base: usize,
+    /// Bytes of user stack, a multiple of `PAGE_SIZE`
+    pub ustack_size: usize,
     pub process: Weak<ProcessControlBlock>,
 }
 
@@ -120,20 +122,25 @@ fn trap_cx_bottom_from_tid(tid: usize) -> usize {
     TRAP_CONTEXT - tid * PAGE_SIZE
 }
 
-fn ustack_bottom_from_tid(ustack_base: usize, tid: usize) -> usize {
-    ustack_base + tid * (PAGE_SIZE + USER_STACK_SIZE)
+/// Every thread has a slot room for the largest stack, its own stack is at
+/// the top of it, so stacks of any size never overlap and a guard page at
+/// least is left unmapped below each one
+fn ustack_top_from_tid(ustack_base: usize, tid: usize) -> usize {
+    ustack_base + tid * (PAGE_SIZE + MAX_USER_STACK_SIZE) + MAX_USER_STACK_SIZE
 }
 
 impl TaskUserRes {
     pub fn new(
         process: Arc<ProcessControlBlock>,
         ustack_base: usize,
+        ustack_size: usize,
         alloc_user_res: bool,
     ) -> Self {
         let tid = process.inner_exclusive_access().alloc_tid();
         let task_user_res = Self {
             tid,
             ustack_base,
+            ustack_size,
             process: Arc::downgrade(&process),
         };
         if alloc_user_res {
@@ -146,8 +153,8 @@ impl TaskUserRes {
         let process = self.process.upgrade().unwrap();
         let mut process_inner = process.inner_exclusive_access();
         // alloc user stack
-        let ustack_bottom = ustack_bottom_from_tid(self.ustack_base, self.tid);
-        let ustack_top = ustack_bottom + USER_STACK_SIZE;
+        let ustack_top = self.ustack_top();
+        let ustack_bottom = ustack_top - self.ustack_size;
         process_inner.memory_set.insert_framed_area(
             ustack_bottom.into(),
             ustack_top.into(),
@@ -168,7 +175,7 @@ impl TaskUserRes {
         let process = self.process.upgrade().unwrap();
         let mut process_inner = process.inner_exclusive_access();
         // dealloc ustack manually
-        let ustack_bottom_va: VirtAddr = ustack_bottom_from_tid(self.ustack_base, self.tid).into();
+        let ustack_bottom_va: VirtAddr = (self.ustack_top() - self.ustack_size).into();
         process_inner
             .memory_set
             .remove_area_with_start_vpn(ustack_bottom_va.into());
@@ -214,7 +221,7 @@ impl TaskUserRes {
         self.ustack_base
     }
     pub fn ustack_top(&self) -> usize {
-        ustack_bottom_from_tid(self.ustack_base, self.tid) + USER_STACK_SIZE
+        ustack_top_from_tid(self.ustack_base, self.tid)
     }
 }
 
@@ -4,31 +4,203 @@
 //! Other CPU process monitoring functions are in Processor.
 
 
-use super::TaskControlBlock;
+use super::{ProcessControlBlock, TaskControlBlock, TaskStatus};
+use crate::config::{DEFAULT_AGING_TICKS, WAIT_REASON_LEN};
 use crate::sync::UPSafeCell;
-use alloc::collections::VecDeque;
+use crate::timer::{get_time_ticks, get_time_us};
+use alloc::collections::{BTreeMap, VecDeque};
 use alloc::sync::Arc;
+use alloc::vec::Vec;
 use lazy_static::*;
 
+/// How [`TaskManager::fetch`] picks the next task, numbered as taken by
+/// `sys_sched_setpolicy`. Every policy keeps the `pass` of the tasks up to
+/// date, so switching back to stride scheduling stays fair.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum SchedPolicy {
+    /// Smallest `pass` first, stepped by `BIG_STRIDE / priority`
+    Stride = 0,
+    /// Ready queue order, reads no per-task field
+    RoundRobin = 1,
+    /// Highest `priority` first, ready queue order among equals
+    Priority = 2,
+    /// Highest `priority` first, a pseudo-random one among equals drawn from
+    /// the seed of `sys_sched_set_seed`. A test aid only entered through
+    /// that call, `sys_sched_setpolicy` does not take it.
+    Seeded = 3,
+}
+
+impl SchedPolicy {
+    pub fn from_id(id: usize) -> Option<Self> {
+        match id {
+            0 => Some(Self::Stride),
+            1 => Some(Self::RoundRobin),
+            2 => Some(Self::Priority),
+            _ => None,
+        }
+    }
+}
+
 pub struct TaskManager {
     ready_queue: VecDeque<Arc<TaskControlBlock>>,
+    /// Ticks a ready task may wait before it is boosted, 0 disables aging
+    aging_interval: usize,
+    policy: SchedPolicy,
+    /// State of the generator drawing among equals under `Seeded`
+    seed: u64,
 }
 
-// YOUR JOB: FIFO->Stride
-/// A simple FIFO scheduler.
+/// A stride scheduler by default, the ready task with the smallest pass runs
+/// next. Tasks boosted by aging run before all others, except under round
+/// robin which cannot starve anyone.
 impl TaskManager {
     pub fn new() -> Self {
         Self {
             ready_queue: VecDeque::new(),
+            aging_interval: DEFAULT_AGING_TICKS,
+            policy: SchedPolicy::Stride,
+            seed: 0,
         }
     }
     /// Add process back to ready queue
     pub fn add(&mut self, task: Arc<TaskControlBlock>) {
+        let mut task_inner = task.inner_exclusive_access();
+        if task_inner.task_status == TaskStatus::Blocking {
+            // woken while suspended, it waits for a resume instead
+            if task_inner.suspend_pending || task_inner.suspended {
+                task_inner.suspend_pending = false;
+                task_inner.suspended = true;
+                return;
+            }
+            task_inner.set_status(TaskStatus::Ready, get_time_us());
+            task_inner.wait_reason = [0; WAIT_REASON_LEN];
+        }
+        // aging only counts the time spent waiting in the ready queue
+        task_inner.last_run_tick = get_time_ticks();
+        drop(task_inner);
         self.ready_queue.push_back(task);
     }
-    /// Take a process out of the ready queue
+    /// Take the next process to run out of the ready queue, as chosen by
+    /// the policy
     pub fn fetch(&mut self) -> Option<Arc<TaskControlBlock>> {
-        self.ready_queue.pop_front()
+        let id = match self.policy {
+            SchedPolicy::Stride => self.pick_stride(),
+            SchedPolicy::RoundRobin => Some(0),
+            SchedPolicy::Priority => self.pick_priority(),
+            SchedPolicy::Seeded => self.pick_seeded(),
+        };
+        let task = self.ready_queue.remove(id?)?;
+        task.inner_exclusive_access().step_pass();
+        Some(task)
+    }
+    /// Position of the task with the smallest pass, boosted ones first
+    fn pick_stride(&self) -> Option<usize> {
+        let mut min: Option<(usize, bool, usize)> = None;
+        for (id, task) in self.ready_queue.iter().enumerate() {
+            let task_inner = task.inner_exclusive_access();
+            let (boosted, pass) = (task_inner.boosted, task_inner.pass);
+            // strides never exceed BIG_STRIDE / 2, so the signed difference
+            // stays correct after the pass wraps around
+            match min {
+                Some((_, min_boosted, _)) if min_boosted && !boosted => {}
+                Some((_, min_boosted, min_pass))
+                    if min_boosted == boosted && (pass.wrapping_sub(min_pass) as isize) >= 0 => {}
+                _ => min = Some((id, boosted, pass)),
+            }
+        }
+        min.map(|(id, _, _)| id)
+    }
+    /// Position of the first task of the highest priority, boosted ones first
+    fn pick_priority(&self) -> Option<usize> {
+        let mut max: Option<(usize, (bool, usize))> = None;
+        for (id, task) in self.ready_queue.iter().enumerate() {
+            let task_inner = task.inner_exclusive_access();
+            let key = (task_inner.boosted, task_inner.priority);
+            if max.map_or(true, |(_, max_key)| key > max_key) {
+                max = Some((id, key));
+            }
+        }
+        max.map(|(id, _)| id)
+    }
+    /// Position of a task of the highest priority drawn from `seed`, boosted
+    /// ones first. The same seed and the same sequence of ready queues give
+    /// the same picks.
+    fn pick_seeded(&mut self) -> Option<usize> {
+        let keys: Vec<(bool, usize)> = self
+            .ready_queue
+            .iter()
+            .map(|task| {
+                let task_inner = task.inner_exclusive_access();
+                (task_inner.boosted, task_inner.priority)
+            })
+            .collect();
+        let max = *keys.iter().max()?;
+        let equals = keys.iter().filter(|&&key| key == max).count();
+        // Knuth's MMIX LCG, the high bits are the random ones
+        self.seed = self
+            .seed
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        let nth = (self.seed >> 33) as usize % equals;
+        keys.iter()
+            .enumerate()
+            .filter(|&(_, &key)| key == max)
+            .nth(nth)
+            .map(|(id, _)| id)
+    }
+    /// Switch to `policy` keeping the ready tasks, return the previous one
+    pub fn set_policy(&mut self, policy: SchedPolicy) -> SchedPolicy {
+        core::mem::replace(&mut self.policy, policy)
+    }
+    /// Boost the ready tasks that have not run for `aging_interval` ticks
+    pub fn age(&mut self, now_tick: usize) {
+        if self.aging_interval == 0 {
+            return;
+        }
+        for task in self.ready_queue.iter() {
+            let mut task_inner = task.inner_exclusive_access();
+            if now_tick - task_inner.last_run_tick >= self.aging_interval {
+                task_inner.boosted = true;
+            }
+        }
+    }
+    /// Switch to `Seeded` drawing from `seed`, return the previous policy
+    pub fn set_seed(&mut self, seed: u64) -> SchedPolicy {
+        self.seed = seed;
+        self.set_policy(SchedPolicy::Seeded)
+    }
+    /// Set the aging interval in ticks, return the previous one
+    pub fn set_aging_interval(&mut self, ticks: usize) -> usize {
+        core::mem::replace(&mut self.aging_interval, ticks)
+    }
+    /// Lower the pass of `task` to at most `pass` if it is in the ready queue,
+    /// return whether it was found
+    pub fn boost(&mut self, task: &Arc<TaskControlBlock>, pass: usize) -> bool {
+        match self.ready_queue.iter().find(|t| Arc::ptr_eq(t, task)) {
+            Some(task) => {
+                let mut task_inner = task.inner_exclusive_access();
+                if (pass.wrapping_sub(task_inner.pass) as isize) < 0 {
+                    task_inner.pass = pass;
+                }
+                true
+            }
+            None => false,
+        }
+    }
+    /// Number of tasks waiting in the ready queue
+    pub fn ready_count(&self) -> usize {
+        self.ready_queue.len()
+    }
+    /// Remove a process from the ready queue if it is there
+    pub fn remove(&mut self, task: Arc<TaskControlBlock>) {
+        if let Some((id, _)) = self
+            .ready_queue
+            .iter()
+            .enumerate()
+            .find(|(_, t)| Arc::ptr_eq(t, &task))
+        {
+            self.ready_queue.remove(id);
+        }
     }
 }
 
@@ -36,6 +208,9 @@ lazy_static! {
     /// TASK_MANAGER instance through lazy_static!
     pub static ref TASK_MANAGER: UPSafeCell<TaskManager> =
         unsafe { UPSafeCell::new(TaskManager::new()) };
+    /// Map from pid to the live process owning it
+    pub static ref PID2PCB: UPSafeCell<BTreeMap<usize, Arc<ProcessControlBlock>>> =
+        unsafe { UPSafeCell::new(BTreeMap::new()) };
 }
 
 pub fn add_task(task: Arc<TaskControlBlock>) {
@@ -45,3 +220,63 @@ pub fn add_task(task: Arc<TaskControlBlock>) {
 pub fn fetch_task() -> Option<Arc<TaskControlBlock>> {
     TASK_MANAGER.exclusive_access().fetch()
 }
+
+pub fn age_ready_tasks() {
+    TASK_MANAGER.exclusive_access().age(get_time_ticks());
+}
+
+pub fn set_aging_interval(ticks: usize) -> usize {
+    TASK_MANAGER.exclusive_access().set_aging_interval(ticks)
+}
+
+pub fn set_sched_policy(policy: SchedPolicy) -> SchedPolicy {
+    TASK_MANAGER.exclusive_access().set_policy(policy)
+}
+
+pub fn set_sched_seed(seed: u64) -> SchedPolicy {
+    TASK_MANAGER.exclusive_access().set_seed(seed)
+}
+
+pub fn boost_task(task: &Arc<TaskControlBlock>, pass: usize) -> bool {
+    TASK_MANAGER.exclusive_access().boost(task, pass)
+}
+
+pub fn remove_task(task: Arc<TaskControlBlock>) {
+    TASK_MANAGER.exclusive_access().remove(task);
+}
+
+pub fn ready_task_count() -> usize {
+    TASK_MANAGER.exclusive_access().ready_count()
+}
+
+/// Number of threads of all processes that are blocked
+pub fn blocked_task_count() -> usize {
+    let map = PID2PCB.exclusive_access();
+    map.values()
+        .map(|process| {
+            let process_inner = process.inner_exclusive_access();
+            process_inner
+                .tasks
+                .iter()
+                .flatten()
+                .filter(|task| task.inner_exclusive_access().task_status == TaskStatus::Blocking)
+                .count()
+        })
+        .sum()
+}
+
+pub fn pid2process(pid: usize) -> Option<Arc<ProcessControlBlock>> {
+    let map = PID2PCB.exclusive_access();
+    map.get(&pid).map(Arc::clone)
+}
+
+pub fn insert_into_pid2process(pid: usize, process: Arc<ProcessControlBlock>) {
+    PID2PCB.exclusive_access().insert(pid, process);
+}
+
+pub fn remove_from_pid2process(pid: usize) {
+    let mut map = PID2PCB.exclusive_access();
+    if map.remove(&pid).is_none() {
+        panic!("cannot find pid {} in pid2process!", pid);
+    }
+}
@@ -15,27 +15,37 @@ mod kthread;
 mod manager;
 mod process;
 mod processor;
+mod sched_trace;
+mod signal;
 pub mod stackless_coroutine;
 mod switch;
 #[allow(clippy::module_inception)]
 mod task;
 
-pub use crate::syscall::process::TaskInfo;
+pub use crate::syscall::process::{RUsage, TaskInfo};
 use crate::{
     fs::{open_file, OpenFlags},
+    sync::futex_cancel,
     task::id::TaskUserRes,
+    timer::{get_time_us, idle_until_next_timer, remove_timer},
 };
 use alloc::{sync::Arc, vec::Vec};
 pub use context::TaskContext;
 pub use id::{kstack_alloc, pid_alloc, KernelStack, PidHandle};
 use lazy_static::*;
-pub use manager::add_task;
-use manager::fetch_task;
+pub use manager::set_sched_seed;
+pub use manager::{add_task, age_ready_tasks, boost_task, pid2process, set_aging_interval};
+pub use manager::{blocked_task_count, ready_task_count, set_sched_policy, SchedPolicy};
+use manager::{fetch_task, remove_from_pid2process, remove_task};
 use process::ProcessControlBlock;
+pub use process::{next_sync_gen, sync_id, sync_slot, DeadlockRequest, DeadlockStats, MutexStats};
 pub use processor::{
     current_process, current_task, current_trap_cx, current_trap_cx_user_va, current_user_token,
     run_tasks, schedule, take_current_task,
 };
+pub use sched_trace::{sched_trace_enable, sched_trace_recent, SchedEvent};
+use sched_trace::{sched_trace_leave, SCHED_BLOCK, SCHED_EXIT, SCHED_PREEMPT, SCHED_YIELD};
+pub use signal::{SignalFlags, SIGINT, SIGKILL, SIGUSR1};
 pub use stackless_coroutine::kernel_stackless_coroutine_test;
 use switch::__switch;
 pub use task::{TaskControlBlock, TaskStatus};
@@ -44,13 +54,74 @@ pub fn block_current_and_run_next() {
     let task = take_current_task().unwrap();
     let mut task_inner = task.inner_exclusive_access();
     let task_cx_ptr = &mut task_inner.task_cx as *mut TaskContext;
-    task_inner.task_status = TaskStatus::Blocking;
+    task_inner.set_status(TaskStatus::Blocking, get_time_us());
     drop(task_inner);
+    sched_trace_leave(&task, SCHED_BLOCK);
+    if let Some(process) = task.process.upgrade() {
+        process.inner_exclusive_access().rusage.nvcsw += 1;
+    }
     schedule(task_cx_ptr);
 }
 
 /// Make current task suspended and switch to the next task
 pub fn suspend_current_and_run_next() {
+    if let Some(process) = current_task().unwrap().process.upgrade() {
+        process.inner_exclusive_access().rusage.nvcsw += 1;
+    }
+    requeue_current_and_run_next(SCHED_YIELD);
+}
+
+/// Suspend the current task whose time slice is used up
+pub fn preempt_current_and_run_next() {
+    if let Some(process) = current_task().unwrap().process.upgrade() {
+        process.inner_exclusive_access().rusage.nivcsw += 1;
+    }
+    requeue_current_and_run_next(SCHED_PREEMPT);
+}
+
+/// Take the current task off the CPU for having reached its `cpu_limit_us`.
+/// It stays blocked until its limit is cleared.
+pub fn limit_current_and_run_next() {
+    let task = take_current_task().unwrap();
+    let mut task_inner = task.inner_exclusive_access();
+    let task_cx_ptr = &mut task_inner.task_cx as *mut TaskContext;
+    task_inner.set_status(TaskStatus::Blocking, get_time_us());
+    task_inner.limited = true;
+    drop(task_inner);
+    sched_trace_leave(&task, SCHED_BLOCK);
+    if let Some(process) = task.process.upgrade() {
+        process.inner_exclusive_access().rusage.nivcsw += 1;
+    }
+    schedule(task_cx_ptr);
+}
+
+/// Keep `task` from running until `sys_thread_resume`, whatever it holds.
+/// A ready task leaves the ready queue at once and the current one blocks,
+/// a blocked one goes on waiting and is only suspended once woken.
+pub fn suspend_task(task: Arc<TaskControlBlock>) {
+    if Arc::ptr_eq(&task, &current_task().unwrap()) {
+        task.inner_exclusive_access().suspended = true;
+        drop(task);
+        block_current_and_run_next();
+        return;
+    }
+    let mut task_inner = task.inner_exclusive_access();
+    if task_inner.suspended {
+        return;
+    }
+    if task_inner.task_status == TaskStatus::Ready {
+        // blocked as far as anyone can tell, like a thread created suspended
+        task_inner.set_status(TaskStatus::Blocking, get_time_us());
+        task_inner.suspended = true;
+        drop(task_inner);
+        remove_task(task);
+    } else {
+        task_inner.suspend_pending = true;
+    }
+}
+
+/// Put the current task back in the ready queue and switch to the next task
+fn requeue_current_and_run_next(reason: usize) {
     // There must be an application running.
     let task = take_current_task().unwrap();
 
@@ -59,9 +130,15 @@ pub fn suspend_current_and_run_next() {
 
     let task_cx_ptr = &mut task_inner.task_cx as *mut TaskContext;
     // Change status to Ready
-    task_inner.task_status = TaskStatus::Ready;
+    task_inner.set_status(TaskStatus::Ready, get_time_us());
     drop(task_inner);
     // ---- release current PCB
+    sched_trace_leave(&task, reason);
+
+    // a lone task yielding waits for something a timer brings about
+    if reason == SCHED_YIELD && ready_task_count() == 0 {
+        idle_until_next_timer();
+    }
 
     // push back to ready queue.
     add_task(task);
@@ -73,6 +150,7 @@ pub fn suspend_current_and_run_next() {
 pub fn exit_current_and_run_next(exit_code: i32) {
     // take from Processor
     let task = take_current_task().unwrap();
+    sched_trace_leave(&task, SCHED_EXIT);
     // **** access current TCB exclusively
     let mut task_inner = task.inner_exclusive_access();
     let process = task.process.upgrade().unwrap();
@@ -80,6 +158,9 @@ pub fn exit_current_and_run_next(exit_code: i32) {
     // Record exit code
     task_inner.exit_code = Some(exit_code);
     task_inner.res = None;
+    // the mutexes it holds are released below
+    task_inner.held_ranks.clear();
+    let run_time_us = task_inner.run_time_us + (get_time_us() - task_inner.status_since_us);
 
     // here we do not remove the thread since we are still using the kstack
     // it will be deallocated when sys_waittid is called
@@ -87,7 +168,36 @@ pub fn exit_current_and_run_next(exit_code: i32) {
     drop(task);
     // debug!("task {} dropped", tid);
 
+    // the thread waits for nothing any more, keep the deadlock detector consistent
+    let mut process_inner = process.inner_exclusive_access();
+    process_inner.rusage.utime_us += run_time_us;
+    process_inner.mutex_request[tid] = None;
+    process_inner.condvar_request[tid] = None;
+    process_inner.sem_request[tid].fill(0);
+    // release whatever it still holds so that other threads are not blocked forever
+    for mutex_id in 0..process_inner.mutex_alloc.len() {
+        if process_inner.mutex_alloc[mutex_id] == Some(tid) {
+            let owner = match &process_inner.mutex_list[mutex_id] {
+                Some(mutex) => mutex.unlock(),
+                None => None,
+            };
+            process_inner.mutex_released(mutex_id, owner);
+        }
+    }
+    for sem_id in 0..process_inner.sem_alloc[tid].len() {
+        let held = process_inner.sem_alloc[tid][sem_id];
+        process_inner.sem_alloc[tid][sem_id] = 0;
+        let granted = match &process_inner.semaphore_list[sem_id] {
+            Some(sem) if held > 0 => sem.up_n(held),
+            _ => Vec::new(),
+        };
+        process_inner.sem_released(sem_id, held, granted);
+    }
+    process_inner.wake_semop_waiters();
+    drop(process_inner);
+
     if tid == 0 {
+        remove_from_pid2process(process.getpid());
         let mut process_inner = process.inner_exclusive_access();
         // mark this process as a zombie process
         process_inner.is_zombie = true;
@@ -113,6 +223,10 @@ pub fn exit_current_and_run_next(exit_code: i32) {
         // otherwise they will be deallocated twice
         for task in process_inner.tasks.iter().filter(|t| t.is_some()) {
             let task = task.as_ref().unwrap();
+            // other threads may still be ready or sleeping, they must never run again
+            remove_task(Arc::clone(task));
+            remove_timer(Arc::clone(task));
+            futex_cancel(task);
             let mut task_inner = task.inner_exclusive_access();
             if let Some(res) = task_inner.res.take() {
                 recycle_res.push(res);
@@ -138,6 +252,86 @@ pub fn exit_current_and_run_next(exit_code: i32) {
     schedule(&mut _unused as *mut _);
 }
 
+/// Exit code and message of a fatal signal pending on the current process
+pub fn check_signals_of_current() -> Option<(i32, &'static str)> {
+    let process = current_process();
+    let process_inner = process.inner_exclusive_access();
+    process_inner.signals.check_error()
+}
+
+/// Exit code of the current process exiting as a whole, if it is
+pub fn group_exit_of_current() -> Option<i32> {
+    current_process().inner_exclusive_access().group_exit
+}
+
+/// Exit code of the current thread if it was picked as a deadlock victim
+pub fn abort_code_of_current() -> Option<i32> {
+    current_task().unwrap().inner_exclusive_access().abort_code
+}
+
+/// Wake `task` blocked in `sys_poll` or a condvar wait, cancelling its
+/// timeout, unless it was already woken by another of the files or the
+/// timer it waits on
+pub fn wake_blocked(task: Arc<TaskControlBlock>) {
+    if task.inner_exclusive_access().task_status != TaskStatus::Blocking {
+        return;
+    }
+    remove_timer(Arc::clone(&task));
+    add_task(task);
+}
+
+/// Wake the threads of `process` blocked in `sys_sleep`, on one of its
+/// mutexes, semaphores and condvars, on a futex or on one of its files, as
+/// well as those suspended by their CPU limit, so that they notice a fatal
+/// signal instead of waiting forever
+pub fn interrupt_blocked_tasks(process: &Arc<ProcessControlBlock>) {
+    let mut process_inner = process.inner_exclusive_access();
+    process_inner.wake_semop_waiters();
+    let tasks: Vec<_> = process_inner.tasks.iter().flatten().cloned().collect();
+    let mutexes: Vec<_> = process_inner.mutex_list.iter().flatten().cloned().collect();
+    let sems: Vec<_> = process_inner
+        .semaphore_list
+        .iter()
+        .flatten()
+        .cloned()
+        .collect();
+    let condvars: Vec<_> = process_inner
+        .condvar_list
+        .iter()
+        .flatten()
+        .cloned()
+        .collect();
+    let mqs: Vec<_> = process_inner.mq_list.iter().flatten().cloned().collect();
+    let files: Vec<_> = process_inner.fd_table.iter().flatten().cloned().collect();
+    drop(process_inner);
+    for task in tasks {
+        let mut task_inner = task.inner_exclusive_access();
+        let held = task_inner.limited || task_inner.suspended;
+        task_inner.limited = false;
+        task_inner.suspended = false;
+        task_inner.suspend_pending = false;
+        drop(task_inner);
+        if remove_timer(Arc::clone(&task)) || futex_cancel(&task) || held {
+            add_task(task);
+        }
+    }
+    for mutex in mutexes {
+        mutex.interrupt();
+    }
+    for sem in sems {
+        sem.interrupt();
+    }
+    for condvar in condvars {
+        condvar.interrupt();
+    }
+    for mq in mqs {
+        mq.interrupt();
+    }
+    for file in files {
+        file.interrupt();
+    }
+}
+
 lazy_static! {
     /// Creation of initial process
     ///
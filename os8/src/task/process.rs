@@ -1,9 +1,14 @@
 use super::id::RecycleAllocator;
-use super::{add_task, pid_alloc, PidHandle, TaskControlBlock};
+use super::manager::insert_into_pid2process;
+use super::{add_task, current_process, current_task, pid_alloc, PidHandle, RUsage, SignalFlags};
+use super::{TaskControlBlock, TaskStatus};
+use crate::config::SYNC_ID_GEN_SHIFT;
+use crate::config::{DEADLOCK_REPORT_EVERY, DEFAULT_SYNC_QUOTA, USER_STACK_SIZE};
 use crate::fs::{File, Stdin, Stdout};
 use crate::mm::{translated_refmut, MemorySet, KERNEL_SPACE};
-use crate::sync::{Condvar, Mutex, Semaphore, UPSafeCell};
+use crate::sync::{sync_trace_record, Condvar, MsgQueue, Mutex, Semaphore, UPSafeCell, SYNC_LOCK};
 use crate::trap::{trap_handler, TrapContext};
+use alloc::collections::{BTreeMap, BTreeSet, VecDeque};
 use alloc::string::String;
 use alloc::sync::{Arc, Weak};
 use alloc::vec;
@@ -17,6 +22,85 @@ pub struct ProcessControlBlock {
     inner: UPSafeCell<ProcessControlBlockInner>,
 }
 
+/// Work done by the deadlock detector since it was last enabled
+#[repr(C)]
+#[derive(Clone, Copy, Default)]
+pub struct DeadlockStats {
+    /// Number of times the detector ran
+    pub runs: usize,
+    /// Number of deadlocks it reported
+    pub deadlocks: usize,
+    /// Number of thread requests it checked, over all runs
+    pub nodes_visited: usize,
+    /// Number of deadlocks not printed, being the same as the previous one
+    pub reports_suppressed: usize,
+    /// Number of lock order warnings printed by lockdep
+    pub lockdep_warnings: usize,
+}
+
+/// Contention of a mutex, counted by `sys_mutex_lock`
+#[repr(C)]
+#[derive(Clone, Copy, Default)]
+pub struct MutexStats {
+    /// Number of locks that found the mutex free
+    pub immediate: usize,
+    /// Number of locks that had to wait for it
+    pub blocked: usize,
+    /// Threads waiting for it right now, only filled when reported
+    pub waiters: usize,
+}
+
+/// The resource a thread was refused because waiting for it would deadlock
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum DeadlockRequest {
+    Mutex(usize),
+    Semaphore(usize),
+    SemOp,
+}
+
+impl DeadlockRequest {
+    /// Print the deadlock line of `tid` in `pid` refused this request
+    fn print(&self, pid: usize, tid: usize) {
+        match self {
+            Self::Mutex(id) => {
+                println!(
+                    " ----- deadlock! pid: {}, tid: {}, mutex_id: {} ------",
+                    pid, tid, id
+                );
+            }
+            Self::Semaphore(id) => {
+                println!("--- deadlock! pid: {}, tid: {}, sem_id: {}", pid, tid, id);
+            }
+            Self::SemOp => {
+                println!("--- deadlock! pid: {}, tid: {}, semop", pid, tid);
+            }
+        }
+    }
+}
+
+/// The deadlock last printed, and how many times it was found again since
+pub struct DeadlockReport {
+    pub tid: usize,
+    pub request: DeadlockRequest,
+    pub repeats: usize,
+}
+
+/// Slot index of sync object id `id`, the bits from `SYNC_ID_GEN_SHIFT` up
+/// holding the generation of the slot
+pub fn sync_slot(id: usize) -> usize {
+    id & ((1 << SYNC_ID_GEN_SHIFT) - 1)
+}
+
+/// Id of the object in `slot` at generation `gen`
+pub fn sync_id(slot: usize, gen: usize) -> usize {
+    gen << SYNC_ID_GEN_SHIFT | slot
+}
+
+/// Generation following `gen`, wrapping before ids would turn negative
+pub fn next_sync_gen(gen: usize) -> usize {
+    (gen + 1) & (isize::MAX as usize >> SYNC_ID_GEN_SHIFT)
+}
+
 pub struct ProcessControlBlockInner {
     pub is_zombie: bool,
     pub memory_set: MemorySet,
@@ -29,14 +113,60 @@ pub struct ProcessControlBlockInner {
     pub mutex_list: Vec<Option<Arc<dyn Mutex>>>,
     pub mutex_alloc: Vec<Option<usize>>,   // [mutex_id] -> tid
     pub mutex_request: Vec<Option<usize>>, // [tid] -> mutex_id
+    /// `[tid]` -> the condvar it waits on, for a signal only a thread still
+    /// able to run can send
+    pub condvar_request: Vec<Option<usize>>,
+    pub mutex_rank: Vec<Option<usize>>,    // [mutex_id] -> rank
+    pub mutex_ceiling: Vec<Option<usize>>, // [mutex_id] -> priority
+    pub mutex_stats: Vec<MutexStats>,      // [mutex_id] -> counters
     pub semaphore_list: Vec<Option<Arc<Semaphore>>>,
-    pub sem_avail: Vec<usize>,           // [mid] -> num
-    pub sem_alloc: Vec<Vec<usize>>,      // [tid] -> {sid, num}
-    pub sem_request: Vec<Option<usize>>, // [tid] -> sid
+    pub sem_avail: Vec<usize>,        // [mid] -> num
+    pub sem_alloc: Vec<Vec<usize>>,   // [tid] -> {sid, num}
+    pub sem_request: Vec<Vec<usize>>, // [tid] -> {sid, num}
+    /// Threads waiting in `sys_semaphore_op`, woken on every release
+    pub semop_waiters: VecDeque<Arc<TaskControlBlock>>,
     pub condvar_list: Vec<Option<Arc<Condvar>>>,
+    /// `[slot]` -> generation, bumped when the condvar in the slot is
+    /// destroyed so that its id does not name the next one
+    pub condvar_gen: Vec<usize>,
+    pub mq_list: Vec<Option<Arc<MsgQueue>>>,
+    /// `[slot]` -> generation, as `condvar_gen`
+    pub mq_gen: Vec<usize>,
+    /// Most live mutexes, semaphores, condvars and message queues, indexed
+    /// by `QUOTA_*`
+    pub sync_quota: [usize; 4],
     pub deadlock_det_enabled: bool,
+    pub deadlock_stats: DeadlockStats,
+    pub last_deadlock: Option<DeadlockReport>,
+    /// Report semaphore deadlocks as `-EDEADSEM` rather than `-EDEADMUTEX`
+    pub deadlock_codes_enabled: bool,
+    /// Reject locking a mutex ranked lower than one already held
+    pub lock_order_enabled: bool,
+    /// Warn when two mutexes are taken in both orders
+    pub lockdep_enabled: bool,
+    /// Refuse a condvar wait after which no thread could signal
+    pub condvar_stall_enabled: bool,
+    /// Make deadlock victims exit instead of only failing their request
+    pub deadlock_abort_enabled: bool,
+    /// `(a, b)`: mutex `b` was locked while `a` was held
+    pub lock_edges: BTreeSet<(usize, usize)>,
+    /// `(kind, id)` -> microseconds threads spent blocked waiting for sync
+    /// object `id` of a `QUOTA_*` kind
+    pub sync_wait_us: BTreeMap<(usize, usize), usize>,
+    /// Record lock events in the global sync trace
+    pub sync_trace_enabled: bool,
+    pub signals: SignalFlags,
+    /// Exit code of `sys_exit_group`, with which every thread exits
+    pub group_exit: Option<i32>,
+    /// Usage counters, the time of live threads and the frames excluded
+    pub rusage: RUsage,
 }
 
+/// The deadlock detector found its bookkeeping inconsistent, some other
+/// path must have failed to keep it up to date
+#[derive(Debug)]
+pub struct SyncStateCorrupt;
+
 impl ProcessControlBlockInner {
     #[allow(unused)]
     pub fn get_user_token(&self) -> usize {
@@ -67,9 +197,399 @@ impl ProcessControlBlockInner {
     pub fn get_task(&self, tid: usize) -> Arc<TaskControlBlock> {
         self.tasks[tid].as_ref().unwrap().clone()
     }
+
+    /// Deadlock detection over mutexes, semaphores and condvar waits
+    /// together, each mutex being a single-instance resource.
+    ///
+    /// Threads whose registered request can be satisfied are assumed to run
+    /// to completion and release everything they hold. Return the threads
+    /// left that can never be satisfied, in tid order and empty without a
+    /// deadlock, or [`SyncStateCorrupt`] if the bookkeeping contradicts
+    /// itself. The work done is counted in `deadlock_stats`.
+    pub fn detect_deadlock(&mut self) -> Result<Vec<usize>, SyncStateCorrupt> {
+        self.deadlock_stats.runs += 1;
+        let (stuck, visited) = self.search_stuck()?;
+        self.deadlock_stats.nodes_visited += visited;
+        self.deadlock_stats.deadlocks += !stuck.is_empty() as usize;
+        Ok(stuck)
+    }
+
+    /// The threads [`Self::detect_deadlock`] finds waiting forever, in tid
+    /// order. A thread waiting on a condvar can go on once some live thread
+    /// is found able to run to completion, as that one may signal it.
+    /// Only a look at the current state, not counted in `deadlock_stats`.
+    pub fn stuck_threads(&self) -> Result<Vec<usize>, SyncStateCorrupt> {
+        self.search_stuck().map(|(stuck, _)| stuck)
+    }
+
+    /// [`Self::stuck_threads`] along with the number of thread requests
+    /// checked on the way
+    fn search_stuck(&self) -> Result<(Vec<usize>, usize), SyncStateCorrupt> {
+        if !self.sync_state_consistent() {
+            return Err(SyncStateCorrupt);
+        }
+        let mutex_n = self.mutex_alloc.len();
+        let thread_n = self.mutex_request.len();
+        // available instances: mutexes first, then semaphores
+        let mut work: Vec<usize> = self
+            .mutex_alloc
+            .iter()
+            .map(|owner| owner.is_none() as usize)
+            .collect();
+        work.extend(self.sem_avail.iter());
+        let live: Vec<bool> = (0..thread_n)
+            .map(|tid| match self.tasks.get(tid) {
+                Some(Some(task)) => task.inner_exclusive_access().exit_code.is_none(),
+                _ => false,
+            })
+            .collect();
+        let mut signaller = false;
+        let mut not_finished: BTreeSet<usize> = (0..thread_n).collect();
+        let mut visited = 0;
+        // every round but the last finishes at least one thread
+        for _ in 0..=thread_n {
+            let finished: Vec<usize> = not_finished
+                .iter()
+                .copied()
+                .filter(|&tid| {
+                    visited += 1;
+                    let mutex_ok = self.mutex_request[tid].map_or(true, |mid| work[mid] > 0);
+                    let sem_ok = self.sem_request[tid]
+                        .iter()
+                        .enumerate()
+                        .all(|(sid, &num)| work[mutex_n + sid] >= num);
+                    let condvar_ok = self.condvar_request[tid].is_none() || signaller;
+                    mutex_ok && sem_ok && condvar_ok
+                })
+                .collect();
+            if finished.is_empty() {
+                return Ok((not_finished.into_iter().collect(), visited));
+            }
+            for tid in finished {
+                not_finished.remove(&tid);
+                signaller |= live[tid];
+                for (mid, owner) in self.mutex_alloc.iter().enumerate() {
+                    if *owner == Some(tid) {
+                        work[mid] += 1;
+                    }
+                }
+                for (sid, num) in self.sem_alloc[tid].iter().enumerate() {
+                    work[mutex_n + sid] = work[mutex_n + sid]
+                        .checked_add(*num)
+                        .ok_or(SyncStateCorrupt)?;
+                }
+            }
+        }
+        Err(SyncStateCorrupt)
+    }
+
+    /// Print the deadlock `tid` ran into asking for `request`, followed by
+    /// the wait reason of each thread of `stuck`, the set the detector found,
+    /// that set one. The same deadlock found again right after is only printed
+    /// every `DEADLOCK_REPORT_EVERY` times, so a thread retrying in a loop
+    /// does not flood the console.
+    pub fn report_deadlock(
+        &mut self,
+        pid: usize,
+        tid: usize,
+        request: DeadlockRequest,
+        stuck: &[usize],
+    ) {
+        match &mut self.last_deadlock {
+            Some(last) if last.tid == tid && last.request == request => {
+                last.repeats += 1;
+                self.deadlock_stats.reports_suppressed += 1;
+                if last.repeats % DEADLOCK_REPORT_EVERY == 0 {
+                    request.print(pid, tid);
+                    println!("    found {} more times", last.repeats);
+                    self.print_wait_reasons(stuck);
+                }
+            }
+            _ => {
+                request.print(pid, tid);
+                self.print_wait_reasons(stuck);
+                self.last_deadlock = Some(DeadlockReport {
+                    tid,
+                    request,
+                    repeats: 0,
+                });
+            }
+        }
+    }
+
+    fn print_wait_reasons(&self, stuck: &[usize]) {
+        for &tid in stuck {
+            if let Some(Some(task)) = self.tasks.get(tid) {
+                if let Some(reason) = task.inner_exclusive_access().wait_reason() {
+                    println!("    tid: {}, {}", tid, reason);
+                }
+            }
+        }
+    }
+
+    /// Whether the detector's bookkeeping fits the threads and primitives:
+    /// every id in range, and threads that exited holding or requesting
+    /// nothing.
+    fn sync_state_consistent(&self) -> bool {
+        let (mutex_n, sem_n, thread_n) = (
+            self.mutex_alloc.len(),
+            self.sem_avail.len(),
+            self.mutex_request.len(),
+        );
+        let condvar_n = self.condvar_list.len();
+        let exited = |tid: usize| match self.tasks.get(tid) {
+            Some(Some(task)) => task.inner_exclusive_access().exit_code.is_some(),
+            _ => true,
+        };
+        let owners_ok = self
+            .mutex_alloc
+            .iter()
+            .flatten()
+            .all(|&tid| tid < thread_n && !exited(tid));
+        let rows_ok = self.sem_alloc.len() == thread_n
+            && self.sem_request.len() == thread_n
+            && self.condvar_request.len() == thread_n
+            && self.sem_alloc.iter().all(|row| row.len() == sem_n)
+            && self.sem_request.iter().all(|row| row.len() == sem_n);
+        owners_ok
+            && rows_ok
+            && (0..thread_n).all(|tid| {
+                let idle = self.mutex_request[tid].is_none()
+                    && self.condvar_request[tid].is_none()
+                    && self.sem_alloc[tid].iter().all(|&n| n == 0)
+                    && self.sem_request[tid].iter().all(|&n| n == 0);
+                let request_ok = self.mutex_request[tid].map_or(true, |mid| mid < mutex_n)
+                    && self.condvar_request[tid].map_or(true, |cid| cid < condvar_n);
+                request_ok && (idle || !exited(tid))
+            })
+    }
+
+    /// Record that `tid` locks `mutex_id` while holding its other mutexes.
+    /// Return the first held mutex that was seen locked after `mutex_id`
+    /// before, an ordering which could deadlock with this one.
+    pub fn record_lock_order(&mut self, tid: usize, mutex_id: usize) -> Option<usize> {
+        let held: Vec<usize> = (0..self.mutex_alloc.len())
+            .filter(|&id| id != mutex_id && self.mutex_alloc[id] == Some(tid))
+            .collect();
+        let mut conflict = None;
+        for id in held {
+            // only a new edge is reported, so each pair is reported once
+            if self.lock_edges.insert((id, mutex_id))
+                && conflict.is_none()
+                && self.lock_edges.contains(&(mutex_id, id))
+            {
+                conflict = Some(id);
+            }
+        }
+        conflict
+    }
+
+    /// The lock order checks of `tid` about to lock `mutex_id`. With ranks
+    /// enforced, return false if it holds a mutex of a higher rank. With
+    /// lockdep on, print a warning when the lock reverses an order seen
+    /// before.
+    pub fn check_lock_order(&mut self, pid: usize, tid: usize, mutex_id: usize) -> bool {
+        if let (true, Some(rank)) = (self.lock_order_enabled, self.mutex_rank[mutex_id]) {
+            let task = Arc::clone(self.tasks[tid].as_ref().unwrap());
+            let task_inner = task.inner_exclusive_access();
+            if task_inner.held_ranks.iter().any(|&(_, held)| held > rank) {
+                return false;
+            }
+        }
+        if self.lockdep_enabled {
+            if let Some(held) = self.record_lock_order(tid, mutex_id) {
+                self.deadlock_stats.lockdep_warnings += 1;
+                println!(
+                    "--- lock order warning! pid: {}, tid: {}, mutex {} locked while holding {}, \
+                     which was locked while holding {} before",
+                    pid, tid, mutex_id, held, mutex_id
+                );
+            }
+        }
+        true
+    }
+
+    /// Record that thread `tid` locked `mutex_id`, taking on its rank and
+    /// priority ceiling
+    pub fn mutex_acquired(&mut self, tid: usize, mutex_id: usize) {
+        self.mutex_alloc[mutex_id] = Some(tid);
+        self.trace_sync(tid, SYNC_LOCK, mutex_id);
+        let task = Arc::clone(self.tasks[tid].as_ref().unwrap());
+        let mut task_inner = task.inner_exclusive_access();
+        if let Some(rank) = self.mutex_rank[mutex_id] {
+            task_inner.held_ranks.push((mutex_id, rank));
+        }
+        if let Some(ceiling) = self.mutex_ceiling[mutex_id] {
+            task_inner.raise_to_ceiling(mutex_id, ceiling);
+        }
+    }
+
+    /// Record that `mutex_id` was unlocked and handed to `owner`, if any,
+    /// which then waits for nothing any more
+    pub fn mutex_released(&mut self, mutex_id: usize, owner: Option<Arc<TaskControlBlock>>) {
+        let tid = owner.and_then(|task| task.inner_exclusive_access().res.as_ref().map(|r| r.tid));
+        self.mutex_alloc[mutex_id] = tid;
+        if let Some(tid) = tid {
+            self.mutex_request[tid] = None;
+        }
+    }
+
+    /// Record that `n` resources of `sem_id` were released and `granted`
+    /// handed on. Their waiters are moved from `sem_request` to `sem_alloc`
+    /// right away, not when they get to run.
+    ///
+    /// This keeps the detector's triple consistent: `sem_avail[sid]` always
+    /// matches the free count of semaphore `sid`, and a resource handed to a
+    /// waiter is in its `sem_alloc` row, no longer in `sem_request`.
+    pub fn sem_released(
+        &mut self,
+        sem_id: usize,
+        n: usize,
+        granted: Vec<(Arc<TaskControlBlock>, usize)>,
+    ) {
+        self.sem_avail[sem_id] += n;
+        for (task, need) in granted {
+            let tid = match task.inner_exclusive_access().res.as_ref() {
+                Some(res) => res.tid,
+                None => continue,
+            };
+            self.sem_avail[sem_id] -= need;
+            self.sem_alloc[tid][sem_id] += need;
+            self.sem_request[tid][sem_id] = 0;
+        }
+        self.wake_semop_waiters();
+    }
+
+    /// Record a lock event of thread `tid` if tracing is enabled. Only
+    /// called from the syscalls of this process, which is the current one.
+    pub fn trace_sync(&mut self, tid: usize, op: usize, id: usize) {
+        if self.sync_trace_enabled {
+            sync_trace_record(current_process().getpid(), tid, op, id);
+        }
+    }
+
+    /// Mutex `mutex_id`, `None` if out of range or destroyed
+    pub fn mutex(&self, mutex_id: usize) -> Option<Arc<dyn Mutex>> {
+        self.mutex_list.get(mutex_id).cloned().flatten()
+    }
+
+    /// Semaphore `sem_id`, `None` if out of range or destroyed
+    pub fn semaphore(&self, sem_id: usize) -> Option<Arc<Semaphore>> {
+        self.semaphore_list.get(sem_id).cloned().flatten()
+    }
+
+    /// Condvar `condvar_id`, `None` if out of range, destroyed or of an
+    /// older generation of its slot
+    pub fn condvar(&self, condvar_id: usize) -> Option<Arc<Condvar>> {
+        let slot = sync_slot(condvar_id);
+        if self.condvar_gen.get(slot) != Some(&(condvar_id >> SYNC_ID_GEN_SHIFT)) {
+            return None;
+        }
+        self.condvar_list.get(slot).cloned().flatten()
+    }
+
+    /// Message queue `mq_id`, `None` if out of range, destroyed or of an
+    /// older generation of its slot
+    pub fn mq(&self, mq_id: usize) -> Option<Arc<MsgQueue>> {
+        let slot = sync_slot(mq_id);
+        if self.mq_gen.get(slot) != Some(&(mq_id >> SYNC_ID_GEN_SHIFT)) {
+            return None;
+        }
+        self.mq_list.get(slot).cloned().flatten()
+    }
+
+    /// Let the threads waiting in `sys_semaphore_op` check their semaphores again
+    pub fn wake_semop_waiters(&mut self) {
+        while let Some(task) = self.semop_waiters.pop_front() {
+            add_task(task);
+        }
+    }
+
+    /// Whether every thread but `tid` is blocked or has exited.
+    pub fn others_paused(&self, tid: usize) -> bool {
+        self.tasks.iter().enumerate().all(|(id, task)| match task {
+            Some(task) if id != tid => {
+                let inner = task.inner_exclusive_access();
+                inner.exit_code.is_some() || inner.task_status == TaskStatus::Blocking
+            }
+            _ => true,
+        })
+    }
+
+    /// Flatten the bookkeeping of the deadlock detector into words:
+    /// `[mutex_n, sem_n, thread_n]` followed by `mutex_alloc`, `mutex_request`,
+    /// `sem_avail`, and `sem_alloc` and `sem_request` row by row. An `Option`
+    /// is stored as `0` for `None` and `id + 1` otherwise.
+    pub fn save_sync_state(&self) -> Vec<usize> {
+        let encode = |x: &Option<usize>| x.map_or(0, |id| id + 1);
+        let mut state = vec![
+            self.mutex_alloc.len(),
+            self.sem_avail.len(),
+            self.mutex_request.len(),
+        ];
+        state.extend(self.mutex_alloc.iter().map(encode));
+        state.extend(self.mutex_request.iter().map(encode));
+        state.extend(self.sem_avail.iter());
+        for row in self.sem_alloc.iter() {
+            state.extend(row.iter());
+        }
+        for row in self.sem_request.iter() {
+            state.extend(row.iter());
+        }
+        state
+    }
+
+    /// Overwrite the mutex and semaphore requests with words from
+    /// [`Self::save_sync_state`]. The owners and counts are what exit and
+    /// the condvar checks release and trust, so they must equal the current
+    /// ones. Return false, leaving everything untouched, if the state does
+    /// not fit the current numbers of mutexes, semaphores and threads or
+    /// its owners and counts differ.
+    pub fn load_sync_state(&mut self, state: &[usize]) -> bool {
+        let (mutex_n, sem_n, thread_n) = (
+            self.mutex_alloc.len(),
+            self.sem_avail.len(),
+            self.mutex_request.len(),
+        );
+        if state.len() != 3 + mutex_n + thread_n + sem_n * (1 + 2 * thread_n)
+            || state[..3] != [mutex_n, sem_n, thread_n]
+        {
+            return false;
+        }
+        let decode = |x: usize, bound: usize| match x {
+            0 => Some(None),
+            x if x <= bound => Some(Some(x - 1)),
+            _ => None,
+        };
+        let mut words = state[3..].iter().copied();
+        let mut take = |n: usize| words.by_ref().take(n).collect::<Vec<_>>();
+        let mutex_alloc: Option<Vec<_>> = take(mutex_n)
+            .into_iter()
+            .map(|x| decode(x, thread_n))
+            .collect();
+        let mutex_request: Option<Vec<_>> = take(thread_n)
+            .into_iter()
+            .map(|x| decode(x, mutex_n))
+            .collect();
+        let sem_avail = take(sem_n);
+        let sem_alloc: Vec<_> = (0..thread_n).map(|_| take(sem_n)).collect();
+        let sem_request: Vec<_> = (0..thread_n).map(|_| take(sem_n)).collect();
+        let held_same = mutex_alloc.as_ref() == Some(&self.mutex_alloc)
+            && sem_avail == self.sem_avail
+            && sem_alloc == self.sem_alloc;
+        match mutex_request {
+            Some(mutex_request) if held_same => {
+                self.mutex_request = mutex_request;
+                self.sem_request = sem_request;
+                true
+            }
+            _ => false,
+        }
+    }
 }
 
 impl ProcessControlBlock {
+    #[track_caller]
     pub fn inner_exclusive_access(&self) -> RefMut<'_, ProcessControlBlockInner> {
         self.inner.exclusive_access()
     }
@@ -101,12 +621,34 @@ impl ProcessControlBlock {
                     mutex_list: Vec::new(),
                     mutex_alloc: Vec::new(),
                     mutex_request: Vec::new(),
+                    condvar_request: Vec::new(),
+                    mutex_rank: Vec::new(),
+                    mutex_ceiling: Vec::new(),
+                    mutex_stats: Vec::new(),
                     semaphore_list: Vec::new(),
                     sem_avail: Vec::new(),
                     sem_alloc: Vec::new(),
                     sem_request: Vec::new(),
+                    semop_waiters: VecDeque::new(),
                     condvar_list: Vec::new(),
+                    condvar_gen: Vec::new(),
+                    mq_list: Vec::new(),
+                    mq_gen: Vec::new(),
+                    sync_quota: [DEFAULT_SYNC_QUOTA; 4],
                     deadlock_det_enabled: false,
+                    deadlock_stats: DeadlockStats::default(),
+                    last_deadlock: None,
+                    deadlock_codes_enabled: false,
+                    lock_order_enabled: false,
+                    lockdep_enabled: false,
+                    condvar_stall_enabled: false,
+                    deadlock_abort_enabled: false,
+                    lock_edges: BTreeSet::new(),
+                    sync_wait_us: BTreeMap::new(),
+                    sync_trace_enabled: false,
+                    signals: SignalFlags::empty(),
+                    group_exit: None,
+                    rusage: RUsage::default(),
                 })
             },
         });
@@ -114,6 +656,7 @@ impl ProcessControlBlock {
         let task = Arc::new(TaskControlBlock::new(
             Arc::clone(&process),
             ustack_base,
+            USER_STACK_SIZE,
             true,
         ));
         // prepare trap_cx of main thread
@@ -133,9 +676,11 @@ impl ProcessControlBlock {
         let mut process_inner = process.inner_exclusive_access();
         process_inner.tasks.push(Some(Arc::clone(&task)));
         process_inner.mutex_request.push(None);
-        process_inner.sem_request.push(None);
+        process_inner.condvar_request.push(None);
+        process_inner.sem_request.push(Vec::new());
         process_inner.sem_alloc.push(Vec::new());
         drop(process_inner);
+        insert_into_pid2process(process.getpid(), Arc::clone(&process));
         // add main thread to scheduler
         add_task(task);
         process
@@ -229,27 +774,49 @@ impl ProcessControlBlock {
                     mutex_list: Vec::new(),
                     mutex_alloc: Vec::new(),
                     mutex_request: Vec::new(),
+                    condvar_request: Vec::new(),
+                    mutex_rank: Vec::new(),
+                    mutex_ceiling: Vec::new(),
+                    mutex_stats: Vec::new(),
                     semaphore_list: Vec::new(),
                     sem_avail: Vec::new(),
                     sem_alloc: Vec::new(),
                     sem_request: Vec::new(),
+                    semop_waiters: VecDeque::new(),
                     condvar_list: Vec::new(),
+                    condvar_gen: Vec::new(),
+                    mq_list: Vec::new(),
+                    mq_gen: Vec::new(),
+                    sync_quota: [DEFAULT_SYNC_QUOTA; 4],
                     deadlock_det_enabled: false,
+                    deadlock_stats: DeadlockStats::default(),
+                    last_deadlock: None,
+                    deadlock_codes_enabled: false,
+                    lock_order_enabled: false,
+                    lockdep_enabled: false,
+                    condvar_stall_enabled: false,
+                    deadlock_abort_enabled: false,
+                    lock_edges: BTreeSet::new(),
+                    sync_wait_us: BTreeMap::new(),
+                    sync_trace_enabled: false,
+                    signals: SignalFlags::empty(),
+                    group_exit: None,
+                    rusage: RUsage::default(),
                 })
             },
         });
         // add child
         parent.children.push(Arc::clone(&child));
         // create main thread of child process
+        let parent_task = parent.get_task(0);
+        let parent_task_inner = parent_task.inner_exclusive_access();
+        let parent_res = parent_task_inner.res.as_ref().unwrap();
+        let (ustack_base, ustack_size) = (parent_res.ustack_base(), parent_res.ustack_size);
+        drop(parent_task_inner);
         let task = Arc::new(TaskControlBlock::new(
             Arc::clone(&child),
-            parent
-                .get_task(0)
-                .inner_exclusive_access()
-                .res
-                .as_ref()
-                .unwrap()
-                .ustack_base(),
+            ustack_base,
+            ustack_size,
             // here we do not allocate trap_cx or ustack again
             // but mention that we allocate a new kernel_stack here
             false,
@@ -258,14 +825,23 @@ impl ProcessControlBlock {
         let mut child_inner = child.inner_exclusive_access();
         child_inner.tasks.push(Some(Arc::clone(&task)));
         child_inner.mutex_request.push(None);
-        child_inner.sem_request.push(None);
+        child_inner.condvar_request.push(None);
+        child_inner.sem_request.push(Vec::new());
         child_inner.sem_alloc.push(Vec::new());
         drop(child_inner);
         // modify kernel_stack_top in trap_cx of this thread
-        let task_inner = task.inner_exclusive_access();
+        let mut task_inner = task.inner_exclusive_access();
         let trap_cx = task_inner.get_trap_cx();
         trap_cx.kernel_sp = task.kernel_stack.get_top();
+        // start from the forking thread's pass so the child does not
+        // monopolize the CPU
+        let forker = current_task().unwrap();
+        let parent_task_inner = forker.inner_exclusive_access();
+        task_inner.set_base_priority(parent_task_inner.base_priority);
+        task_inner.pass = parent_task_inner.pass;
+        drop(parent_task_inner);
         drop(task_inner);
+        insert_into_pid2process(child.getpid(), Arc::clone(&child));
         // add this thread to scheduler
         add_task(task);
         child
@@ -291,13 +867,35 @@ impl ProcessControlBlock {
                     task_res_allocator: RecycleAllocator::new(),
                     mutex_list: Vec::new(),
                     mutex_request: Vec::new(),
+                    condvar_request: Vec::new(),
+                    mutex_rank: Vec::new(),
+                    mutex_ceiling: Vec::new(),
+                    mutex_stats: Vec::new(),
                     mutex_alloc: Vec::new(),
                     semaphore_list: Vec::new(),
                     sem_avail: Vec::new(),
                     sem_alloc: Vec::new(),
                     sem_request: Vec::new(),
+                    semop_waiters: VecDeque::new(),
                     condvar_list: Vec::new(),
+                    condvar_gen: Vec::new(),
+                    mq_list: Vec::new(),
+                    mq_gen: Vec::new(),
+                    sync_quota: [DEFAULT_SYNC_QUOTA; 4],
                     deadlock_det_enabled: false,
+                    deadlock_stats: DeadlockStats::default(),
+                    last_deadlock: None,
+                    deadlock_codes_enabled: false,
+                    lock_order_enabled: false,
+                    lockdep_enabled: false,
+                    condvar_stall_enabled: false,
+                    deadlock_abort_enabled: false,
+                    lock_edges: BTreeSet::new(),
+                    sync_wait_us: BTreeMap::new(),
+                    sync_trace_enabled: false,
+                    signals: SignalFlags::empty(),
+                    group_exit: None,
+                    rusage: RUsage::default(),
                 })
             },
         });
@@ -6,9 +6,11 @@
 
 use super::__switch;
 use super::process::ProcessControlBlock;
+use super::sched_trace::sched_trace_enter;
 use super::{fetch_task, TaskStatus};
 use super::{TaskContext, TaskControlBlock};
 use crate::sync::UPSafeCell;
+use crate::timer::{get_time_ms, get_time_us, set_next_trigger_within};
 use crate::trap::TrapContext;
 use alloc::sync::Arc;
 use lazy_static::*;
@@ -53,11 +55,17 @@ pub fn run_tasks() {
         let mut processor = PROCESSOR.exclusive_access();
         if let Some(task) = fetch_task() {
             // println!("task get!");
+            sched_trace_enter(&task);
             let idle_task_cx_ptr = processor.get_idle_task_cx_ptr();
             // access coming task TCB exclusively
             let mut task_inner = task.inner_exclusive_access();
             let next_task_cx_ptr = &task_inner.task_cx as *const TaskContext;
-            task_inner.task_status = TaskStatus::Running;
+            task_inner.set_status(TaskStatus::Running, get_time_us());
+            // start a fresh quantum for the coming task
+            task_inner.slice_start_ms = get_time_ms();
+            task_inner.boosted = false;
+            task_inner.timer_fired = false;
+            set_next_trigger_within(task_inner.timeslice_ms);
             drop(task_inner);
             // release coming task TCB manually
             processor.current = Some(task);
@@ -0,0 +1,142 @@
+//! Ring buffer of context switches, for debugging scheduling decisions
+
+use super::TaskControlBlock;
+use crate::config::{SCHED_TRACE_LEN, WAIT_REASON_LEN};
+use crate::sync::UPSafeCell;
+use crate::timer::get_time_ticks;
+use alloc::collections::VecDeque;
+use alloc::vec::Vec;
+use lazy_static::*;
+
+/// Why a task left the CPU
+pub const SCHED_YIELD: usize = 1;
+pub const SCHED_BLOCK: usize = 2;
+pub const SCHED_PREEMPT: usize = 3;
+pub const SCHED_EXIT: usize = 4;
+
+/// Pid or tid of a side of a switch that was no user task
+pub const SCHED_NONE: usize = usize::MAX;
+
+/// One context switch, tids being only unique within their process
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct SchedEvent {
+    pub tick: usize,
+    pub from_pid: usize,
+    pub from_tid: usize,
+    pub to_pid: usize,
+    pub to_tid: usize,
+    /// One of the `SCHED_*` reasons
+    pub reason: usize,
+    /// Wait reason the task leaving had set when it blocked, NUL padded
+    pub wait_reason: [u8; WAIT_REASON_LEN],
+}
+
+/// Task that left the CPU, waiting for the next one to be picked
+#[derive(Clone, Copy)]
+struct Leaving {
+    pid: usize,
+    tid: usize,
+    reason: usize,
+    wait_reason: [u8; WAIT_REASON_LEN],
+}
+
+/// The last `SCHED_TRACE_LEN` switches, the oldest is overwritten when full.
+///
+/// A task leaving the CPU only notes itself in `leaving`, the event is
+/// recorded once the scheduler has picked the next task.
+pub struct SchedTrace {
+    enabled: bool,
+    leaving: Option<Leaving>,
+    events: VecDeque<SchedEvent>,
+}
+
+impl SchedTrace {
+    pub fn new() -> Self {
+        Self {
+            enabled: false,
+            leaving: None,
+            events: VecDeque::new(),
+        }
+    }
+}
+
+lazy_static! {
+    static ref SCHED_TRACE: UPSafeCell<SchedTrace> = unsafe { UPSafeCell::new(SchedTrace::new()) };
+}
+
+/// Pid and tid of `task`, `SCHED_NONE` for what it does not have
+fn task_ids(task: &TaskControlBlock) -> (usize, usize) {
+    let pid = task.process.upgrade().map_or(SCHED_NONE, |p| p.getpid());
+    let tid = task
+        .inner_exclusive_access()
+        .res
+        .as_ref()
+        .map_or(SCHED_NONE, |res| res.tid);
+    (pid, tid)
+}
+
+/// Turn tracing on or off, turning it on starts an empty trace
+pub fn sched_trace_enable(enabled: bool) {
+    let mut trace = SCHED_TRACE.exclusive_access();
+    if enabled && !trace.enabled {
+        trace.events.clear();
+        trace.leaving = None;
+    }
+    trace.enabled = enabled;
+}
+
+/// Note that `task` leaves the CPU for `reason`
+pub fn sched_trace_leave(task: &TaskControlBlock, reason: usize) {
+    if !SCHED_TRACE.exclusive_access().enabled {
+        return;
+    }
+    let (pid, tid) = task_ids(task);
+    let wait_reason = if reason == SCHED_BLOCK {
+        task.inner_exclusive_access().wait_reason
+    } else {
+        [0; WAIT_REASON_LEN]
+    };
+    SCHED_TRACE.exclusive_access().leaving = Some(Leaving {
+        pid,
+        tid,
+        reason,
+        wait_reason,
+    });
+}
+
+/// Record the switch to `task`, just picked by the scheduler
+pub fn sched_trace_enter(task: &TaskControlBlock) {
+    if !SCHED_TRACE.exclusive_access().enabled {
+        return;
+    }
+    let (to_pid, to_tid) = task_ids(task);
+    let mut trace = SCHED_TRACE.exclusive_access();
+    // nothing noted means the previous task left through a path with no
+    // user task to name, treat it as blocked
+    let leaving = trace.leaving.take().unwrap_or(Leaving {
+        pid: SCHED_NONE,
+        tid: SCHED_NONE,
+        reason: SCHED_BLOCK,
+        wait_reason: [0; WAIT_REASON_LEN],
+    });
+    if trace.events.len() == SCHED_TRACE_LEN {
+        trace.events.pop_front();
+    }
+    trace.events.push_back(SchedEvent {
+        tick: get_time_ticks(),
+        from_pid: leaving.pid,
+        from_tid: leaving.tid,
+        to_pid,
+        to_tid,
+        reason: leaving.reason,
+        wait_reason: leaving.wait_reason,
+    });
+}
+
+/// The most recent `n` switches at most, oldest first
+pub fn sched_trace_recent(n: usize) -> Vec<SchedEvent> {
+    let trace = SCHED_TRACE.exclusive_access();
+    let skip = trace.events.len().saturating_sub(n);
+    trace.events.iter().skip(skip).copied().collect()
+}
@@ -0,0 +1,46 @@
+//! Minimal signal facility
+//!
+//! A signal is recorded as a pending bit in the target process and acted on
+//! when one of its threads goes back to user mode.
+
+/// Interrupt from keyboard, terminates the process
+pub const SIGINT: usize = 2;
+/// Terminate the process, cannot be ignored
+pub const SIGKILL: usize = 9;
+/// User-defined signal, only recorded until polled with `sys_sigpending`
+pub const SIGUSR1: usize = 10;
+
+bitflags! {
+    /// Pending signals of a process, bit `n` stands for signal number `n`
+    pub struct SignalFlags: u32 {
+        const SIGINT = 1 << SIGINT;
+        const SIGKILL = 1 << SIGKILL;
+        const SIGUSR1 = 1 << SIGUSR1;
+    }
+}
+
+impl SignalFlags {
+    /// Convert a signal number into its flag, `None` if unsupported
+    pub fn from_signum(signum: usize) -> Option<Self> {
+        if signum >= 32 {
+            return None;
+        }
+        Self::from_bits(1 << signum)
+    }
+
+    /// Whether a signal terminating the process is pending
+    pub fn is_fatal(&self) -> bool {
+        self.intersects(Self::SIGINT | Self::SIGKILL)
+    }
+
+    /// Exit code and message of the first fatal signal, if any
+    pub fn check_error(&self) -> Option<(i32, &'static str)> {
+        if self.contains(Self::SIGKILL) {
+            Some((-(SIGKILL as i32), "Killed, SIGKILL=9"))
+        } else if self.contains(Self::SIGINT) {
+            Some((-(SIGINT as i32), "Killed, SIGINT=2"))
+        } else {
+            None
+        }
+    }
+}
@@ -2,9 +2,13 @@
 
 use super::id::TaskUserRes;
 use super::{kstack_alloc, KernelStack, ProcessControlBlock, TaskContext};
+use crate::config::WAIT_REASON_LEN;
+use crate::config::{BIG_STRIDE, CPU_NUM, DEFAULT_PRIORITY, DEFAULT_TIMESLICE_MS, TLS_SLOTS};
+use crate::timer::{get_time_ticks, get_time_us};
 use crate::trap::TrapContext;
 use crate::{mm::PhysPageNum, sync::UPSafeCell};
 use alloc::sync::{Arc, Weak};
+use alloc::vec::Vec;
 use core::cell::RefMut;
 
 /// Task control block structure
@@ -33,6 +37,62 @@ pub struct TaskControlBlockInner {
     /// It is set when active exit or execution error occurs
     pub exit_code: Option<i32>,
     pub res: Option<TaskUserRes>,
+    /// Thread-local storage slots
+    pub tls: [usize; TLS_SLOTS],
+    /// Stride scheduling priority, the stride step is `BIG_STRIDE / priority`.
+    /// It is `base_priority` raised to the ceilings of the mutexes held.
+    pub priority: usize,
+    /// Priority set by the task itself
+    pub base_priority: usize,
+    /// Mutexes with a priority ceiling held by the task as `(mutex_id, ceiling)`
+    pub held_ceilings: Vec<(usize, usize)>,
+    /// Stride scheduling pass, the ready task with the smallest pass runs next
+    pub pass: usize,
+    /// Length of the scheduling quantum of this task in milliseconds
+    pub timeslice_ms: usize,
+    /// Time in milliseconds when the task was last scheduled
+    pub slice_start_ms: usize,
+    /// Number of times the task has been switched in
+    pub switch_count: usize,
+    /// Accumulated time spent running, in microseconds
+    pub run_time_us: usize,
+    /// Accumulated time spent blocked, in microseconds
+    pub blocked_time_us: usize,
+    /// Part of `blocked_time_us` spent in finished mutex, semaphore and
+    /// condvar waits
+    pub sync_wait_us: usize,
+    /// Part of `blocked_time_us` spent in finished sleeps
+    pub sleep_us: usize,
+    /// Time in microseconds when `task_status` last changed
+    pub status_since_us: usize,
+    /// Ranked mutexes held by the task as `(mutex_id, rank)`
+    pub held_ranks: Vec<(usize, usize)>,
+    /// Tick when the task last entered the ready queue, used for aging
+    pub last_run_tick: usize,
+    /// Set by aging when the task waited too long, cleared once it runs
+    pub boosted: bool,
+    /// Set when a timer woke the task, cleared once it runs
+    pub timer_fired: bool,
+    /// Run time in microseconds after which the task is taken off the CPU
+    pub cpu_limit_us: Option<usize>,
+    /// Set when the task was suspended for reaching `cpu_limit_us`
+    pub limited: bool,
+    /// Set while a thread created or put suspended waits for
+    /// `sys_thread_resume`
+    pub suspended: bool,
+    /// Set when the thread was blocked as it was suspended, it is suspended
+    /// instead of made ready once woken
+    pub suspend_pending: bool,
+    /// Set when the thread was picked as a deadlock victim, it exits with
+    /// this code on its way back to user mode
+    pub abort_code: Option<i32>,
+    /// Bit `i` set if the task may run on hart `i`. It always includes a
+    /// hart below `CPU_NUM`, so with a single hart every ready task can be
+    /// picked and the scheduler has nothing to check.
+    pub cpu_mask: usize,
+    /// What the thread says it is about to wait for, NUL padded, shown in
+    /// the scheduler trace and deadlock reports. Cleared once it is woken.
+    pub wait_reason: [u8; WAIT_REASON_LEN],
 }
 
 /// Simple access to its internal fields
@@ -50,15 +110,90 @@ impl TaskControlBlockInner {
     fn get_status(&self) -> TaskStatus {
         self.task_status
     }
+
+    /// Advance the pass by one stride step, called each time the task is scheduled
+    pub fn step_pass(&mut self) {
+        self.pass = self.pass.wrapping_add((BIG_STRIDE / self.priority).max(1));
+    }
+
+    /// Set the priority the task runs at when holding no ceiling mutex
+    pub fn set_base_priority(&mut self, priority: usize) {
+        self.base_priority = priority;
+        self.apply_ceilings();
+    }
+
+    /// Run at least at `ceiling` until `mutex_id` is released
+    pub fn raise_to_ceiling(&mut self, mutex_id: usize, ceiling: usize) {
+        self.held_ceilings.push((mutex_id, ceiling));
+        self.apply_ceilings();
+    }
+
+    /// Drop the ceiling of `mutex_id`, in whatever order mutexes are released
+    pub fn drop_ceiling(&mut self, mutex_id: usize) {
+        self.held_ceilings.retain(|&(id, _)| id != mutex_id);
+        self.apply_ceilings();
+    }
+
+    fn apply_ceilings(&mut self) {
+        self.priority = self
+            .held_ceilings
+            .iter()
+            .map(|&(_, ceiling)| ceiling)
+            .fold(self.base_priority, usize::max);
+    }
+
+    /// The wait reason set with `sys_thread_set_waitreason`, if any
+    pub fn wait_reason(&self) -> Option<&str> {
+        let len = self.wait_reason.iter().position(|&b| b == 0);
+        let reason = &self.wait_reason[..len.unwrap_or(WAIT_REASON_LEN)];
+        // only valid UTF-8 is ever stored
+        core::str::from_utf8(reason).ok().filter(|s| !s.is_empty())
+    }
+
+    /// Change the status at `now_us`, charging the time spent in the old one
+    pub fn set_status(&mut self, status: TaskStatus, now_us: usize) {
+        let elapsed = now_us - self.status_since_us;
+        match self.task_status {
+            TaskStatus::Running => self.run_time_us += elapsed,
+            TaskStatus::Blocking => self.blocked_time_us += elapsed,
+            _ => {}
+        }
+        if status == TaskStatus::Running {
+            self.switch_count += 1;
+        }
+        self.task_status = status;
+        self.status_since_us = now_us;
+    }
+
+    /// Whether the run time at `now_us` has reached `cpu_limit_us`
+    pub fn over_cpu_limit(&self, now_us: usize) -> bool {
+        let mut run_time_us = self.run_time_us;
+        if self.task_status == TaskStatus::Running {
+            run_time_us += now_us - self.status_since_us;
+        }
+        self.cpu_limit_us
+            .map_or(false, |limit| run_time_us >= limit)
+    }
+
+    /// Milliseconds left in the current quantum at `now_ms`, 0 once it is used up
+    pub fn remaining_slice_ms(&self, now_ms: usize) -> usize {
+        (self.slice_start_ms + self.timeslice_ms).saturating_sub(now_ms)
+    }
 }
 
 impl TaskControlBlock {
     pub fn new(
         process: Arc<ProcessControlBlock>,
         ustack_base: usize,
+        ustack_size: usize,
         alloc_user_res: bool,
     ) -> Self {
-        let res = TaskUserRes::new(Arc::clone(&process), ustack_base, alloc_user_res);
+        let res = TaskUserRes::new(
+            Arc::clone(&process),
+            ustack_base,
+            ustack_size,
+            alloc_user_res,
+        );
         let trap_cx_ppn = res.trap_cx_ppn();
         let kernel_stack = kstack_alloc();
         let kstack_top = kernel_stack.get_top();
@@ -72,12 +207,37 @@ impl TaskControlBlock {
                     task_cx: TaskContext::goto_trap_return(kstack_top),
                     task_status: TaskStatus::Ready,
                     exit_code: None,
+                    tls: [0; TLS_SLOTS],
+                    priority: DEFAULT_PRIORITY,
+                    base_priority: DEFAULT_PRIORITY,
+                    held_ceilings: Vec::new(),
+                    pass: 0,
+                    timeslice_ms: DEFAULT_TIMESLICE_MS,
+                    slice_start_ms: 0,
+                    switch_count: 0,
+                    run_time_us: 0,
+                    blocked_time_us: 0,
+                    sync_wait_us: 0,
+                    sleep_us: 0,
+                    status_since_us: get_time_us(),
+                    held_ranks: Vec::new(),
+                    last_run_tick: get_time_ticks(),
+                    boosted: false,
+                    timer_fired: false,
+                    cpu_limit_us: None,
+                    limited: false,
+                    suspended: false,
+                    suspend_pending: false,
+                    abort_code: None,
+                    cpu_mask: (1 << CPU_NUM) - 1,
+                    wait_reason: [0; WAIT_REASON_LEN],
                 })
             },
         }
     }
 
     /// Get the mutex to get the RefMut TaskControlBlockInner
+    #[track_caller]
     pub fn inner_exclusive_access(&self) -> RefMut<'_, TaskControlBlockInner> {
         let inner = self.inner.exclusive_access();
         // if self.process.upgrade().unwrap().pid.0 > 1 {
@@ -123,6 +283,30 @@ impl TaskControlBlock {
                     task_cx: context,
                     task_status: TaskStatus::Ready,
                     exit_code: None,
+                    tls: [0; TLS_SLOTS],
+                    priority: DEFAULT_PRIORITY,
+                    base_priority: DEFAULT_PRIORITY,
+                    held_ceilings: Vec::new(),
+                    pass: 0,
+                    timeslice_ms: DEFAULT_TIMESLICE_MS,
+                    slice_start_ms: 0,
+                    switch_count: 0,
+                    run_time_us: 0,
+                    blocked_time_us: 0,
+                    sync_wait_us: 0,
+                    sleep_us: 0,
+                    status_since_us: get_time_us(),
+                    held_ranks: Vec::new(),
+                    last_run_tick: get_time_ticks(),
+                    boosted: false,
+                    timer_fired: false,
+                    cpu_limit_us: None,
+                    limited: false,
+                    suspended: false,
+                    suspend_pending: false,
+                    abort_code: None,
+                    cpu_mask: (1 << CPU_NUM) - 1,
+                    wait_reason: [0; WAIT_REASON_LEN],
                 })
             },
         }
@@ -4,39 +4,116 @@ use crate::config::CLOCK_FREQ;
 use crate::sbi::set_timer;
 use crate::sync::UPSafeCell;
 use crate::task::{add_task, TaskControlBlock};
-use alloc::collections::BinaryHeap;
+use alloc::collections::{BTreeMap, BinaryHeap};
 use alloc::sync::Arc;
 use core::cmp::Ordering;
+use core::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
 use lazy_static::*;
+use riscv::asm::wfi;
 use riscv::register::time;
 
-const TICKS_PER_SEC: usize = 100;
+pub const TICKS_PER_SEC: usize = 100;
 const MILLI_PER_SEC: usize = 1_000;
 const MICRO_PER_SEC: usize = 1_000_000;
+/// Width of the `time` counter, it wraps around past `2^COUNTER_BITS - 1`
+const COUNTER_BITS: u32 = 64;
 
-/// read the `mtime` register
+/// Widens readings of a wrapping counter into a monotonic 64-bit count
+///
+/// Each reading adds the cycles elapsed since the previous one, modulo the
+/// width of the counter. Two readings must be less than a wrap period apart,
+/// which the timer interrupt of every scheduler tick guarantees.
+pub struct Clock {
+    mask: u64,
+    last: u64,
+    cycles: u64,
+}
+
+impl Clock {
+    /// A clock over a `bits` wide counter currently reading `raw`
+    pub fn new(bits: u32, raw: u64) -> Self {
+        let mask = if bits >= 64 {
+            u64::MAX
+        } else {
+            (1 << bits) - 1
+        };
+        Self {
+            mask,
+            last: raw & mask,
+            cycles: raw & mask,
+        }
+    }
+
+    /// Account for the counter now reading `raw`, return the widened count
+    pub fn update(&mut self, raw: u64) -> u64 {
+        let raw = raw & self.mask;
+        self.cycles += raw.wrapping_sub(self.last) & self.mask;
+        self.last = raw;
+        self.cycles
+    }
+}
+
+/// Timer interrupts taken since boot
+static TIMER_TICKS: AtomicUsize = AtomicUsize::new(0);
+/// Microseconds spent in [`idle_until_next_timer`] since boot
+static IDLE_US: AtomicUsize = AtomicUsize::new(0);
+
+lazy_static! {
+    static ref CLOCK: UPSafeCell<Clock> =
+        unsafe { UPSafeCell::new(Clock::new(COUNTER_BITS, time::read() as u64)) };
+}
+
+/// read the `mtime` register, widened so that it never wraps
 pub fn get_time() -> usize {
-    time::read()
+    CLOCK.exclusive_access().update(time::read() as u64) as usize
 }
 
 /// get current time in microseconds
 pub fn get_time_us() -> usize {
-    time::read() / (CLOCK_FREQ / MICRO_PER_SEC)
+    get_time() / (CLOCK_FREQ / MICRO_PER_SEC)
 }
 
 /// get current time in milliseconds
 pub fn get_time_ms() -> usize {
-    time::read() / (CLOCK_FREQ / MILLI_PER_SEC)
+    get_time() / (CLOCK_FREQ / MILLI_PER_SEC)
+}
+
+/// get current time in scheduler ticks
+pub fn get_time_ticks() -> usize {
+    get_time() / (CLOCK_FREQ / TICKS_PER_SEC)
+}
+
+/// Count a timer interrupt, called from the trap handler
+pub fn tick() {
+    TIMER_TICKS.fetch_add(1, AtomicOrdering::Relaxed);
+}
+
+/// Timer interrupts taken since boot. While user code keeps running they
+/// come every `1 / TICKS_PER_SEC` seconds, or sooner when a time slice ends
+/// first, and none are taken in the kernel. So this counts scheduling
+/// points rather than time, which `get_time_ms` measures.
+pub fn timer_ticks() -> usize {
+    TIMER_TICKS.load(AtomicOrdering::Relaxed)
 }
 
 /// set the next timer interrupt
 pub fn set_next_trigger() {
-    set_timer(get_time() + CLOCK_FREQ / TICKS_PER_SEC);
+    set_next_trigger_within(MILLI_PER_SEC / TICKS_PER_SEC);
+}
+
+/// set the next timer interrupt, at the latest `ms` milliseconds from now
+pub fn set_next_trigger_within(ms: usize) {
+    let ticks = (CLOCK_FREQ / TICKS_PER_SEC).min(ms * (CLOCK_FREQ / MILLI_PER_SEC));
+    // the comparator works on the raw counter, wrapping along with it
+    set_timer(time::read().wrapping_add(ticks));
 }
 
+/// A pending wakeup of `task`, ordered so that `BinaryHeap` pops the earliest first
 pub struct TimerCondVar {
     pub expire_ms: usize,
     pub task: Arc<TaskControlBlock>,
+    /// Sequence number telling this timer apart from later ones of the same task
+    id: usize,
 }
 
 impl PartialEq for TimerCondVar {
@@ -47,9 +124,8 @@ impl PartialEq for TimerCondVar {
 impl Eq for TimerCondVar {}
 impl PartialOrd for TimerCondVar {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        let a = -(self.expire_ms as isize);
-        let b = -(other.expire_ms as isize);
-        Some(a.cmp(&b))
+        // reversed, negating would overflow for far away expiries
+        Some(other.expire_ms.cmp(&self.expire_ms))
     }
 }
 
@@ -59,25 +135,181 @@ impl Ord for TimerCondVar {
     }
 }
 
+/// Min-heap of timers with lazy cancellation
+///
+/// A blocked task has at most one live timer, recorded in `live` by the
+/// address of its TCB together with its expiry. Cancelling only drops that
+/// record, the heap entry stays behind as a tombstone and is discarded when
+/// it reaches the top. Tombstones hold their task, so once they outnumber
+/// the live timers the heap is rebuilt without them: a cancelled timer far
+/// in the future would otherwise keep its TCB alive until it expires.
+pub struct Timers {
+    heap: BinaryHeap<TimerCondVar>,
+    live: BTreeMap<usize, (usize, usize)>,
+    next_id: usize,
+}
+
+impl Timers {
+    pub fn new() -> Self {
+        Self {
+            heap: BinaryHeap::new(),
+            live: BTreeMap::new(),
+            next_id: 0,
+        }
+    }
+
+    fn key(task: &Arc<TaskControlBlock>) -> usize {
+        Arc::as_ptr(task) as usize
+    }
+
+    pub fn push(&mut self, expire_ms: usize, task: Arc<TaskControlBlock>) {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.live.insert(Self::key(&task), (id, expire_ms));
+        self.heap.push(TimerCondVar {
+            expire_ms,
+            task,
+            id,
+        });
+    }
+
+    /// Pop the earliest live timer expired at `current_ms`, skipping tombstones
+    pub fn pop_expired(&mut self, current_ms: usize) -> Option<Arc<TaskControlBlock>> {
+        while let Some(timer) = self.heap.peek() {
+            if timer.expire_ms > current_ms {
+                break;
+            }
+            let timer = self.heap.pop().unwrap();
+            let key = Self::key(&timer.task);
+            if self.live.get(&key).map(|&(id, _)| id) == Some(timer.id) {
+                self.live.remove(&key);
+                return Some(timer.task);
+            }
+        }
+        None
+    }
+
+    pub fn cancel(&mut self, task: &Arc<TaskControlBlock>) -> bool {
+        let cancelled = self.live.remove(&Self::key(task)).is_some();
+        if self.heap.len() > 2 * self.live.len() {
+            self.compact();
+        }
+        cancelled
+    }
+
+    /// Drop all tombstones, in time linear in the size of the heap
+    fn compact(&mut self) {
+        let live = &self.live;
+        self.heap = core::mem::take(&mut self.heap)
+            .into_iter()
+            .filter(|timer| live.get(&Self::key(&timer.task)).map(|&(id, _)| id) == Some(timer.id))
+            .collect();
+    }
+
+    /// Number of live timers, so of tasks waiting for one
+    pub fn pending(&self) -> usize {
+        self.live.len()
+    }
+
+    /// Expiry of the earliest live timer, if there is one
+    pub fn next_expiry(&self) -> Option<usize> {
+        self.live.values().map(|&(_, expire_ms)| expire_ms).min()
+    }
+
+    /// Expiry of the live timer of `task`, if it has one
+    pub fn expiry(&self, task: &Arc<TaskControlBlock>) -> Option<usize> {
+        self.live
+            .get(&Self::key(task))
+            .map(|&(_, expire_ms)| expire_ms)
+    }
+}
+
 lazy_static! {
-    static ref TIMERS: UPSafeCell<BinaryHeap<TimerCondVar>> =
-        unsafe { UPSafeCell::new(BinaryHeap::<TimerCondVar>::new()) };
+    static ref TIMERS: UPSafeCell<Timers> = unsafe { UPSafeCell::new(Timers::new()) };
 }
 
 pub fn add_timer(expire_ms: usize, task: Arc<TaskControlBlock>) {
-    let mut timers = TIMERS.exclusive_access();
-    timers.push(TimerCondVar { expire_ms, task });
+    TIMERS.exclusive_access().push(expire_ms, task);
 }
 
 pub fn check_timer() {
     let current_ms = get_time_ms();
     let mut timers = TIMERS.exclusive_access();
-    while let Some(timer) = timers.peek() {
-        if timer.expire_ms <= current_ms {
-            add_task(Arc::clone(&timer.task));
-            timers.pop();
-        } else {
+    while let Some(task) = timers.pop_expired(current_ms) {
+        task.inner_exclusive_access().timer_fired = true;
+        add_task(task);
+    }
+}
+
+/// Cancel the pending timer of `task`, return whether there was one
+pub fn remove_timer(task: Arc<TaskControlBlock>) -> bool {
+    TIMERS.exclusive_access().cancel(&task)
+}
+
+/// Expiry in milliseconds of the pending timer of `task`, if it has one.
+/// A timer that has expired stays pending until the next `check_timer`.
+pub fn timer_expiry(task: &Arc<TaskControlBlock>) -> Option<usize> {
+    TIMERS.exclusive_access().expiry(task)
+}
+
+/// Number of tasks with a pending timer
+pub fn pending_timers() -> usize {
+    TIMERS.exclusive_access().pending()
+}
+
+/// Wait for an interrupt until the earliest pending timer expires, one
+/// scheduler tick at the latest, then wake the tasks whose timers expired.
+/// Return at once if no task waits for a timer.
+///
+/// Called when the only runnable task yields: instead of running it again
+/// and again until a sleeper wakes, the CPU halts in between. Interrupts are
+/// off in the kernel, the pending timer interrupt only ends `wfi` and is
+/// cleared when the next task is scheduled. Waiting no longer than a tick
+/// keeps polling loops such as console reads responsive.
+pub fn idle_until_next_timer() {
+    let expire_ms = match TIMERS.exclusive_access().next_expiry() {
+        Some(expire_ms) => expire_ms,
+        None => return,
+    };
+    let start_us = get_time_us();
+    let deadline_ms = expire_ms.min(get_time_ms() + MILLI_PER_SEC / TICKS_PER_SEC);
+    loop {
+        let now_ms = get_time_ms();
+        if now_ms >= deadline_ms {
             break;
         }
+        set_next_trigger_within(deadline_ms - now_ms);
+        unsafe {
+            wfi();
+        }
+    }
+    IDLE_US.fetch_add(get_time_us() - start_us, AtomicOrdering::Relaxed);
+    check_timer();
+}
+
+/// Microseconds the CPU spent idle waiting for a timer since boot
+pub fn idle_time_us() -> usize {
+    IDLE_US.load(AtomicOrdering::Relaxed)
+}
+
+/// Check that `Clock` stays monotonic across wraps of a narrow counter
+pub fn clock_wrap_test() {
+    let mut clock = Clock::new(16, 0xfff0);
+    assert_eq!(clock.update(0xfffe), 0xfffe);
+    // wrapped: 0xffff, 0x0 .. 0x20 are 0x22 cycles after 0xfffe
+    assert_eq!(clock.update(0x20), 0x1_0020);
+    assert_eq!(clock.update(0x20), 0x1_0020);
+    let mut now = 0x1_0020;
+    for _ in 0..3 {
+        now = clock.update(0xfff0) + 0x30;
+        assert_eq!(clock.update(0x20), now);
     }
+    assert_eq!(now, 0x4_0020);
+    // the ms deadline of a sleep taken just before a wrap is still ahead
+    let ms = |cycles: u64| cycles as usize / (CLOCK_FREQ / MILLI_PER_SEC);
+    let mut clock = Clock::new(16, 0);
+    let expire_ms = ms(clock.update(0xffff)) + 1;
+    assert!(ms(clock.update(0x10)) < expire_ms);
+    assert!(ms(clock.update(0x10 + (CLOCK_FREQ / MILLI_PER_SEC) as u64)) >= expire_ms);
+    info!("clock_wrap_test passed!");
 }
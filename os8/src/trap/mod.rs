@@ -17,10 +17,12 @@ mod context;
 use crate::config::TRAMPOLINE;
 use crate::syscall::syscall;
 use crate::task::{
-    current_trap_cx, current_trap_cx_user_va, current_user_token, exit_current_and_run_next,
-    suspend_current_and_run_next,
+    abort_code_of_current, age_ready_tasks, check_signals_of_current, current_process,
+    current_task, current_trap_cx, current_trap_cx_user_va, current_user_token,
+    exit_current_and_run_next, group_exit_of_current, limit_current_and_run_next,
+    preempt_current_and_run_next,
 };
-use crate::timer::{check_timer, set_next_trigger};
+use crate::timer::{check_timer, get_time_ms, get_time_us, set_next_trigger_within, tick};
 use riscv::register::{
     mtvec::TrapMode,
     scause::{self, Exception, Interrupt, Trap},
@@ -62,7 +64,10 @@ pub fn trap_handler() -> ! {
             let mut cx = current_trap_cx();
             cx.sepc += 4;
             // get system call return value
-            let result = syscall(cx.x[17], [cx.x[10], cx.x[11], cx.x[12], cx.x[13]]);
+            let result = syscall(
+                cx.x[17],
+                [cx.x[10], cx.x[11], cx.x[12], cx.x[13], cx.x[14], cx.x[15]],
+            );
             // cx is changed during sys_exec, so we have to call it again
             cx = current_trap_cx();
             cx.x[10] = result as usize;
@@ -79,6 +84,10 @@ pub fn trap_handler() -> ! {
                 stval,
                 current_trap_cx().sepc,
             );
+            current_process()
+                .inner_exclusive_access()
+                .rusage
+                .page_faults += 1;
             // page fault exit code
             exit_current_and_run_next(-2);
         }
@@ -88,9 +97,23 @@ pub fn trap_handler() -> ! {
             exit_current_and_run_next(-3);
         }
         Trap::Interrupt(Interrupt::SupervisorTimer) => {
-            set_next_trigger();
+            tick();
             check_timer();
-            suspend_current_and_run_next();
+            age_ready_tasks();
+            let task = current_task().unwrap();
+            let task_inner = task.inner_exclusive_access();
+            let over_limit = task_inner.over_cpu_limit(get_time_us());
+            let remaining = task_inner.remaining_slice_ms(get_time_ms());
+            drop(task_inner);
+            drop(task);
+            if over_limit {
+                limit_current_and_run_next();
+            } else if remaining == 0 {
+                // the next trigger is set when a task is scheduled
+                preempt_current_and_run_next();
+            } else {
+                set_next_trigger_within(remaining);
+            }
         }
         _ => {
             panic!(
@@ -100,6 +123,19 @@ pub fn trap_handler() -> ! {
             );
         }
     }
+    // the process is exiting as a whole, follow the other threads
+    if let Some(exit_code) = group_exit_of_current() {
+        exit_current_and_run_next(exit_code);
+    }
+    // a deadlock victim exits, releasing what it holds
+    if let Some(exit_code) = abort_code_of_current() {
+        exit_current_and_run_next(exit_code);
+    }
+    // deliver fatal signals before going back to user mode
+    if let Some((errno, msg)) = check_signals_of_current() {
+        println!("[kernel] {}", msg);
+        exit_current_and_run_next(errno);
+    }
     trap_return();
 }
 
@@ -0,0 +1,38 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{exit, get_affinity, gettid, set_affinity, thread_create, waittid};
+
+// 理想结果：默认可在 0 号核上运行，设置的掩码可以读回；空掩码返回 -1，
+// 不包含 0 号核的掩码返回 -EINVAL，线程不存在时返回 -1
+
+const EINVAL: isize = 22;
+
+fn worker() -> ! {
+    exit(get_affinity(gettid() as usize) as i32)
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let tid = gettid() as usize;
+    assert_eq!(get_affinity(tid), 1);
+    assert_eq!(set_affinity(tid, 0), -1);
+    assert_eq!(set_affinity(tid, 0b10), -EINVAL);
+    assert_eq!(set_affinity(tid, usize::MAX), -EINVAL);
+    assert_eq!(get_affinity(tid), 1);
+    assert_eq!(set_affinity(100, 1), -1);
+    assert_eq!(get_affinity(100), -1);
+
+    // bits of absent harts are kept
+    assert_eq!(set_affinity(tid, 0b11), 0);
+    assert_eq!(get_affinity(tid), 0b11);
+    // still scheduled with a restricted mask
+    let thread = thread_create(worker as usize, 0) as usize;
+    assert_eq!(set_affinity(thread, 1), 0);
+    assert_eq!(waittid(thread), 1);
+    println!("affinity test passed!");
+    0
+}
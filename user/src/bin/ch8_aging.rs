@@ -0,0 +1,48 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{exit, get_time, set_aging_interval, set_priority, yield_};
+use user_lib::{thread_create, waittid};
+
+// 高优先级线程一直占用 CPU，低优先级线程的步长极大
+// 理想结果：老化机制提升低优先级线程，使其在有限时间内完成
+
+const AGING_TICKS: usize = 10;
+const LOW_ROUNDS: usize = 5;
+
+static mut LOW_DONE: bool = false;
+
+fn high() -> ! {
+    set_priority(isize::MAX);
+    while unsafe { !core::ptr::read_volatile(&LOW_DONE) } {}
+    exit(0)
+}
+
+fn low() -> ! {
+    set_priority(2);
+    // every round pushes the pass far beyond the spinner's
+    for _ in 0..LOW_ROUNDS {
+        yield_();
+    }
+    unsafe {
+        core::ptr::write_volatile(&mut LOW_DONE, true);
+    }
+    exit(0)
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let old = set_aging_interval(AGING_TICKS);
+    let start = get_time();
+    let high_tid = thread_create(high as usize, 0);
+    let low_tid = thread_create(low as usize, 0);
+    waittid(low_tid as usize);
+    waittid(high_tid as usize);
+    println!("low priority thread finished in {}ms", get_time() - start);
+    set_aging_interval(old as usize);
+    println!("aging test passed!");
+    0
+}
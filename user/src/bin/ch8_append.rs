@@ -0,0 +1,42 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{close, open, read, write, OpenFlags};
+
+// 理想结果：两个追加模式的文件描述符交替写入时互不覆盖
+
+const EINVAL: isize = 22;
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let fname = "append\0";
+    let fd = open(fname, OpenFlags::CREATE | OpenFlags::WRONLY);
+    assert!(fd > 0);
+    write(fd as usize, b"log:");
+    close(fd as usize);
+
+    let a = open(fname, OpenFlags::WRONLY | OpenFlags::APPEND);
+    let b = open(fname, OpenFlags::RDWR | OpenFlags::APPEND);
+    assert!(a > 0 && b > 0);
+    let (a, b) = (a as usize, b as usize);
+    write(a, b"a1,");
+    write(b, b"b1,");
+    write(a, b"a2,");
+    write(b, b"b2");
+    close(a);
+    close(b);
+
+    let fd = open(fname, OpenFlags::RDONLY);
+    assert!(fd > 0);
+    let mut buffer = [0u8; 64];
+    let read_len = read(fd as usize, &mut buffer) as usize;
+    close(fd as usize);
+    assert_eq!(&buffer[..read_len], b"log:a1,b1,a2,b2");
+
+    assert_eq!(open(fname, OpenFlags::RDONLY | OpenFlags::APPEND), -EINVAL);
+    println!("append test passed!");
+    0
+}
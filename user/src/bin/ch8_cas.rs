@@ -0,0 +1,91 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+extern crate alloc;
+
+use alloc::vec::Vec;
+use core::sync::atomic::{AtomicU32, Ordering};
+use user_lib::{cas, exit, futex_wait, futex_wake, sys_cas, thread_create, waittid};
+
+// 理想结果：值相等时交换并返回旧值，不等时不修改只返回当前值；未对齐返回 -EINVAL，
+// 未映射、只读或用户态不可访问（如 TrapContext）的地址返回 -EFAULT；
+// 仅用 cas 和 futex 实现的互斥锁能保护计数器
+
+const EFAULT: isize = 14;
+const EINVAL: isize = 22;
+const PER_THREAD: usize = 200;
+const THREAD_COUNT: usize = 4;
+const UNMAPPED: usize = 0x1000_0000;
+const PAGE_SIZE: usize = 0x1000;
+/// Trap context of the main thread, mapped without `U` just below the trampoline
+const TRAP_CONTEXT: usize = usize::MAX - 2 * PAGE_SIZE + 1;
+
+const UNLOCKED: u32 = 0;
+const LOCKED: u32 = 1;
+const CONTENDED: u32 = 2;
+
+static LOCK: AtomicU32 = AtomicU32::new(UNLOCKED);
+static READ_ONLY: u32 = 5;
+static mut A: usize = 0;
+
+fn lock() {
+    let mut state = cas(&LOCK, UNLOCKED, LOCKED) as u32;
+    while state != UNLOCKED {
+        // mark it contended so that the holder wakes a waiter
+        if state == CONTENDED || cas(&LOCK, LOCKED, CONTENDED) as u32 != UNLOCKED {
+            futex_wait(&LOCK, CONTENDED);
+        }
+        state = cas(&LOCK, UNLOCKED, CONTENDED) as u32;
+    }
+}
+
+fn unlock() {
+    // only the holder moves the word away from CONTENDED
+    if cas(&LOCK, LOCKED, UNLOCKED) as u32 == CONTENDED {
+        cas(&LOCK, CONTENDED, UNLOCKED);
+        futex_wake(&LOCK, 1);
+    }
+}
+
+unsafe fn f() -> ! {
+    for _ in 0..PER_THREAD {
+        lock();
+        let a = &mut A as *mut usize;
+        let cur = a.read_volatile();
+        a.write_volatile(cur + 1);
+        unlock();
+    }
+    exit(0)
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let word = AtomicU32::new(7);
+    assert_eq!(cas(&word, 8, 9), 7);
+    assert_eq!(word.load(Ordering::SeqCst), 7);
+    assert_eq!(cas(&word, 7, 9), 7);
+    assert_eq!(word.load(Ordering::SeqCst), 9);
+    // values with the top bit set do not look like errors
+    assert_eq!(cas(&word, 9, u32::MAX), 9);
+    assert_eq!(cas(&word, u32::MAX, 0), u32::MAX as isize);
+
+    let addr = &word as *const _ as usize;
+    assert_eq!(sys_cas(addr + 1, 0, 1), -EINVAL);
+    assert_eq!(sys_cas(UNMAPPED, 0, 1), -EFAULT);
+    assert_eq!(sys_cas(&READ_ONLY as *const u32 as usize, 5, 6), -EFAULT);
+    // a no-op swap, harmless even if the kernel let it through
+    assert_eq!(sys_cas(TRAP_CONTEXT, 0, 0), -EFAULT);
+
+    let threads: Vec<_> = (0..THREAD_COUNT)
+        .map(|_| thread_create(f as usize, 0) as usize)
+        .collect();
+    for tid in threads {
+        assert_eq!(waittid(tid), 0);
+    }
+    assert_eq!(unsafe { A }, PER_THREAD * THREAD_COUNT);
+    assert_eq!(LOCK.load(Ordering::SeqCst), UNLOCKED);
+    println!("cas test passed!");
+    0
+}
@@ -0,0 +1,78 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use core::ptr::{read_volatile, write_volatile};
+use user_lib::{check_deadlock, deadlock_stats, exit, mutex_blocking_create, mutex_lock};
+use user_lib::{mutex_unlock, sleep_blocking, thread_create, waittid, yield_, DeadlockStats};
+
+// 理想结果：未开启死锁检测也能查询；只是等待持锁线程时返回 0，
+// 两个线程以相反顺序加锁陷入死锁后返回 2，查询不改变状态也不计入检测统计
+
+const POLL_MS: usize = 10;
+
+static mut A: usize = 0;
+static mut B: usize = 0;
+static mut HELD: usize = 0;
+
+unsafe fn waiter() -> ! {
+    assert_eq!(mutex_lock(A), 0);
+    mutex_unlock(A);
+    exit(0)
+}
+
+unsafe fn lock_both(first: usize, second: usize) -> ! {
+    assert_eq!(mutex_lock(first), 0);
+    write_volatile(&mut HELD, read_volatile(&HELD) + 1);
+    while read_volatile(&HELD) < 2 {
+        yield_();
+    }
+    mutex_lock(second);
+    unreachable!()
+}
+
+unsafe fn a_then_b() -> ! {
+    lock_both(A, B)
+}
+
+unsafe fn b_then_a() -> ! {
+    lock_both(B, A)
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    unsafe {
+        A = mutex_blocking_create() as usize;
+        B = mutex_blocking_create() as usize;
+    }
+    assert_eq!(check_deadlock(), 0);
+
+    // waiting for a thread that can go on is no deadlock
+    unsafe {
+        assert_eq!(mutex_lock(A), 0);
+    }
+    let thread = thread_create(waiter as usize, 0) as usize;
+    sleep_blocking(POLL_MS);
+    assert_eq!(check_deadlock(), 0);
+    unsafe {
+        mutex_unlock(A);
+    }
+    assert_eq!(waittid(thread), 0);
+
+    thread_create(a_then_b as usize, 0);
+    thread_create(b_then_a as usize, 0);
+    while check_deadlock() == 0 {
+        sleep_blocking(POLL_MS);
+    }
+    // polling again finds the same state
+    assert_eq!(check_deadlock(), 2);
+    assert_eq!(check_deadlock(), 2);
+    let mut stats = DeadlockStats::default();
+    deadlock_stats(&mut stats);
+    assert_eq!((stats.runs, stats.deadlocks), (0, 0));
+    println!("check deadlock test passed!");
+    // the deadlocked threads go away with the process
+    0
+}
@@ -0,0 +1,39 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{close, dup, pipe, read, write};
+
+// 理想结果：关闭无效或已关闭的 fd 返回 -EBADF，管道的所有写端关闭后才读到 EOF
+
+const EBADF: isize = 9;
+const EPIPE: isize = 32;
+
+#[no_mangle]
+pub fn main() -> i32 {
+    assert_eq!(close(100000), -EBADF);
+    let mut pipe_fd = [0usize; 2];
+    pipe(&mut pipe_fd);
+    assert_eq!(close(pipe_fd[0] + 100), -EBADF);
+
+    // a second write end keeps the pipe open
+    let write_dup = dup(pipe_fd[1]) as usize;
+    assert_eq!(close(pipe_fd[1]), 0);
+    assert_eq!(close(pipe_fd[1]), -EBADF);
+    assert_eq!(write(write_dup, b"x"), 1);
+    let mut buffer = [0u8; 4];
+    assert_eq!(read(pipe_fd[0], &mut buffer), 1);
+    assert_eq!(close(write_dup), 0);
+    assert_eq!(read(pipe_fd[0], &mut buffer), 0);
+    assert_eq!(close(pipe_fd[0]), 0);
+    assert_eq!(close(pipe_fd[0]), -EBADF);
+
+    pipe(&mut pipe_fd);
+    assert_eq!(close(pipe_fd[0]), 0);
+    assert_eq!(write(pipe_fd[1], b"lost"), -EPIPE);
+    assert_eq!(close(pipe_fd[1]), 0);
+    println!("close test passed!");
+    0
+}
@@ -0,0 +1,78 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use core::ptr::{read_volatile, write_volatile};
+use user_lib::{condvar_create, condvar_destroy, condvar_signal, condvar_sticky_create};
+use user_lib::{condvar_wait, exit, mutex_blocking_create, mutex_lock, mutex_unlock};
+use user_lib::{set_sync_quota, thread_create, waittid, yield_, QUOTA_CONDVAR, SYNC_ID_GEN_SHIFT};
+
+// 理想结果：有线程等待（包括已被唤醒但尚未返回）时销毁失败，销毁后重新创建得到同一槽位的全新条件变量，
+// 且销毁会归还配额
+
+static mut MUTEX: usize = 0;
+static mut CONDVAR: usize = 0;
+static mut WOKEN: bool = false;
+
+unsafe fn waiter() -> ! {
+    mutex_lock(MUTEX);
+    assert_eq!(condvar_wait(CONDVAR, MUTEX), 0);
+    write_volatile(&mut WOKEN, true);
+    mutex_unlock(MUTEX);
+    exit(0)
+}
+
+unsafe fn wait_until_woken(condvar: usize) {
+    CONDVAR = condvar;
+    write_volatile(&mut WOKEN, false);
+    let thread = thread_create(waiter as usize, 0) as usize;
+    for _ in 0..10 {
+        yield_();
+    }
+    assert!(!read_volatile(&WOKEN));
+    assert_eq!(condvar_destroy(condvar), -1);
+    // signalled but not back from its wait yet
+    condvar_signal(condvar);
+    assert_eq!(condvar_destroy(condvar), -1);
+    waittid(thread);
+    assert!(read_volatile(&WOKEN));
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    unsafe {
+        MUTEX = mutex_blocking_create() as usize;
+    }
+    let sticky = condvar_sticky_create();
+    assert!(sticky >= 0);
+    let sticky = sticky as usize;
+    unsafe {
+        wait_until_woken(sticky);
+    }
+    assert_eq!(condvar_destroy(sticky), 0);
+    assert_eq!(condvar_destroy(sticky), -1);
+    assert_eq!(condvar_destroy(sticky + 100), -1);
+
+    // a signal kept by the old condvar does not reach the new one
+    let sticky = condvar_sticky_create() as usize;
+    condvar_signal(sticky);
+    assert_eq!(condvar_destroy(sticky), 0);
+    let fresh = condvar_create() as usize;
+    assert_eq!(fresh & ((1 << SYNC_ID_GEN_SHIFT) - 1), sticky);
+    unsafe {
+        wait_until_woken(fresh);
+    }
+    assert_eq!(condvar_destroy(fresh), 0);
+
+    // a destroyed condvar does not count against the quota
+    assert_eq!(set_sync_quota(QUOTA_CONDVAR, 1), 0);
+    let only = condvar_create();
+    assert!(only >= 0);
+    assert_eq!(condvar_create(), -1);
+    assert_eq!(condvar_destroy(only as usize), 0);
+    assert!(condvar_create() >= 0);
+    println!("condvar destroy test passed!");
+    0
+}
@@ -0,0 +1,65 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{condvar_create, condvar_signal, condvar_wait, exit, mutex_blocking_create};
+use user_lib::{mutex_lock, mutex_unlock, sleep, thread_create, waittid};
+
+// 理想结果：未持有互斥锁时等待返回 -EPERM，持有时等待后仍然拥有互斥锁
+
+const EPERM: isize = 1;
+const MUTEX: usize = 0;
+const CONDVAR: usize = 0;
+
+static mut HOLDING: bool = false;
+
+fn holder() -> ! {
+    mutex_lock(MUTEX);
+    unsafe {
+        HOLDING = true;
+    }
+    sleep(50);
+    unsafe {
+        HOLDING = false;
+    }
+    mutex_unlock(MUTEX);
+    exit(0)
+}
+
+fn signaler() -> ! {
+    for _ in 0..2 {
+        sleep(20);
+        mutex_lock(MUTEX);
+        condvar_signal(CONDVAR);
+        mutex_unlock(MUTEX);
+    }
+    exit(0)
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    assert_eq!(mutex_blocking_create() as usize, MUTEX);
+    assert_eq!(condvar_create() as usize, CONDVAR);
+    // not locked at all
+    assert_eq!(condvar_wait(CONDVAR, MUTEX), -EPERM);
+
+    // locked by another thread
+    let tid = thread_create(holder as usize, 0) as usize;
+    while unsafe { !HOLDING } {
+        sleep(1);
+    }
+    assert_eq!(condvar_wait(CONDVAR, MUTEX), -EPERM);
+    waittid(tid);
+
+    // the mutex is ours again after every wait, so waiting twice works
+    let tid = thread_create(signaler as usize, 0) as usize;
+    mutex_lock(MUTEX);
+    assert_eq!(condvar_wait(CONDVAR, MUTEX), 0);
+    assert_eq!(condvar_wait(CONDVAR, MUTEX), 0);
+    mutex_unlock(MUTEX);
+    waittid(tid);
+    println!("condvar eperm test passed!");
+    0
+}
@@ -0,0 +1,63 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+extern crate alloc;
+
+use alloc::vec::Vec;
+use user_lib::{condvar_create, condvar_signal_n, condvar_wait, exit, mutex_blocking_create};
+use user_lib::{mutex_lock, mutex_unlock, sleep, thread_create, waittid};
+
+// 理想结果：每次最多唤醒 n 个等待者，返回实际唤醒的数量
+
+const MUTEX: usize = 0;
+const CONDVAR: usize = 0;
+const THREAD_N: usize = 5;
+
+static mut WAITING: usize = 0;
+static mut WOKEN: usize = 0;
+
+unsafe fn waiter() -> ! {
+    mutex_lock(MUTEX);
+    WAITING += 1;
+    condvar_wait(CONDVAR, MUTEX);
+    WOKEN += 1;
+    mutex_unlock(MUTEX);
+    exit(0)
+}
+
+fn woken() -> usize {
+    mutex_lock(MUTEX);
+    let woken = unsafe { WOKEN };
+    mutex_unlock(MUTEX);
+    woken
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    assert_eq!(mutex_blocking_create() as usize, MUTEX);
+    assert_eq!(condvar_create() as usize, CONDVAR);
+    let threads: Vec<_> = (0..THREAD_N)
+        .map(|_| thread_create(waiter as usize, 0) as usize)
+        .collect();
+    while unsafe { WAITING } < THREAD_N {
+        sleep(1);
+    }
+
+    assert_eq!(condvar_signal_n(CONDVAR, 2), 2);
+    sleep(20);
+    assert_eq!(woken(), 2);
+    assert_eq!(condvar_signal_n(CONDVAR, 0), 0);
+    sleep(20);
+    assert_eq!(woken(), 2);
+    // fewer waiters than asked for
+    assert_eq!(condvar_signal_n(CONDVAR, 10), (THREAD_N - 2) as isize);
+    for tid in threads {
+        waittid(tid);
+    }
+    assert_eq!(woken(), THREAD_N);
+    assert_eq!(condvar_signal_n(CONDVAR, 3), 0);
+    println!("condvar signal n test passed!");
+    0
+}
@@ -0,0 +1,56 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+extern crate alloc;
+
+use alloc::vec::Vec;
+use user_lib::{condvar_create, condvar_signal_tid, condvar_wait, exit, gettid};
+use user_lib::{mutex_blocking_create, mutex_lock, mutex_unlock, sleep, thread_create, waittid};
+
+// 理想结果：按指定的线程唤醒条件变量的等待者，不在等待的线程返回 -1
+
+const MUTEX: usize = 0;
+const CONDVAR: usize = 0;
+const THREAD_N: usize = 3;
+
+static mut WAITING: usize = 0;
+static mut ORDER: [usize; THREAD_N] = [0; THREAD_N];
+static mut WOKEN: usize = 0;
+
+unsafe fn waiter() -> ! {
+    mutex_lock(MUTEX);
+    WAITING += 1;
+    condvar_wait(CONDVAR, MUTEX);
+    ORDER[WOKEN] = gettid() as usize;
+    WOKEN += 1;
+    mutex_unlock(MUTEX);
+    exit(0)
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    assert_eq!(mutex_blocking_create() as usize, MUTEX);
+    assert_eq!(condvar_create() as usize, CONDVAR);
+    let tids: Vec<_> = (0..THREAD_N)
+        .map(|_| thread_create(waiter as usize, 0) as usize)
+        .collect();
+    while unsafe { WAITING } < THREAD_N {
+        sleep(1);
+    }
+    // wake them from the last to arrive to the first
+    for (i, &tid) in tids.iter().rev().enumerate() {
+        mutex_lock(MUTEX);
+        assert_eq!(condvar_signal_tid(CONDVAR, tid), 0);
+        mutex_unlock(MUTEX);
+        waittid(tid);
+        assert_eq!(unsafe { ORDER[i] }, tid);
+        // woken and gone
+        assert_eq!(condvar_signal_tid(CONDVAR, tid), -1);
+    }
+    assert_eq!(condvar_signal_tid(CONDVAR, gettid() as usize), -1);
+    assert_eq!(condvar_signal_tid(CONDVAR, 100), -1);
+    println!("condvar signal tid test passed!");
+    0
+}
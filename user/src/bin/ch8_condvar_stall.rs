@@ -0,0 +1,100 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use core::ptr::{read_volatile, write_volatile};
+use user_lib::{condvar_create, condvar_signal, condvar_wait, exit, mutex_blocking_create};
+use user_lib::{mutex_lock, mutex_unlock, set_deadlock_checks, thread_create, waittid, yield_};
+use user_lib::{CONDVAR_STALL, DEADLOCK_DETECT};
+
+// 理想结果：等待条件变量后没有线程能发出信号时，等待失败并返回 -EDEADLK 且仍持有互斥锁；
+// 线程持锁等待条件变量、而唯一能发信号的线程去抢这把锁时，检测到死锁
+
+const EDEADLK: isize = 35;
+const EDEADMUTEX: isize = 0xdead;
+
+static mut MUTEX_A: usize = 0;
+static mut MUTEX_B: usize = 0;
+static mut CONDVAR: usize = 0;
+static mut READY: bool = false;
+static mut GO: bool = false;
+
+unsafe fn wait_for_go() {
+    write_volatile(&mut READY, true);
+    while !read_volatile(&GO) {
+        assert_eq!(condvar_wait(CONDVAR, MUTEX_A), 0);
+    }
+}
+
+unsafe fn waiter() -> ! {
+    mutex_lock(MUTEX_A);
+    wait_for_go();
+    mutex_unlock(MUTEX_A);
+    exit(0)
+}
+
+unsafe fn holding_waiter() -> ! {
+    mutex_lock(MUTEX_B);
+    mutex_lock(MUTEX_A);
+    wait_for_go();
+    mutex_unlock(MUTEX_A);
+    mutex_unlock(MUTEX_B);
+    exit(0)
+}
+
+/// Start `f`, wait until it sleeps on the condvar and take `MUTEX_A`
+unsafe fn start(f: unsafe fn() -> !) -> usize {
+    write_volatile(&mut READY, false);
+    write_volatile(&mut GO, false);
+    let tid = thread_create(f as usize, 0) as usize;
+    while !read_volatile(&READY) {
+        yield_();
+    }
+    mutex_lock(MUTEX_A);
+    tid
+}
+
+/// Let the thread waiting on the condvar finish
+unsafe fn release(tid: usize) {
+    write_volatile(&mut GO, true);
+    condvar_signal(CONDVAR);
+    mutex_unlock(MUTEX_A);
+    waittid(tid);
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    unsafe {
+        MUTEX_A = mutex_blocking_create() as usize;
+        MUTEX_B = mutex_blocking_create() as usize;
+        CONDVAR = condvar_create() as usize;
+    }
+    assert_eq!(set_deadlock_checks(DEADLOCK_DETECT | CONDVAR_STALL), 0);
+    let (mutex_a, mutex_b, condvar) = unsafe { (MUTEX_A, MUTEX_B, CONDVAR) };
+
+    // alone, nobody could ever signal, and the mutex is kept
+    mutex_lock(mutex_a);
+    assert_eq!(condvar_wait(condvar, mutex_a), -EDEADLK);
+    mutex_unlock(mutex_a);
+
+    // both threads would wait on the condvar
+    unsafe {
+        let tid = start(waiter);
+        assert_eq!(condvar_wait(condvar, mutex_a), -EDEADLK);
+        release(tid);
+    }
+
+    // the only signaller blocks on a mutex the waiter keeps
+    unsafe {
+        let tid = start(holding_waiter);
+        mutex_unlock(mutex_a);
+        assert_eq!(mutex_lock(mutex_b), -EDEADMUTEX);
+        mutex_lock(mutex_a);
+        release(tid);
+    }
+    set_deadlock_checks(0);
+    println!("condvar stall test passed!");
+    0
+}
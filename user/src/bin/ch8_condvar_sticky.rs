@@ -0,0 +1,58 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{condvar_create, condvar_signal, condvar_sticky_create, condvar_wait};
+use user_lib::{exit, mutex_blocking_create, mutex_lock, mutex_unlock};
+use user_lib::{sleep_blocking, thread_create, waittid};
+
+// 理想结果：粘性条件变量记住一次无人等待时的 signal，经典条件变量的 signal 会丢失
+
+static mut MUTEX: usize = 0;
+static mut WOKEN: bool = false;
+
+unsafe fn waiter(condvar: usize) -> ! {
+    mutex_lock(MUTEX);
+    condvar_wait(condvar, MUTEX);
+    WOKEN = true;
+    mutex_unlock(MUTEX);
+    exit(0)
+}
+
+/// Start a waiter and check that it needs one more signal to go on
+fn wake_later(condvar: usize) {
+    unsafe {
+        WOKEN = false;
+    }
+    let tid = thread_create(waiter as usize, condvar) as usize;
+    sleep_blocking(30);
+    assert!(!unsafe { WOKEN });
+    condvar_signal(condvar);
+    waittid(tid);
+    assert!(unsafe { WOKEN });
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let mutex = mutex_blocking_create() as usize;
+    unsafe {
+        MUTEX = mutex;
+    }
+
+    // two signals without waiters count as one
+    let sticky = condvar_sticky_create() as usize;
+    condvar_signal(sticky);
+    condvar_signal(sticky);
+    mutex_lock(mutex);
+    condvar_wait(sticky, mutex);
+    mutex_unlock(mutex);
+    wake_later(sticky);
+
+    let classic = condvar_create() as usize;
+    condvar_signal(classic);
+    wake_later(classic);
+    println!("condvar sticky test passed!");
+    0
+}
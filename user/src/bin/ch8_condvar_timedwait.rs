@@ -0,0 +1,67 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{condvar_create, condvar_signal, condvar_signal_n, condvar_timedwait, exit};
+use user_lib::{get_time, mutex_blocking_create, mutex_lock, mutex_unlock, sleep_blocking};
+use user_lib::{sys_mutex_unlock, thread_create, waittid};
+
+// 理想结果：超时前被唤醒返回 0，超时返回 -ETIMEDOUT，两种情况返回时都重新持有互斥锁；
+// 超时的等待者不会留在等待队列中，未持有互斥锁时返回 -EPERM
+
+const EPERM: isize = 1;
+const ETIMEDOUT: isize = 110;
+const TIMEOUT_MS: usize = 30;
+const SIGNAL_AFTER_MS: usize = 20;
+const LONG_MS: usize = 1000;
+
+static mut MUTEX: usize = 0;
+static mut CONDVAR: usize = 0;
+
+unsafe fn signaller() -> ! {
+    sleep_blocking(SIGNAL_AFTER_MS);
+    mutex_lock(MUTEX);
+    condvar_signal(CONDVAR);
+    mutex_unlock(MUTEX);
+    exit(0)
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let mutex = mutex_blocking_create() as usize;
+    let condvar = condvar_create() as usize;
+    unsafe {
+        MUTEX = mutex;
+        CONDVAR = condvar;
+    }
+    assert_eq!(condvar_timedwait(condvar, mutex, TIMEOUT_MS), -EPERM);
+
+    // nobody signals
+    assert_eq!(mutex_lock(mutex), 0);
+    let start = get_time();
+    assert_eq!(condvar_timedwait(condvar, mutex, TIMEOUT_MS), -ETIMEDOUT);
+    assert!(get_time() - start >= TIMEOUT_MS as isize);
+    // not left queued, and the mutex is ours again
+    assert_eq!(condvar_signal_n(condvar, 10), 0);
+    assert_eq!(sys_mutex_unlock(mutex), 0);
+
+    // signalled in time
+    let thread = thread_create(signaller as usize, 0) as usize;
+    assert_eq!(mutex_lock(mutex), 0);
+    let start = get_time();
+    assert_eq!(condvar_timedwait(condvar, mutex, LONG_MS), 0);
+    assert!(get_time() - start < LONG_MS as isize);
+    assert_eq!(sys_mutex_unlock(mutex), 0);
+    waittid(thread);
+
+    // the cancelled timer does not cut a later wait short
+    assert_eq!(mutex_lock(mutex), 0);
+    let start = get_time();
+    assert_eq!(condvar_timedwait(condvar, mutex, TIMEOUT_MS), -ETIMEDOUT);
+    assert!(get_time() - start >= TIMEOUT_MS as isize);
+    mutex_unlock(mutex);
+    println!("condvar timedwait test passed!");
+    0
+}
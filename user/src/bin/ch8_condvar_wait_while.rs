@@ -0,0 +1,58 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{condvar_create, condvar_signal, condvar_wait_while, exit, mutex_blocking_create};
+use user_lib::{mutex_lock, mutex_unlock, sleep, thread_create, waittid};
+
+// 理想结果：条件未满足时的唤醒不会让等待者返回，条件满足后才返回
+
+const MUTEX: usize = 0;
+const CONDVAR: usize = 0;
+
+static mut READY: usize = 0;
+static mut WOKEN: bool = false;
+
+unsafe fn waiter() -> ! {
+    mutex_lock(MUTEX);
+    assert_eq!(condvar_wait_while(CONDVAR, MUTEX, &READY, 0), 0);
+    assert_eq!(READY, 1);
+    WOKEN = true;
+    mutex_unlock(MUTEX);
+    exit(0)
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    assert_eq!(mutex_blocking_create() as usize, MUTEX);
+    assert_eq!(condvar_create() as usize, CONDVAR);
+    // a predicate that no longer holds returns at once
+    mutex_lock(MUTEX);
+    unsafe {
+        assert_eq!(condvar_wait_while(CONDVAR, MUTEX, &READY, 1), 0);
+    }
+    mutex_unlock(MUTEX);
+
+    let tid = thread_create(waiter as usize, 0) as usize;
+    sleep(50);
+    // signals without a change of the predicate are absorbed by the loop
+    for _ in 0..3 {
+        mutex_lock(MUTEX);
+        condvar_signal(CONDVAR);
+        mutex_unlock(MUTEX);
+        sleep(20);
+        assert!(unsafe { !WOKEN });
+    }
+    mutex_lock(MUTEX);
+    unsafe {
+        READY = 1;
+    }
+    condvar_signal(CONDVAR);
+    mutex_unlock(MUTEX);
+    waittid(tid);
+    assert!(unsafe { WOKEN });
+    println!("condvar wait while test passed!");
+    0
+}
@@ -0,0 +1,54 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use core::ptr::{read_volatile, write_volatile};
+use user_lib::{clear_cpu_limit, exit, set_cpu_limit, sleep_blocking, task_stats};
+use user_lib::{thread_create, waittid, TaskStats};
+
+// 理想结果：运行时间达到上限的线程不再运行，解除上限后继续运行
+
+const LIMIT_MS: usize = 50;
+
+static mut COUNTER: usize = 0;
+static mut STOP: bool = false;
+
+unsafe fn spinner() -> ! {
+    while !read_volatile(&STOP) {
+        write_volatile(&mut COUNTER, read_volatile(&COUNTER) + 1);
+    }
+    exit(0)
+}
+
+fn counter() -> usize {
+    unsafe { read_volatile(&COUNTER) }
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let tid = thread_create(spinner as usize, 0) as usize;
+    assert_eq!(set_cpu_limit(tid, LIMIT_MS), 0);
+    assert_eq!(set_cpu_limit(tid + 100, LIMIT_MS), -1);
+    sleep_blocking(4 * LIMIT_MS);
+
+    // suspended by now, it no longer makes progress
+    let mut stats = TaskStats::default();
+    task_stats(tid, &mut stats);
+    assert!(stats.run_time_us >= LIMIT_MS * 1000);
+    let count = counter();
+    sleep_blocking(LIMIT_MS);
+    assert_eq!(counter(), count);
+    println!("thread {} stopped after {}us", tid, stats.run_time_us);
+
+    assert_eq!(clear_cpu_limit(tid), 0);
+    sleep_blocking(LIMIT_MS);
+    assert!(counter() > count);
+    unsafe {
+        write_volatile(&mut STOP, true);
+    }
+    assert_eq!(waittid(tid), 0);
+    println!("cpu limit test passed!");
+    0
+}
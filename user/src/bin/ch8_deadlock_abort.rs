@@ -0,0 +1,63 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use core::ptr::{read_volatile, write_volatile};
+use user_lib::{exit, mutex_blocking_create, mutex_lock, mutex_unlock, set_deadlock_checks};
+use user_lib::{thread_blocked_on, thread_create, waittid, yield_, BLOCKED_ON_MUTEX};
+use user_lib::{DEADLOCK_ABORT, DEADLOCK_DETECT, DEADLOCK_MUTEX};
+
+// 理想结果：两个线程交叉加锁形成死锁时，发出请求的线程以死锁返回值退出并释放持有的锁，
+// 另一个线程随后拿到锁并正常结束
+
+static mut MUTEX_A: usize = 0;
+static mut MUTEX_B: usize = 0;
+static mut HOLDS_B: bool = false;
+static mut WAITER: usize = 0;
+
+unsafe fn first() -> ! {
+    assert_eq!(mutex_lock(MUTEX_A), 0);
+    while !read_volatile(&HOLDS_B) {
+        yield_();
+    }
+    // blocks until the victim is gone
+    assert_eq!(mutex_lock(MUTEX_B), 0);
+    mutex_unlock(MUTEX_B);
+    mutex_unlock(MUTEX_A);
+    exit(0)
+}
+
+unsafe fn victim() -> ! {
+    assert_eq!(mutex_lock(MUTEX_B), 0);
+    write_volatile(&mut HOLDS_B, true);
+    while thread_blocked_on(read_volatile(&WAITER)) != BLOCKED_ON_MUTEX | MUTEX_B as isize {
+        yield_();
+    }
+    mutex_lock(MUTEX_A);
+    unreachable!("a deadlock victim does not come back from its request");
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    assert_eq!(set_deadlock_checks(DEADLOCK_DETECT | DEADLOCK_ABORT), 0);
+    unsafe {
+        MUTEX_A = mutex_blocking_create() as usize;
+        MUTEX_B = mutex_blocking_create() as usize;
+        WAITER = thread_create(first as usize, 0) as usize;
+    }
+    let victim = thread_create(victim as usize, 0) as usize;
+    assert_eq!(waittid(victim), DEADLOCK_MUTEX);
+    assert_eq!(waittid(unsafe { WAITER }), 0);
+
+    // nothing is left held
+    unsafe {
+        assert_eq!(mutex_lock(MUTEX_A), 0);
+        assert_eq!(mutex_lock(MUTEX_B), 0);
+        mutex_unlock(MUTEX_B);
+        mutex_unlock(MUTEX_A);
+    }
+    println!("deadlock abort test passed!");
+    0
+}
@@ -0,0 +1,33 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{mutex_blocking_create, mutex_lock, mutex_unlock, semaphore_create};
+use user_lib::{semaphore_down, semaphore_up, set_deadlock_checks};
+use user_lib::{DEADLOCK_CODES, DEADLOCK_DETECT, DEADLOCK_MUTEX, DEADLOCK_SEMAPHORE};
+
+// 理想结果：默认都返回 -0xdead，开启 DEADLOCK_CODES 后信号量死锁返回 -0xdeae
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let mutex = mutex_blocking_create() as usize;
+    let sem = semaphore_create(1) as usize;
+
+    set_deadlock_checks(DEADLOCK_DETECT);
+    assert_eq!(semaphore_down(sem), 0);
+    assert_eq!(semaphore_down(sem), DEADLOCK_MUTEX);
+    semaphore_up(sem);
+
+    set_deadlock_checks(DEADLOCK_DETECT | DEADLOCK_CODES);
+    assert_eq!(semaphore_down(sem), 0);
+    assert_eq!(semaphore_down(sem), DEADLOCK_SEMAPHORE);
+    semaphore_up(sem);
+    assert_eq!(mutex_lock(mutex), 0);
+    assert_eq!(mutex_lock(mutex), DEADLOCK_MUTEX);
+    mutex_unlock(mutex);
+    set_deadlock_checks(0);
+    println!("deadlock codes test passed!");
+    0
+}
@@ -0,0 +1,55 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{enable_deadlock_detect, exit, mutex_blocking_create, mutex_lock, mutex_unlock};
+use user_lib::{semaphore_create, semaphore_down, sync_checkpoint, sync_restore};
+use user_lib::{thread_create, waittid};
+
+// 理想结果：死锁检测发现记账信息自相矛盾时返回 -ENOTRECOVERABLE，而不是给出错误结论或死循环
+
+const ENOTRECOVERABLE: isize = 131;
+
+fn short_lived() -> ! {
+    exit(0)
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let mutex = mutex_blocking_create() as usize;
+    let held = semaphore_create(1) as usize;
+    let wanted = semaphore_create(1) as usize;
+    let exited = thread_create(short_lived as usize, 0) as usize;
+    waittid(exited);
+    assert_eq!(enable_deadlock_detect(true), 0);
+    assert_eq!(semaphore_down(held), 0);
+
+    let mut saved = [0usize; 64];
+    let n = sync_checkpoint(&mut saved) as usize;
+    let (mutex_n, thread_n) = (saved[0], saved[2]);
+    let mutex_alloc = 3;
+    let sem_avail = mutex_alloc + mutex_n + thread_n;
+
+    // an exited thread still owning the mutex
+    let mut corrupt = saved;
+    corrupt[mutex_alloc + mutex] = exited + 1;
+    assert_eq!(sync_restore(&corrupt[..n]), 0);
+    assert_eq!(mutex_lock(mutex), -ENOTRECOVERABLE);
+    assert_eq!(sync_restore(&saved[..n]), 0);
+    assert_eq!(mutex_lock(mutex), 0);
+    mutex_unlock(mutex);
+
+    // releasing what the caller holds would overflow the available count
+    let mut saved = [0usize; 64];
+    assert_eq!(sync_checkpoint(&mut saved) as usize, n);
+    let mut corrupt = saved;
+    corrupt[sem_avail + held] = usize::MAX;
+    assert_eq!(sync_restore(&corrupt[..n]), 0);
+    assert_eq!(semaphore_down(wanted), -ENOTRECOVERABLE);
+    assert_eq!(sync_restore(&saved[..n]), 0);
+    assert_eq!(semaphore_down(wanted), 0);
+    println!("deadlock corrupt test passed!");
+    0
+}
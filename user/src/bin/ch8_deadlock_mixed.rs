@@ -0,0 +1,54 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+extern crate alloc;
+
+use user_lib::{
+    enable_deadlock_detect, exit, mutex_blocking_create, mutex_lock, mutex_unlock,
+    semaphore_create, semaphore_down, semaphore_up, sleep_blocking,
+};
+use user_lib::{thread_create, waittid};
+
+// 线程 A 持有互斥锁并等待信号量，线程 B 持有信号量并等待互斥锁
+// 理想结果：B 加锁时检测到跨越互斥锁与信号量的死锁
+
+const MUTEX: usize = 0;
+const SEM: usize = 0;
+
+fn thread_a() -> ! {
+    assert_eq!(mutex_lock(MUTEX), 0);
+    sleep_blocking(50);
+    assert_eq!(semaphore_down(SEM), 0);
+    semaphore_up(SEM);
+    mutex_unlock(MUTEX);
+    exit(0)
+}
+
+fn thread_b() -> ! {
+    assert_eq!(semaphore_down(SEM), 0);
+    sleep_blocking(100);
+    let ret = mutex_lock(MUTEX);
+    semaphore_up(SEM);
+    if ret == -0xdead {
+        exit(0)
+    }
+    if ret == 0 {
+        mutex_unlock(MUTEX);
+    }
+    exit(1)
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    enable_deadlock_detect(true);
+    assert_eq!(mutex_blocking_create() as usize, MUTEX);
+    assert_eq!(semaphore_create(1) as usize, SEM);
+    let a = thread_create(thread_a as usize, 0);
+    let b = thread_create(thread_b as usize, 0);
+    assert_eq!(waittid(a as usize), 0);
+    assert_eq!(waittid(b as usize), 0);
+    println!("deadlock test mixed OK!");
+    0
+}
@@ -0,0 +1,64 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{deadlock_stats, enable_deadlock_detect, exit, mutex_blocking_create, mutex_lock};
+use user_lib::{mutex_unlock, thread_blocked_on, thread_create, thread_set_waitreason, waittid};
+use user_lib::{yield_, DeadlockStats, BLOCKED_ON_MUTEX};
+
+// 理想结果：线程 2 加锁 A 时发现死锁，内核输出
+//  ----- deadlock! pid: <pid>, tid: 2, mutex_id: 0 ------
+//     tid: 1, waiting for B
+//     tid: 2, waiting for A
+// 即死锁中每个线程各自的等待原因，随后两个线程都能正常结束
+
+const EDEADMUTEX: isize = 0xdead;
+
+static mut A: usize = 0;
+static mut B: usize = 0;
+static mut FIRST: usize = 0;
+
+unsafe fn first() -> ! {
+    assert_eq!(mutex_lock(A), 0);
+    assert_eq!(thread_set_waitreason("waiting for B"), 0);
+    // blocks until the second thread gives up
+    assert_eq!(mutex_lock(B), 0);
+    mutex_unlock(B);
+    mutex_unlock(A);
+    exit(0)
+}
+
+unsafe fn second() -> ! {
+    assert_eq!(mutex_lock(B), 0);
+    while thread_blocked_on(FIRST) != BLOCKED_ON_MUTEX | B as isize {
+        yield_();
+    }
+    assert_eq!(thread_set_waitreason("waiting for A"), 0);
+    assert_eq!(mutex_lock(A), -EDEADMUTEX);
+    mutex_unlock(B);
+    exit(0)
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    unsafe {
+        A = mutex_blocking_create() as usize;
+        B = mutex_blocking_create() as usize;
+    }
+    enable_deadlock_detect(true);
+    let first = thread_create(first as usize, 0) as usize;
+    unsafe {
+        FIRST = first;
+    }
+    let second = thread_create(second as usize, 0) as usize;
+    assert_eq!(waittid(second), 0);
+    assert_eq!(waittid(first), 0);
+    let mut stats = DeadlockStats::default();
+    deadlock_stats(&mut stats);
+    enable_deadlock_detect(false);
+    assert_eq!(stats.deadlocks, 1);
+    println!("deadlock reasons test passed!");
+    0
+}
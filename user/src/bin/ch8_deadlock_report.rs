@@ -0,0 +1,53 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{deadlock_stats, enable_deadlock_detect, mutex_blocking_create, mutex_lock};
+use user_lib::{mutex_unlock, semaphore_create, semaphore_down, semaphore_up, DeadlockStats};
+
+// 理想结果：重复的同一死锁只打印第一次（之后每 DEADLOCK_REPORT_EVERY 次打印一行汇总），其余计入 reports_suppressed，不同的死锁重新打印
+
+const EDEADMUTEX: isize = 0xdead;
+const DEADLOCK_REPORT_EVERY: usize = 16;
+const RETRIES: usize = 2 * DEADLOCK_REPORT_EVERY + 3;
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let mutex = mutex_blocking_create() as usize;
+    let sem = semaphore_create(1) as usize;
+    let mut stats = DeadlockStats::default();
+    enable_deadlock_detect(true);
+    assert_eq!(mutex_lock(mutex), 0);
+    assert_eq!(semaphore_down(sem), 0);
+
+    // a retry loop hitting the same deadlock, only the first one is new
+    for _ in 0..RETRIES {
+        assert_eq!(mutex_lock(mutex), -EDEADMUTEX);
+    }
+    deadlock_stats(&mut stats);
+    assert_eq!(stats.deadlocks, RETRIES);
+    assert_eq!(stats.reports_suppressed, RETRIES - 1);
+
+    // another resource is another report, and so is the first one again
+    assert_eq!(semaphore_down(sem), -EDEADMUTEX);
+    assert_eq!(mutex_lock(mutex), -EDEADMUTEX);
+    assert_eq!(mutex_lock(mutex), -EDEADMUTEX);
+    deadlock_stats(&mut stats);
+    assert_eq!(stats.deadlocks, RETRIES + 3);
+    assert_eq!(stats.reports_suppressed, RETRIES);
+
+    // turning detection on again forgets the last report
+    enable_deadlock_detect(false);
+    enable_deadlock_detect(true);
+    assert_eq!(mutex_lock(mutex), -EDEADMUTEX);
+    deadlock_stats(&mut stats);
+    assert_eq!((stats.deadlocks, stats.reports_suppressed), (1, 0));
+
+    semaphore_up(sem);
+    mutex_unlock(mutex);
+    enable_deadlock_detect(false);
+    println!("deadlock report test passed!");
+    0
+}
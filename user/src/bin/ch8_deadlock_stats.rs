@@ -0,0 +1,49 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{deadlock_stats, enable_deadlock_detect, mutex_blocking_create, mutex_lock};
+use user_lib::{mutex_unlock, semaphore_create, semaphore_down, semaphore_up, DeadlockStats};
+
+// 理想结果：统计检测次数、死锁次数和访问的节点数，重新开启检测后清零
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let mutex = mutex_blocking_create() as usize;
+    let sem = semaphore_create(1) as usize;
+    let mut stats = DeadlockStats::default();
+
+    // no detection, nothing counted
+    mutex_lock(mutex);
+    mutex_unlock(mutex);
+    deadlock_stats(&mut stats);
+    assert_eq!(
+        (stats.runs, stats.deadlocks, stats.nodes_visited),
+        (0, 0, 0)
+    );
+
+    enable_deadlock_detect(true);
+    assert_eq!(mutex_lock(mutex), 0);
+    assert_eq!(semaphore_down(sem), 0);
+    deadlock_stats(&mut stats);
+    assert_eq!((stats.runs, stats.deadlocks), (2, 0));
+    assert!(stats.nodes_visited >= 2);
+    assert_eq!(mutex_lock(mutex), -0xdead);
+    deadlock_stats(&mut stats);
+    assert_eq!((stats.runs, stats.deadlocks), (3, 1));
+    semaphore_up(sem);
+    mutex_unlock(mutex);
+
+    enable_deadlock_detect(false);
+    enable_deadlock_detect(true);
+    deadlock_stats(&mut stats);
+    assert_eq!(
+        (stats.runs, stats.deadlocks, stats.nodes_visited),
+        (0, 0, 0)
+    );
+    enable_deadlock_detect(false);
+    println!("deadlock stats test passed!");
+    0
+}
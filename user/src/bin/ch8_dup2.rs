@@ -0,0 +1,39 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{close, dup, dup2, lseek, open, read, write, OpenFlags, SEEK_SET};
+
+// 理想结果：复制出的文件描述符共享同一个文件及读写位置
+
+const EBADF: isize = 9;
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let fd = open("dup2\0", OpenFlags::CREATE | OpenFlags::RDWR);
+    assert!(fd > 0);
+    let fd = fd as usize;
+    assert_eq!(dup2(fd, 10), 10);
+    let copy = dup(fd);
+    assert!(copy > 0);
+    let copy = copy as usize;
+
+    write(fd, b"ab");
+    write(10, b"cd");
+    write(copy, b"ef");
+    assert_eq!(lseek(10, 0, SEEK_SET), 0);
+    close(fd);
+    close(copy);
+    let mut buffer = [0u8; 16];
+    let read_len = read(10, &mut buffer) as usize;
+    assert_eq!(&buffer[..read_len], b"abcdef");
+    close(10);
+
+    assert_eq!(dup(fd), -EBADF);
+    assert_eq!(dup2(fd, 11), -EBADF);
+    assert_eq!(dup2(1, 100000), -EBADF);
+    println!("dup2 test passed!");
+    0
+}
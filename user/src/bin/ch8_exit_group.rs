@@ -0,0 +1,73 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{exit, exit_group, fork, get_time, mutex_blocking_create, mutex_lock};
+use user_lib::{semaphore_create, semaphore_down, sleep_blocking, thread_create, waitpid};
+use user_lib::{waittid, yield_};
+
+// 理想结果：任一线程调用 exit_group 后，阻塞在锁、信号量、睡眠中的线程和忙等的线程全部结束，父进程得到指定的退出码
+
+const EXIT_CODE: i32 = 42;
+
+static mut MUTEX: usize = 0;
+static mut SEM: usize = 0;
+
+unsafe fn lock_waiter() -> ! {
+    mutex_lock(MUTEX);
+    exit(1)
+}
+
+unsafe fn sem_waiter() -> ! {
+    semaphore_down(SEM);
+    exit(1)
+}
+
+fn sleeper() -> ! {
+    sleep_blocking(100_000);
+    exit(1)
+}
+
+fn spinner() -> ! {
+    loop {
+        yield_();
+    }
+}
+
+fn terminator() -> ! {
+    sleep_blocking(20);
+    exit_group(EXIT_CODE)
+}
+
+fn child() -> ! {
+    unsafe {
+        MUTEX = mutex_blocking_create() as usize;
+        SEM = semaphore_create(0) as usize;
+        mutex_lock(MUTEX);
+    }
+    thread_create(lock_waiter as usize, 0);
+    thread_create(sem_waiter as usize, 0);
+    thread_create(sleeper as usize, 0);
+    thread_create(spinner as usize, 0);
+    let tid = thread_create(terminator as usize, 0) as usize;
+    // the main thread is waiting too when the group exits
+    waittid(tid);
+    exit(1)
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let start = get_time();
+    let pid = fork();
+    if pid == 0 {
+        child();
+    }
+    let mut exit_code = 0;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, EXIT_CODE);
+    assert!(get_time() - start < 10_000);
+    println!("exit group test passed!");
+    0
+}
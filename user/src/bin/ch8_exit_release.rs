@@ -0,0 +1,45 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{
+    exit, mutex_blocking_create, mutex_lock, mutex_unlock, semaphore_create, semaphore_down,
+    semaphore_up, sleep_blocking,
+};
+use user_lib::{thread_create, waittid};
+
+// 线程持有互斥锁与信号量后直接退出
+// 理想结果：等待者在持有者退出后获得资源，不会永久阻塞
+
+const MUTEX: usize = 0;
+const SEM: usize = 0;
+
+fn holder() -> ! {
+    assert_eq!(mutex_lock(MUTEX), 0);
+    assert_eq!(semaphore_down(SEM), 0);
+    sleep_blocking(100);
+    exit(0)
+}
+
+fn waiter() -> ! {
+    sleep_blocking(20);
+    assert_eq!(mutex_lock(MUTEX), 0);
+    mutex_unlock(MUTEX);
+    assert_eq!(semaphore_down(SEM), 0);
+    semaphore_up(SEM);
+    exit(0)
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    assert_eq!(mutex_blocking_create() as usize, MUTEX);
+    assert_eq!(semaphore_create(1) as usize, SEM);
+    let holder_tid = thread_create(holder as usize, 0);
+    let waiter_tid = thread_create(waiter as usize, 0);
+    assert_eq!(waittid(holder_tid as usize), 0);
+    assert_eq!(waittid(waiter_tid as usize), 0);
+    println!("exit release test passed!");
+    0
+}
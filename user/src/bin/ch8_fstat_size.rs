@@ -0,0 +1,43 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{close, fstat, open, pipe, write, OpenFlags, Stat, StatMode};
+
+// 理想结果：普通文件报告写入的字节数，管道与控制台报告特殊类型且大小为 0
+
+const EBADF: isize = 9;
+const N: usize = 1000;
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let fd = open("fstat_size\0", OpenFlags::CREATE | OpenFlags::WRONLY);
+    assert!(fd > 0);
+    let fd = fd as usize;
+    let data = [b'x'; N];
+    assert_eq!(write(fd, &data), N as isize);
+    let stat = Stat::new();
+    assert_eq!(fstat(fd, &stat), 0);
+    assert_eq!(stat.mode, StatMode::FILE);
+    assert_eq!(stat.size, N as u64);
+    assert_eq!(stat.nlink, 1);
+    close(fd);
+
+    let mut pipe_fd = [0usize; 2];
+    pipe(&mut pipe_fd);
+    let stat = Stat::new();
+    assert_eq!(fstat(pipe_fd[0], &stat), 0);
+    assert_eq!(stat.mode, StatMode::FIFO);
+    assert_eq!(stat.size, 0);
+    close(pipe_fd[0]);
+    close(pipe_fd[1]);
+
+    let stat = Stat::new();
+    assert_eq!(fstat(1, &stat), 0);
+    assert_eq!(stat.mode, StatMode::CHR);
+    assert_eq!(fstat(100, &stat), -EBADF);
+    println!("fstat size test passed!");
+    0
+}
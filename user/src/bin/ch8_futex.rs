@@ -0,0 +1,83 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+extern crate alloc;
+
+use alloc::vec::Vec;
+use core::sync::atomic::{AtomicU32, Ordering};
+use user_lib::{exit, futex_wait, futex_wake, sys_futex_wait, thread_create, waittid};
+
+// 理想结果：基于 futex 的用户态互斥锁保护计数器，值不符时等待立即返回；
+// 未映射或用户态不可访问（如 TrapContext）的地址返回 -EFAULT
+
+const EAGAIN: isize = 11;
+const EFAULT: isize = 14;
+const PER_THREAD: usize = 1000;
+const THREAD_COUNT: usize = 8;
+const UNMAPPED: usize = 0x1000_0000;
+const PAGE_SIZE: usize = 0x1000;
+/// Trap context of the main thread, mapped without `U` just below the trampoline
+const TRAP_CONTEXT: usize = usize::MAX - 2 * PAGE_SIZE + 1;
+
+const UNLOCKED: u32 = 0;
+const LOCKED: u32 = 1;
+const CONTENDED: u32 = 2;
+
+static LOCK: AtomicU32 = AtomicU32::new(UNLOCKED);
+static mut A: usize = 0;
+
+fn lock() {
+    if LOCK
+        .compare_exchange(UNLOCKED, LOCKED, Ordering::Acquire, Ordering::Relaxed)
+        .is_ok()
+    {
+        return;
+    }
+    // only trap into the kernel under contention
+    while LOCK.swap(CONTENDED, Ordering::Acquire) != UNLOCKED {
+        futex_wait(&LOCK, CONTENDED);
+    }
+}
+
+fn unlock() {
+    if LOCK.swap(UNLOCKED, Ordering::Release) == CONTENDED {
+        futex_wake(&LOCK, 1);
+    }
+}
+
+unsafe fn f() -> ! {
+    let mut t = 2usize;
+    for _ in 0..PER_THREAD {
+        lock();
+        let a = &mut A as *mut usize;
+        let cur = a.read_volatile();
+        for _ in 0..500 {
+            t = t * t % 10007;
+        }
+        a.write_volatile(cur + 1);
+        unlock();
+    }
+    exit(t as i32)
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let word = AtomicU32::new(7);
+    assert_eq!(futex_wait(&word, 8), -EAGAIN);
+    assert_eq!(futex_wake(&word, 1), 0);
+    assert_eq!(sys_futex_wait(UNMAPPED, 0), -EFAULT);
+    assert_eq!(sys_futex_wait(TRAP_CONTEXT, 0), -EFAULT);
+
+    let mut v = Vec::new();
+    for _ in 0..THREAD_COUNT {
+        v.push(thread_create(f as usize, 0) as usize);
+    }
+    for tid in v.into_iter() {
+        waittid(tid);
+    }
+    assert_eq!(unsafe { A }, PER_THREAD * THREAD_COUNT);
+    println!("futex test passed!");
+    0
+}
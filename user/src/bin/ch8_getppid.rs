@@ -0,0 +1,59 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{close, fork, getpid, getppid, pipe, read, sleep_blocking, waitpid, write};
+
+// 理想结果：父进程退出前孤儿进程的父进程是它，退出后被 initproc（pid 为 0）收养；
+// 没有父进程时返回 -1，不会与 initproc 混淆
+
+const INITPROC_PID: isize = 0;
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let mut pipe_fd = [0usize; 2];
+    pipe(&mut pipe_fd);
+    // the grandchild tells its parent it has seen it alive
+    let mut ready_fd = [0usize; 2];
+    pipe(&mut ready_fd);
+    let pid = getpid();
+    let child = fork();
+    if child == 0 {
+        assert_eq!(getppid(), pid);
+        let parent = getpid();
+        if fork() == 0 {
+            // orphan-to-be, wait until its parent is gone
+            close(pipe_fd[0]);
+            close(ready_fd[0]);
+            let before = getppid();
+            write(ready_fd[1], &[0]);
+            close(ready_fd[1]);
+            assert_eq!(before, parent);
+            while getppid() == parent {
+                sleep_blocking(10);
+            }
+            let ppid = getppid();
+            write(pipe_fd[1], &ppid.to_ne_bytes());
+            close(pipe_fd[1]);
+            return 0;
+        }
+        // exit as soon as the grandchild saw us, leaving it behind
+        close(ready_fd[1]);
+        let mut byte = [0u8; 1];
+        read(ready_fd[0], &mut byte);
+        return 0;
+    }
+    close(pipe_fd[1]);
+    close(ready_fd[0]);
+    close(ready_fd[1]);
+    let mut exit_code: i32 = 0;
+    assert_eq!(waitpid(child as usize, &mut exit_code), child);
+    let mut buffer = [0u8; core::mem::size_of::<isize>()];
+    assert_eq!(read(pipe_fd[0], &mut buffer), buffer.len() as isize);
+    close(pipe_fd[0]);
+    assert_eq!(isize::from_ne_bytes(buffer), INITPROC_PID);
+    println!("getppid test passed!");
+    0
+}
@@ -0,0 +1,45 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{get_time, getrusage, sleep_blocking, thread_create, yield_, RUsage};
+
+// 理想结果：忙等增加 CPU 时间和被抢占次数，睡眠增加主动切换次数，线程访问非法地址增加缺页次数
+
+fn faulty() -> ! {
+    unsafe {
+        (0 as *mut usize).write_volatile(1);
+    }
+    unreachable!()
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let mut before = RUsage::default();
+    assert_eq!(getrusage(&mut before), 0);
+    assert!(before.max_frames > 0);
+
+    let start = get_time();
+    while get_time() - start < 50 {}
+    sleep_blocking(10);
+    thread_create(faulty as usize, 0);
+    // its exit code -2 cannot be told apart from "still running" by waittid
+    let mut after = RUsage::default();
+    loop {
+        getrusage(&mut after);
+        if after.page_faults > before.page_faults {
+            break;
+        }
+        yield_();
+    }
+    assert!(after.utime_us - before.utime_us >= 40_000);
+    assert!(after.nivcsw > before.nivcsw);
+    assert!(after.nvcsw > before.nvcsw);
+    assert_eq!(after.page_faults, before.page_faults + 1);
+    // the thread's stack and trap context came on top of what was there
+    assert!(after.max_frames > before.max_frames);
+    println!("getrusage test passed!");
+    0
+}
@@ -0,0 +1,43 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{
+    mutex_blocking_create, mutex_lock, mutex_set_rank, mutex_unlock, set_deadlock_checks,
+    LOCK_ORDER_CHECK,
+};
+
+// 理想结果：按等级从低到高加锁成功，持有高等级锁时加低等级锁被拒绝
+
+const EDEADLK: isize = 35;
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let low = mutex_blocking_create() as usize;
+    let high = mutex_blocking_create() as usize;
+    let unranked = mutex_blocking_create() as usize;
+    assert_eq!(mutex_set_rank(low, 1), 0);
+    assert_eq!(mutex_set_rank(high, 2), 0);
+    assert_eq!(mutex_set_rank(100, 1), -1);
+    assert_eq!(set_deadlock_checks(LOCK_ORDER_CHECK), 0);
+
+    assert_eq!(mutex_lock(low), 0);
+    assert_eq!(mutex_lock(high), 0);
+    mutex_unlock(high);
+    mutex_unlock(low);
+
+    assert_eq!(mutex_lock(high), 0);
+    assert_eq!(mutex_lock(low), -EDEADLK);
+    assert_eq!(mutex_lock(unranked), 0);
+    mutex_unlock(unranked);
+    mutex_unlock(high);
+
+    // nothing is held any more, so the low rank is fine again
+    assert_eq!(mutex_lock(low), 0);
+    mutex_unlock(low);
+    assert_eq!(set_deadlock_checks(4), -1);
+    println!("lock order test passed!");
+    0
+}
@@ -0,0 +1,56 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{
+    deadlock_stats, exit, mutex_blocking_create, mutex_lock, mutex_unlock, set_deadlock_checks,
+    thread_create, waittid, DeadlockStats, LOCKDEP,
+};
+
+// 理想结果：两个线程先后以相反顺序加锁，虽未死锁，内核输出一次 lock order warning
+
+static mut A: usize = 0;
+static mut B: usize = 0;
+
+unsafe fn lock_both(first: usize, second: usize) {
+    assert_eq!(mutex_lock(first), 0);
+    assert_eq!(mutex_lock(second), 0);
+    mutex_unlock(second);
+    mutex_unlock(first);
+}
+
+unsafe fn reversed() -> ! {
+    lock_both(B, A);
+    // seen once already, no second warning
+    lock_both(B, A);
+    exit(0)
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    assert_eq!(set_deadlock_checks(8), -1);
+    assert_eq!(set_deadlock_checks(LOCKDEP), 0);
+    unsafe {
+        A = mutex_blocking_create() as usize;
+        B = mutex_blocking_create() as usize;
+        lock_both(A, B);
+    }
+    let mut stats = DeadlockStats::default();
+    deadlock_stats(&mut stats);
+    assert_eq!(stats.lockdep_warnings, 0);
+    // run one after the other, the latent deadlock never happens
+    let tid = thread_create(reversed as usize, 0) as usize;
+    assert_eq!(waittid(tid), 0);
+    deadlock_stats(&mut stats);
+    assert_eq!(stats.lockdep_warnings, 1);
+    // the same order again is no news
+    unsafe {
+        lock_both(A, B);
+    }
+    deadlock_stats(&mut stats);
+    assert_eq!(stats.lockdep_warnings, 1);
+    println!("lockdep test passed!");
+    0
+}
@@ -0,0 +1,49 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{close, lseek, open, pipe, read, write, OpenFlags, SEEK_CUR, SEEK_END, SEEK_SET};
+
+// 理想结果：移动文件读写位置后读到相同的内容，管道不可移动；
+// 结果为负或溢出时返回 -EINVAL 且位置不变
+
+const EINVAL: isize = 22;
+const ESPIPE: isize = 29;
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let test_str = "Hello, world!";
+    let fd = open("lseek\0", OpenFlags::CREATE | OpenFlags::RDWR);
+    assert!(fd > 0);
+    let fd = fd as usize;
+    write(fd, test_str.as_bytes());
+
+    let mut buffer = [0u8; 100];
+    assert_eq!(lseek(fd, 0, SEEK_SET), 0);
+    let read_len = read(fd, &mut buffer) as usize;
+    assert_eq!(test_str, core::str::from_utf8(&buffer[..read_len]).unwrap());
+
+    assert_eq!(lseek(fd, -6, SEEK_END), 7);
+    let read_len = read(fd, &mut buffer) as usize;
+    assert_eq!("world!", core::str::from_utf8(&buffer[..read_len]).unwrap());
+
+    assert_eq!(lseek(fd, -13, SEEK_CUR), 0);
+    assert_eq!(lseek(fd, -1, SEEK_CUR), -EINVAL);
+    assert_eq!(lseek(fd, 1, SEEK_SET), 1);
+    assert_eq!(lseek(fd, isize::MAX, SEEK_CUR), -EINVAL);
+    assert_eq!(lseek(fd, isize::MAX, SEEK_END), -EINVAL);
+    assert_eq!(lseek(fd, 0, SEEK_CUR), 1);
+    assert_eq!(lseek(fd, 0, 3), -EINVAL);
+    close(fd);
+
+    let mut pipe_fd = [0usize; 2];
+    pipe(&mut pipe_fd);
+    assert_eq!(lseek(pipe_fd[0], 0, SEEK_SET), -ESPIPE);
+    assert_eq!(lseek(1, 0, SEEK_SET), -ESPIPE);
+    close(pipe_fd[0]);
+    close(pipe_fd[1]);
+    println!("lseek test passed!");
+    0
+}
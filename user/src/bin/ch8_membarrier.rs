@@ -0,0 +1,48 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use core::ptr::{read_volatile, write_volatile};
+use user_lib::{exit, membarrier, thread_create, waittid, yield_, MEMBARRIER_GLOBAL};
+
+// 理想结果：本地和全局屏障都返回 0，未知标志返回 -EINVAL；
+// 写者在屏障后发布标志，读者看到标志时一定能读到之前写入的数据
+
+const EINVAL: isize = 22;
+const ROUNDS: usize = 20;
+
+static mut DATA: usize = 0;
+static mut READY: bool = false;
+
+unsafe fn writer(value: usize) -> ! {
+    write_volatile(&mut DATA, value);
+    assert_eq!(membarrier(0), 0);
+    write_volatile(&mut READY, true);
+    exit(0)
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    assert_eq!(membarrier(0), 0);
+    assert_eq!(membarrier(MEMBARRIER_GLOBAL), 0);
+    assert_eq!(membarrier(2), -EINVAL);
+    assert_eq!(membarrier(MEMBARRIER_GLOBAL | 4), -EINVAL);
+
+    for round in 1..=ROUNDS {
+        unsafe {
+            write_volatile(&mut READY, false);
+            assert_eq!(membarrier(MEMBARRIER_GLOBAL), 0);
+            let thread = thread_create(writer as usize, round) as usize;
+            while !read_volatile(&READY) {
+                yield_();
+            }
+            assert_eq!(membarrier(0), 0);
+            assert_eq!(read_volatile(&DATA), round);
+            waittid(thread);
+        }
+    }
+    println!("membarrier test passed!");
+    0
+}
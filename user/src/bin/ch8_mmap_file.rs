@@ -0,0 +1,62 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{close, mmap_file, open, write, OpenFlags};
+
+// 理想结果：映射区域内容与文件一致，文件末尾之后为 0，非法参数返回对应错误码
+
+const EBADF: isize = 9;
+const EACCES: isize = 13;
+const EINVAL: isize = 22;
+const PAGE: usize = 4096;
+/// End of the user half of the Sv39 address space
+const USER_END: usize = 1 << 38;
+const FILE_LEN: usize = PAGE + 100;
+const PROT_R: usize = 1;
+const PROT_W: usize = 2;
+
+fn pattern(i: usize) -> u8 {
+    (i % 251) as u8
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let fname = "mmapfile\0";
+    let fd = open(fname, OpenFlags::CREATE | OpenFlags::WRONLY);
+    assert!(fd > 0);
+    let mut data = [0u8; FILE_LEN];
+    for (i, byte) in data.iter_mut().enumerate() {
+        *byte = pattern(i);
+    }
+    write(fd as usize, &data);
+    assert_eq!(mmap_file(0x10000000, PAGE, PROT_R, fd as usize, 0), -EACCES);
+    close(fd as usize);
+
+    let fd = open(fname, OpenFlags::RDONLY) as usize;
+    let start = 0x10000000;
+    assert_eq!(mmap_file(start, 2 * PAGE, PROT_R, fd, 0), 0);
+    for i in 0..2 * PAGE {
+        let byte = unsafe { *((start + i) as *const u8) };
+        assert_eq!(byte, if i < FILE_LEN { pattern(i) } else { 0 });
+    }
+    let second = 0x10010000;
+    assert_eq!(mmap_file(second, PAGE, PROT_R, fd, PAGE), 0);
+    for i in 0..100 {
+        let byte = unsafe { *((second + i) as *const u8) };
+        assert_eq!(byte, pattern(PAGE + i));
+    }
+
+    assert_eq!(mmap_file(0x10020000, PAGE, PROT_R, fd + 10, 0), -EBADF);
+    assert_eq!(mmap_file(0x10020000, PAGE, PROT_R, fd, 3 * PAGE), -EINVAL);
+    assert_eq!(mmap_file(0x10020000, PAGE, PROT_R | PROT_W, fd, 0), -EINVAL);
+    assert_eq!(mmap_file(start + PAGE, PAGE, PROT_R, fd, 0), -EINVAL);
+    // too long to copy in, or reaching past the user half of the address space
+    assert_eq!(mmap_file(0x10020000, 1 << 30, PROT_R, fd, 0), -EINVAL);
+    assert_eq!(mmap_file(USER_END - PAGE, 2 * PAGE, PROT_R, fd, 0), -EINVAL);
+    close(fd);
+    println!("mmap file test passed!");
+    0
+}
@@ -0,0 +1,88 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use core::mem::size_of;
+use core::ptr::{read_volatile, write_volatile};
+use user_lib::{exit, mq_create, mq_destroy, mq_recv_value, mq_send, mq_send_value};
+use user_lib::{thread_create, waittid, yield_, SYNC_ID_GEN_SHIFT};
+
+// 理想结果：用整数消息队列完成生产者/消费者，满时发送阻塞、空时接收阻塞且顺序不变；
+// 有线程收发时销毁失败，销毁后旧 id 失效，重新创建复用槽位但 id 不同；
+// 整数收发接口用在消息长度不同的队列上返回 -EINVAL
+
+const EINVAL: isize = 22;
+const CAPACITY: usize = 4;
+const VALUES: usize = 32;
+
+static mut MQ: usize = 0;
+static mut RECEIVED: bool = false;
+
+unsafe fn producer() -> ! {
+    for i in 0..VALUES {
+        assert_eq!(mq_send_value(MQ, i * i), 0);
+    }
+    exit(0)
+}
+
+unsafe fn receiver() -> ! {
+    let mut value = 0;
+    assert_eq!(mq_recv_value(MQ, &mut value), 0);
+    write_volatile(&mut RECEIVED, true);
+    exit(value as i32)
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let mq = mq_create(CAPACITY, size_of::<usize>()) as usize;
+    unsafe {
+        MQ = mq;
+    }
+    let thread = thread_create(producer as usize, 0) as usize;
+    let mut value = 0;
+    for i in 0..VALUES {
+        assert_eq!(mq_recv_value(mq, &mut value), 0);
+        assert_eq!(value, i * i);
+    }
+    waittid(thread);
+
+    // busy while a receiver waits on it
+    let thread = thread_create(receiver as usize, 0) as usize;
+    for _ in 0..10 {
+        yield_();
+    }
+    assert!(unsafe { !read_volatile(&RECEIVED) });
+    assert_eq!(mq_destroy(mq), -1);
+    assert_eq!(mq_send_value(mq, 7), 0);
+    assert_eq!(waittid(thread), 7);
+
+    // queued messages do not keep it alive
+    assert_eq!(mq_send_value(mq, 8), 0);
+    assert_eq!(mq_destroy(mq), 0);
+    assert_eq!(mq_destroy(mq), -1);
+    assert_eq!(mq_send_value(mq, 9), -1);
+    assert_eq!(mq_recv_value(mq, &mut value), -1);
+
+    let fresh = mq_create(CAPACITY, size_of::<usize>()) as usize;
+    assert_ne!(fresh, mq);
+    assert_eq!(fresh & ((1 << SYNC_ID_GEN_SHIFT) - 1), mq);
+    assert_eq!(mq_send_value(fresh, 10), 0);
+    assert_eq!(mq_recv_value(fresh, &mut value), 0);
+    assert_eq!(value, 10);
+    assert_eq!(mq_destroy(fresh), 0);
+
+    // the helpers only fit queues of usize messages
+    for &size in [size_of::<u32>(), 2 * size_of::<usize>()].iter() {
+        let other = mq_create(CAPACITY, size) as usize;
+        assert_eq!(mq_send_value(other, 11), -EINVAL);
+        assert_eq!(mq_send(other, &[0u8; 16][..size]), 0);
+        value = 12;
+        assert_eq!(mq_recv_value(other, &mut value), -EINVAL);
+        assert_eq!(value, 12);
+        assert_eq!(mq_destroy(other), 0);
+    }
+    println!("mq destroy test passed!");
+    0
+}
@@ -0,0 +1,88 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{exit, mq_create, mq_recv, mq_send, thread_create, waittid};
+
+// 理想结果：两个线程通过一对消息队列来回传递计数，每轮都收到对方加一后的值；
+// 队列满时发送者阻塞，接收顺序与发送顺序一致，参数无效时创建失败；
+// 缓冲区长度与消息长度不同返回 -EINVAL，未映射返回 -EFAULT，且都不收发消息
+
+const EFAULT: isize = 14;
+const EINVAL: isize = 22;
+const ROUNDS: u64 = 50;
+const BURST: u64 = 8;
+const MSG_SIZE: usize = 8;
+const UNMAPPED: usize = 0x1000_0000;
+
+static mut PING: usize = 0;
+static mut PONG: usize = 0;
+
+unsafe fn ponger() -> ! {
+    let mut msg = [0u8; MSG_SIZE];
+    for _ in 0..ROUNDS {
+        assert_eq!(mq_recv(PING, &mut msg), 0);
+        let n = u64::from_le_bytes(msg) + 1;
+        assert_eq!(mq_send(PONG, &n.to_le_bytes()), 0);
+    }
+    exit(0)
+}
+
+unsafe fn producer() -> ! {
+    // more than the queue holds, later sends wait for the receiver
+    for i in 0..BURST {
+        assert_eq!(mq_send(PING, &i.to_le_bytes()), 0);
+    }
+    exit(0)
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    assert_eq!(mq_create(0, MSG_SIZE), -EINVAL);
+    assert_eq!(mq_create(1, 0), -EINVAL);
+    let mut msg = [0u8; MSG_SIZE];
+    assert_eq!(mq_send(100, &msg), -1);
+    assert_eq!(mq_recv(100, &mut msg), -1);
+
+    unsafe {
+        PING = mq_create(1, MSG_SIZE) as usize;
+        PONG = mq_create(1, MSG_SIZE) as usize;
+    }
+    let thread = thread_create(ponger as usize, 0) as usize;
+    for i in 0..ROUNDS {
+        unsafe {
+            assert_eq!(mq_send(PING, &(i * 2).to_le_bytes()), 0);
+            assert_eq!(mq_recv(PONG, &mut msg), 0);
+        }
+        assert_eq!(u64::from_le_bytes(msg), i * 2 + 1);
+    }
+    assert_eq!(waittid(thread), 0);
+
+    unsafe {
+        PING = mq_create(BURST as usize / 2, MSG_SIZE) as usize;
+    }
+    let thread = thread_create(producer as usize, 0) as usize;
+    for i in 0..BURST {
+        unsafe {
+            assert_eq!(mq_recv(PING, &mut msg), 0);
+        }
+        assert_eq!(u64::from_le_bytes(msg), i);
+    }
+    assert_eq!(waittid(thread), 0);
+
+    // bad buffers are refused before a message is queued or taken
+    let mq = mq_create(1, MSG_SIZE) as usize;
+    let unmapped = unsafe { core::slice::from_raw_parts_mut(UNMAPPED as *mut u8, MSG_SIZE) };
+    assert_eq!(mq_send(mq, &msg[..MSG_SIZE - 1]), -EINVAL);
+    assert_eq!(mq_send(mq, &[0u8; MSG_SIZE + 1]), -EINVAL);
+    assert_eq!(mq_send(mq, unmapped), -EFAULT);
+    assert_eq!(mq_recv(mq, &mut msg[..MSG_SIZE - 1]), -EINVAL);
+    assert_eq!(mq_send(mq, &7u64.to_le_bytes()), 0);
+    assert_eq!(mq_recv(mq, unmapped), -EFAULT);
+    assert_eq!(mq_recv(mq, &mut msg), 0);
+    assert_eq!(u64::from_le_bytes(msg), 7);
+    println!("mq pingpong test passed!");
+    0
+}
@@ -0,0 +1,71 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+extern crate alloc;
+
+use alloc::vec::Vec;
+use user_lib::{exit, get_time, mutex_lock, mutex_unlock, sys_mutex_create};
+use user_lib::{thread_create, waittid, MUTEX_ADAPTIVE, MUTEX_BLOCKING, MUTEX_SPIN};
+
+// 比较三种互斥锁在短临界区与长临界区下的耗时
+// 理想结果：短临界区下自适应锁接近自旋锁，长临界区下接近阻塞锁，且计数正确
+
+const THREAD_N: usize = 4;
+const ROUNDS: usize = 200;
+
+static mut MUTEX_ID: usize = 0;
+static mut WORK: usize = 0;
+static mut COUNTER: usize = 0;
+
+fn busy(n: usize) {
+    let mut j = 0usize;
+    for i in 0..n {
+        j = j.wrapping_add(i);
+        unsafe { core::ptr::write_volatile(&mut j, j) };
+    }
+}
+
+unsafe fn worker() -> ! {
+    for _ in 0..ROUNDS {
+        mutex_lock(MUTEX_ID);
+        let c = COUNTER;
+        busy(WORK);
+        COUNTER = c + 1;
+        mutex_unlock(MUTEX_ID);
+    }
+    exit(0)
+}
+
+fn run(kind: usize, work: usize) -> isize {
+    unsafe {
+        MUTEX_ID = sys_mutex_create(kind) as usize;
+        WORK = work;
+        COUNTER = 0;
+    }
+    let start = get_time();
+    let threads: Vec<_> = (0..THREAD_N)
+        .map(|_| thread_create(worker as usize, 0))
+        .collect();
+    for thread in threads.iter() {
+        waittid(*thread as usize);
+    }
+    assert_eq!(unsafe { COUNTER }, THREAD_N * ROUNDS);
+    get_time() - start
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    for (name, work) in [("short", 10), ("long", 20000)] {
+        let spin = run(MUTEX_SPIN, work);
+        let blocking = run(MUTEX_BLOCKING, work);
+        let adaptive = run(MUTEX_ADAPTIVE, work);
+        println!(
+            "{} sections: spin = {}ms, blocking = {}ms, adaptive = {}ms",
+            name, spin, blocking, adaptive
+        );
+    }
+    println!("mutex bench test passed!");
+    0
+}
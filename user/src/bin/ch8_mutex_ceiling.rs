@@ -0,0 +1,80 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{exit, mutex_blocking_create, mutex_lock, mutex_set_ceiling, mutex_unlock};
+use user_lib::{sched_setpolicy, set_priority, thread_create, waittid, yield_};
+use user_lib::{SCHED_PRIORITY, SCHED_STRIDE};
+
+// 理想结果：低优先级线程持有天花板互斥锁时，中优先级线程无法抢占；解锁后恢复原优先级，中优先级线程随即运行
+
+// all above the default priority 16 of the other processes, which would
+// otherwise starve us under the priority policy
+const MAIN: isize = 18;
+const LOW: isize = 20;
+const MID: isize = 50;
+const CEILING: usize = 100;
+const MID_ROUNDS: usize = 5;
+
+static mut MUTEX: usize = 0;
+static mut COUNTER: usize = 0;
+// what the low thread saw, checked by main once the policy is restored
+static mut MID_RAN_WHILE_HELD: bool = false;
+static mut MID_ROUNDS_AFTER_UNLOCK: usize = 0;
+
+unsafe fn mid() -> ! {
+    set_priority(MID);
+    for _ in 0..MID_ROUNDS {
+        COUNTER += 1;
+        yield_();
+    }
+    exit(0)
+}
+
+unsafe fn low() -> ! {
+    set_priority(LOW);
+    mutex_lock(MUTEX);
+    // the mid thread becomes ready while we hold the mutex
+    let tid = thread_create(mid as usize, 0) as usize;
+    for _ in 0..10 {
+        yield_();
+        if COUNTER != 0 {
+            MID_RAN_WHILE_HELD = true;
+        }
+    }
+    mutex_unlock(MUTEX);
+    // back at the low priority, the mid thread runs to its end first
+    yield_();
+    MID_ROUNDS_AFTER_UNLOCK = COUNTER;
+    waittid(tid);
+    exit(0)
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let mutex = mutex_blocking_create() as usize;
+    assert_eq!(mutex_set_ceiling(mutex, 1), -1);
+    assert_eq!(mutex_set_ceiling(mutex + 1, CEILING), -1);
+    assert_eq!(mutex_set_ceiling(mutex, CEILING), 0);
+    unsafe {
+        MUTEX = mutex;
+    }
+    sched_setpolicy(SCHED_PRIORITY);
+    // below the test threads, which always run first
+    set_priority(MAIN);
+    let tid = thread_create(low as usize, 0) as usize;
+    let exit_code = waittid(tid);
+    // the policy is system-wide: restore it before any assert can fail
+    sched_setpolicy(SCHED_STRIDE);
+    set_priority(16);
+
+    assert_eq!(exit_code, 0);
+    unsafe {
+        assert!(!MID_RAN_WHILE_HELD);
+        assert_eq!(MID_ROUNDS_AFTER_UNLOCK, MID_ROUNDS);
+    }
+    println!("mutex ceiling test passed!");
+    0
+}
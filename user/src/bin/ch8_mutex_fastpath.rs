@@ -0,0 +1,68 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{enable_deadlock_detect, exit, get_time, mutex_blocking_create, mutex_lock};
+use user_lib::{mutex_unlock, thread_blocked_on, thread_create, waittid, yield_, BLOCKED_ON_MUTEX};
+
+// 理想结果：未开启死锁检测时无竞争加锁走快速路径，耗时少于开启检测时，竞争时等待者仍显示阻塞在该锁上；之后开启检测仍能发现死锁
+
+const ROUNDS: usize = 10000;
+const EDEADMUTEX: isize = 0xdead;
+
+static mut MUTEX: usize = 0;
+
+/// Time `ROUNDS` uncontended lock/unlock pairs of `mutex`
+fn time_pairs(mutex: usize) -> isize {
+    let start = get_time();
+    for _ in 0..ROUNDS {
+        assert_eq!(mutex_lock(mutex), 0);
+        mutex_unlock(mutex);
+    }
+    get_time() - start
+}
+
+unsafe fn contender() -> ! {
+    assert_eq!(mutex_lock(MUTEX), 0);
+    mutex_unlock(MUTEX);
+    exit(0)
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let mutex = mutex_blocking_create() as usize;
+    unsafe {
+        MUTEX = mutex;
+    }
+    let fast = time_pairs(mutex);
+    // the baseline: with detection on every lock takes the full path,
+    // recording its request and running the detector
+    enable_deadlock_detect(true);
+    let full = time_pairs(mutex);
+    enable_deadlock_detect(false);
+    println!(
+        "{} uncontended lock/unlock pairs: {}ms on the fast path, {}ms with detection on",
+        ROUNDS, fast, full
+    );
+
+    // a contended lock still records what the waiter waits for
+    assert_eq!(mutex_lock(mutex), 0);
+    let tid = thread_create(contender as usize, 0) as usize;
+    while thread_blocked_on(tid) == -1 {
+        yield_();
+    }
+    assert_eq!(thread_blocked_on(tid), BLOCKED_ON_MUTEX | mutex as isize);
+    mutex_unlock(mutex);
+    waittid(tid);
+
+    // the bookkeeping skipped so far does not confuse the detector
+    enable_deadlock_detect(true);
+    assert_eq!(mutex_lock(mutex), 0);
+    assert_eq!(mutex_lock(mutex), -EDEADMUTEX);
+    mutex_unlock(mutex);
+    enable_deadlock_detect(false);
+    println!("mutex fastpath test passed!");
+    0
+}
@@ -0,0 +1,70 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+extern crate alloc;
+
+use alloc::vec::Vec;
+use user_lib::{exit, mutex_barging_create, mutex_blocking_create, mutex_kind, mutex_lock};
+use user_lib::{mutex_unlock, thread_create, waittid, yield_, MUTEX_BARGING};
+
+// 理想结果：交接模式下等待者严格按到达顺序获得锁，抢占模式下刚解锁的线程可以立即重新获得锁
+
+const THREAD_N: usize = 5;
+const MAIN: usize = THREAD_N;
+
+static mut MUTEX: usize = 0;
+static mut ORDER: [usize; THREAD_N + 1] = [0; THREAD_N + 1];
+static mut ORDER_CNT: usize = 0;
+
+unsafe fn record(id: usize) {
+    ORDER[ORDER_CNT] = id;
+    ORDER_CNT += 1;
+}
+
+unsafe fn worker(id: *const usize) -> ! {
+    mutex_lock(MUTEX);
+    record(*id);
+    mutex_unlock(MUTEX);
+    exit(0)
+}
+
+/// Queue all workers on `mutex` in creation order, then unlock it and
+/// immediately try to take it back
+unsafe fn contend(mutex: usize) -> [usize; THREAD_N + 1] {
+    MUTEX = mutex;
+    ORDER_CNT = 0;
+    let ids: Vec<_> = (0..THREAD_N).collect();
+    mutex_lock(mutex);
+    let mut threads = Vec::new();
+    for id in ids.iter() {
+        threads.push(thread_create(worker as usize, id as *const _ as usize));
+    }
+    for _ in 0..4 * THREAD_N {
+        yield_();
+    }
+    mutex_unlock(mutex);
+    mutex_lock(mutex);
+    record(MAIN);
+    mutex_unlock(mutex);
+    for thread in threads.iter() {
+        waittid(*thread as usize);
+    }
+    assert_eq!(ORDER_CNT, THREAD_N + 1);
+    ORDER
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let handoff = mutex_blocking_create() as usize;
+    let order = unsafe { contend(handoff) };
+    assert_eq!(order, [0, 1, 2, 3, 4, MAIN]);
+
+    let barging = mutex_barging_create() as usize;
+    assert_eq!(mutex_kind(barging), MUTEX_BARGING as isize);
+    let order = unsafe { contend(barging) };
+    assert_eq!(order[0], MAIN);
+    println!("mutex handoff test passed!");
+    0
+}
@@ -0,0 +1,23 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{mutex_adaptive_create, mutex_blocking_create, mutex_create, mutex_kind};
+use user_lib::{MUTEX_ADAPTIVE, MUTEX_BLOCKING, MUTEX_SPIN};
+
+// 理想结果：每个互斥锁报告创建时的类型，无效 id 返回 -1
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let spin = mutex_create() as usize;
+    let blocking = mutex_blocking_create() as usize;
+    let adaptive = mutex_adaptive_create() as usize;
+    assert_eq!(mutex_kind(spin), MUTEX_SPIN as isize);
+    assert_eq!(mutex_kind(blocking), MUTEX_BLOCKING as isize);
+    assert_eq!(mutex_kind(adaptive), MUTEX_ADAPTIVE as isize);
+    assert_eq!(mutex_kind(adaptive + 1), -1);
+    println!("mutex kind test passed!");
+    0
+}
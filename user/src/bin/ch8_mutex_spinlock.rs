@@ -0,0 +1,74 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use core::ptr::{read_volatile, write_volatile};
+use user_lib::{exit, mutex_blocking_create, mutex_lock, mutex_spinlock, mutex_unlock};
+use user_lib::{thread_create, waittid, yield_};
+
+// 理想结果：有限次尝试内拿到锁返回 0，用完次数仍被占用返回 1 而不会阻塞，无效 id 返回 -EINVAL
+
+const EINVAL: isize = 22;
+const HOLD_YIELDS: usize = 5;
+
+static mut MUTEX: usize = 0;
+static mut HELD: bool = false;
+static mut RELEASE: bool = false;
+
+unsafe fn holder(yields: usize) -> ! {
+    assert_eq!(mutex_lock(MUTEX), 0);
+    write_volatile(&mut HELD, true);
+    for _ in 0..yields {
+        yield_();
+    }
+    while !read_volatile(&RELEASE) {
+        yield_();
+    }
+    write_volatile(&mut HELD, false);
+    mutex_unlock(MUTEX);
+    exit(0)
+}
+
+unsafe fn hold(yields: usize, release: bool) -> usize {
+    write_volatile(&mut RELEASE, release);
+    let thread = thread_create(holder as usize, yields) as usize;
+    while !read_volatile(&HELD) {
+        yield_();
+    }
+    thread
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let mutex = mutex_blocking_create() as usize;
+    unsafe {
+        MUTEX = mutex;
+    }
+    assert_eq!(mutex_spinlock(mutex + 100, 0), -EINVAL);
+    assert_eq!(mutex_spinlock(mutex, 0), 0);
+    // held by the caller itself, it fails instead of blocking forever
+    assert_eq!(mutex_spinlock(mutex, 3), 1);
+    mutex_unlock(mutex);
+
+    unsafe {
+        // released within the budget
+        let thread = hold(HOLD_YIELDS, true);
+        assert_eq!(mutex_spinlock(mutex, 0), 1);
+        assert_eq!(mutex_spinlock(mutex, HOLD_YIELDS * 10), 0);
+        assert!(!read_volatile(&HELD));
+        mutex_unlock(mutex);
+        waittid(thread);
+
+        // not released, the budget runs out
+        let thread = hold(0, false);
+        assert_eq!(mutex_spinlock(mutex, 10), 1);
+        write_volatile(&mut RELEASE, true);
+        waittid(thread);
+    }
+    assert_eq!(mutex_spinlock(mutex, 0), 0);
+    mutex_unlock(mutex);
+    println!("mutex spinlock test passed!");
+    0
+}
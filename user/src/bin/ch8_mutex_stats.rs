@@ -0,0 +1,77 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+extern crate alloc;
+
+use alloc::vec::Vec;
+use user_lib::{enable_deadlock_detect, exit, mutex_blocking_create, mutex_lock, mutex_stats};
+use user_lib::{mutex_unlock, thread_create, waittid, yield_, MutexStats, MUTEX_STATS_RESET};
+
+// 理想结果：分别统计直接拿到锁和需要等待的次数，报告当前等待者数量，带重置标志读取后计数清零
+
+const EINVAL: isize = 22;
+const THREAD_N: usize = 2;
+const ROUNDS: usize = 3;
+
+static mut MUTEX: usize = 0;
+
+unsafe fn contender() -> ! {
+    assert_eq!(mutex_lock(MUTEX), 0);
+    mutex_unlock(MUTEX);
+    exit(0)
+}
+
+fn stats(mutex: usize) -> MutexStats {
+    let mut stats = MutexStats::default();
+    assert_eq!(mutex_stats(mutex, &mut stats, 0), 0);
+    stats
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let mutex = mutex_blocking_create() as usize;
+    unsafe {
+        MUTEX = mutex;
+    }
+    let mut out = MutexStats::default();
+    assert_eq!(mutex_stats(mutex + 100, &mut out, 0), -EINVAL);
+    assert_eq!(mutex_stats(mutex, &mut out, 2), -EINVAL);
+
+    for _ in 0..ROUNDS {
+        assert_eq!(mutex_lock(mutex), 0);
+        mutex_unlock(mutex);
+    }
+    // the detector does not make a free mutex look contended
+    assert_eq!(enable_deadlock_detect(true), 0);
+    assert_eq!(mutex_lock(mutex), 0);
+    let now = stats(mutex);
+    assert_eq!(
+        (now.immediate, now.blocked, now.waiters),
+        (ROUNDS + 1, 0, 0)
+    );
+
+    let threads: Vec<_> = (0..THREAD_N)
+        .map(|_| thread_create(contender as usize, 0) as usize)
+        .collect();
+    while stats(mutex).waiters < THREAD_N {
+        yield_();
+    }
+    let now = stats(mutex);
+    assert_eq!((now.blocked, now.waiters), (THREAD_N, THREAD_N));
+    mutex_unlock(mutex);
+    for thread in threads {
+        waittid(thread);
+    }
+
+    assert_eq!(mutex_stats(mutex, &mut out, MUTEX_STATS_RESET), 0);
+    assert_eq!(
+        (out.immediate, out.blocked, out.waiters),
+        (ROUNDS + 1, THREAD_N, 0)
+    );
+    let now = stats(mutex);
+    assert_eq!((now.immediate, now.blocked, now.waiters), (0, 0, 0));
+    println!("mutex stats test passed!");
+    0
+}
@@ -0,0 +1,94 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use core::ptr::{read_volatile, write_volatile};
+use user_lib::{deadlock_stats, enable_deadlock_detect, exit, mutex_blocking_create, mutex_lock};
+use user_lib::{mutex_set_rank, mutex_stats, set_deadlock_checks, DeadlockStats, MutexStats};
+use user_lib::{mutex_trylock_checked, mutex_unlock, thread_create, waittid, yield_};
+use user_lib::{DEADLOCK_MUTEX, LOCK_ORDER_CHECK};
+
+// 理想结果：空闲时拿到锁返回 0，被其他线程持有时返回 1 且不阻塞；
+// 开启死锁检测后，若等待会形成环则立即返回 -0xdead，调用者释放自己的锁后对方得以继续；
+// 这种检查不计入死锁检测的统计；开启锁等级检查后，违反等级顺序时返回 -EDEADLK
+
+const EINVAL: isize = 22;
+const EDEADLK: isize = 35;
+
+static mut A: usize = 0;
+static mut B: usize = 0;
+static mut HELD: bool = false;
+static mut WAIT_A: bool = false;
+
+/// Hold B, then wait for A once told to
+unsafe fn holder() -> ! {
+    assert_eq!(mutex_lock(B), 0);
+    write_volatile(&mut HELD, true);
+    while !read_volatile(&WAIT_A) {
+        yield_();
+    }
+    assert_eq!(mutex_lock(A), 0);
+    mutex_unlock(A);
+    mutex_unlock(B);
+    exit(0)
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let (a, b) = unsafe {
+        A = mutex_blocking_create() as usize;
+        B = mutex_blocking_create() as usize;
+        (A, B)
+    };
+    assert_eq!(mutex_trylock_checked(b + 100), -EINVAL);
+    assert_eq!(mutex_trylock_checked(a), 0);
+    // held by the caller itself, it is just busy with detection off
+    assert_eq!(mutex_trylock_checked(a), 1);
+
+    let thread = thread_create(holder as usize, 0) as usize;
+    unsafe {
+        while !read_volatile(&HELD) {
+            yield_();
+        }
+    }
+    assert_eq!(mutex_trylock_checked(b), 1);
+    assert_eq!(enable_deadlock_detect(true), 0);
+    // the holder of B can still go on
+    assert_eq!(mutex_trylock_checked(b), 1);
+
+    unsafe {
+        write_volatile(&mut WAIT_A, true);
+    }
+    let mut stats = MutexStats::default();
+    while stats.waiters == 0 {
+        yield_();
+        mutex_stats(a, &mut stats, 0);
+    }
+    let mut before = DeadlockStats::default();
+    deadlock_stats(&mut before);
+    assert_eq!(mutex_trylock_checked(b), DEADLOCK_MUTEX);
+    assert_eq!(mutex_trylock_checked(a), DEADLOCK_MUTEX);
+    let mut after = DeadlockStats::default();
+    deadlock_stats(&mut after);
+    assert_eq!(after.runs, before.runs);
+    assert_eq!(after.deadlocks, before.deadlocks);
+    // back off, the holder gets A and releases both
+    mutex_unlock(a);
+    assert_eq!(waittid(thread), 0);
+    assert_eq!(mutex_trylock_checked(b), 0);
+    mutex_unlock(b);
+
+    // ranks are enforced as for a blocking lock
+    assert_eq!(mutex_set_rank(a, 1), 0);
+    assert_eq!(mutex_set_rank(b, 2), 0);
+    assert_eq!(set_deadlock_checks(LOCK_ORDER_CHECK), 0);
+    assert_eq!(mutex_trylock_checked(b), 0);
+    assert_eq!(mutex_trylock_checked(a), -EDEADLK);
+    mutex_unlock(b);
+    assert_eq!(mutex_trylock_checked(a), 0);
+    mutex_unlock(a);
+    println!("mutex trylock checked test passed!");
+    0
+}
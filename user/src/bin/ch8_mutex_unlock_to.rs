@@ -0,0 +1,72 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+extern crate alloc;
+
+use alloc::vec::Vec;
+use user_lib::{exit, gettid, mutex_barging_create, mutex_blocking_create, mutex_lock};
+use user_lib::{mutex_unlock, mutex_unlock_to, thread_create, waittid, yield_};
+
+// 理想结果：锁直接交给指定的等待线程，即使解锁者立即重新加锁也抢不到；指定的线程没有等待时按普通解锁处理
+
+const THREAD_N: usize = 4;
+const CHOSEN: usize = 2;
+
+static mut MUTEX: usize = 0;
+static mut ORDER: [usize; THREAD_N + 1] = [0; THREAD_N + 1];
+static mut ORDER_CNT: usize = 0;
+
+unsafe fn record(tid: usize) {
+    ORDER[ORDER_CNT] = tid;
+    ORDER_CNT += 1;
+}
+
+unsafe fn worker() -> ! {
+    mutex_lock(MUTEX);
+    record(gettid() as usize);
+    mutex_unlock(MUTEX);
+    exit(0)
+}
+
+/// Queue all workers on `mutex`, hand it to the `CHOSEN` one and take it back
+/// at once, return the tids in locking order
+unsafe fn contend(mutex: usize) -> ([usize; THREAD_N + 1], Vec<usize>) {
+    MUTEX = mutex;
+    ORDER_CNT = 0;
+    mutex_lock(mutex);
+    let threads: Vec<_> = (0..THREAD_N)
+        .map(|_| thread_create(worker as usize, 0) as usize)
+        .collect();
+    for _ in 0..4 * THREAD_N {
+        yield_();
+    }
+    mutex_unlock_to(mutex, threads[CHOSEN]);
+    mutex_lock(mutex);
+    record(gettid() as usize);
+    mutex_unlock(mutex);
+    for thread in threads.iter() {
+        waittid(*thread);
+    }
+    assert_eq!(ORDER_CNT, THREAD_N + 1);
+    (ORDER, threads)
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    for mutex in [mutex_blocking_create(), mutex_barging_create()] {
+        let (order, threads) = unsafe { contend(mutex as usize) };
+        assert_eq!(order[0], threads[CHOSEN]);
+        assert_ne!(order[1], gettid() as usize);
+    }
+
+    // nobody waits, a plain unlock
+    let mutex = mutex_blocking_create() as usize;
+    mutex_lock(mutex);
+    mutex_unlock_to(mutex, 100);
+    mutex_lock(mutex);
+    mutex_unlock(mutex);
+    println!("mutex unlock to test passed!");
+    0
+}
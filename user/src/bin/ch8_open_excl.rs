@@ -0,0 +1,69 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+extern crate alloc;
+
+use alloc::vec::Vec;
+use core::ptr::{read_volatile, write_volatile};
+use user_lib::{close, exit, open, thread_create, unlink, waittid, yield_, OpenFlags};
+
+// 理想结果：文件已存在时独占创建返回 -EEXIST，不存在时创建成功；
+// 多个线程同时独占创建同一文件，恰好一个成功
+
+const EEXIST: isize = 17;
+const THREAD_N: usize = 4;
+const ROUNDS: usize = 5;
+const LOCK_FILE: &str = "excl_lock\0";
+
+static mut GO: bool = false;
+
+fn create_excl() -> isize {
+    let flags = OpenFlags::CREATE | OpenFlags::EXCL | OpenFlags::WRONLY;
+    open(LOCK_FILE, flags)
+}
+
+fn racer() -> ! {
+    while unsafe { !read_volatile(&GO) } {
+        yield_();
+    }
+    let fd = create_excl();
+    if fd >= 0 {
+        close(fd as usize);
+        exit(1)
+    }
+    assert_eq!(fd, -EEXIST);
+    exit(0)
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    unlink(LOCK_FILE);
+    let fd = create_excl();
+    assert!(fd > 0);
+    close(fd as usize);
+    assert_eq!(create_excl(), -EEXIST);
+    // without EXCL an existing file still opens
+    let fd = open(LOCK_FILE, OpenFlags::CREATE | OpenFlags::WRONLY);
+    assert!(fd > 0);
+    close(fd as usize);
+
+    for _ in 0..ROUNDS {
+        assert_eq!(unlink(LOCK_FILE), 0);
+        unsafe {
+            write_volatile(&mut GO, false);
+        }
+        let threads: Vec<_> = (0..THREAD_N)
+            .map(|_| thread_create(racer as usize, 0) as usize)
+            .collect();
+        unsafe {
+            write_volatile(&mut GO, true);
+        }
+        let winners: isize = threads.iter().map(|&tid| waittid(tid)).sum();
+        assert_eq!(winners, 1);
+    }
+    unlink(LOCK_FILE);
+    println!("open excl test passed!");
+    0
+}
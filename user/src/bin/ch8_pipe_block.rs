@@ -0,0 +1,59 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{close, exit, fork, pipe, read, waitpid, write};
+
+// 理想结果：写端全部关闭后读到 EOF，读端全部关闭后写入返回 -EPIPE
+
+const EPIPE: isize = 32;
+const TOTAL: usize = 1000;
+const CHUNK: usize = 100;
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let mut pipe_fd = [0usize; 2];
+    pipe(&mut pipe_fd);
+    let pid = fork();
+    if pid == 0 {
+        // producer: far more than the pipe buffer holds
+        close(pipe_fd[0]);
+        let mut chunk = [0u8; CHUNK];
+        for i in 0..TOTAL / CHUNK {
+            for (j, byte) in chunk.iter_mut().enumerate() {
+                *byte = ((i * CHUNK + j) % 251) as u8;
+            }
+            assert_eq!(write(pipe_fd[1], &chunk), CHUNK as isize);
+        }
+        close(pipe_fd[1]);
+        exit(0);
+    }
+    // consumer: odd-sized reads, the last one is short and then EOF follows
+    close(pipe_fd[1]);
+    let mut buffer = [0u8; 7];
+    let mut received = 0;
+    loop {
+        let len = read(pipe_fd[0], &mut buffer) as usize;
+        if len == 0 {
+            break;
+        }
+        for (j, byte) in buffer[..len].iter().enumerate() {
+            assert_eq!(*byte, ((received + j) % 251) as u8);
+        }
+        received += len;
+    }
+    assert_eq!(received, TOTAL);
+    close(pipe_fd[0]);
+    let mut exit_code = 0;
+    waitpid(pid as usize, &mut exit_code);
+    assert_eq!(exit_code, 0);
+
+    pipe(&mut pipe_fd);
+    close(pipe_fd[0]);
+    assert_eq!(write(pipe_fd[1], b"lost"), -EPIPE);
+    close(pipe_fd[1]);
+    println!("pipe block test passed!");
+    0
+}
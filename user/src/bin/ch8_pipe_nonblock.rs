@@ -0,0 +1,43 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{close, pipe2, read, write, OpenFlags};
+
+// 理想结果：非阻塞管道在空时读、满时写返回 -EAGAIN，只传输能传输的部分，写端关闭后读到 EOF
+
+const EAGAIN: isize = 11;
+const EINVAL: isize = 22;
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let mut pipe_fd = [0usize; 2];
+    assert_eq!(pipe2(&mut pipe_fd, OpenFlags::CREATE), -EINVAL);
+    assert_eq!(pipe2(&mut pipe_fd, OpenFlags::NONBLOCK), 0);
+    let mut buffer = [0u8; 100];
+    assert_eq!(read(pipe_fd[0], &mut buffer), -EAGAIN);
+
+    // the buffer only takes part of it
+    let data = [7u8; 100];
+    let written = write(pipe_fd[1], &data);
+    assert!(written > 0 && written < data.len() as isize);
+    assert_eq!(write(pipe_fd[1], &data), -EAGAIN);
+    assert_eq!(read(pipe_fd[0], &mut buffer), written);
+    assert!(buffer[..written as usize].iter().all(|&b| b == 7));
+    assert_eq!(read(pipe_fd[0], &mut buffer), -EAGAIN);
+
+    close(pipe_fd[1]);
+    assert_eq!(read(pipe_fd[0], &mut buffer), 0);
+    close(pipe_fd[0]);
+
+    // no flag, the usual blocking pipe
+    assert_eq!(pipe2(&mut pipe_fd, OpenFlags::empty()), 0);
+    assert_eq!(write(pipe_fd[1], b"x"), 1);
+    close(pipe_fd[1]);
+    assert_eq!(read(pipe_fd[0], &mut buffer), 1);
+    close(pipe_fd[0]);
+    println!("pipe nonblock test passed!");
+    0
+}
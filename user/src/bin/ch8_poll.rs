@@ -0,0 +1,68 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{close, exit, get_time, pipe, poll, read, sleep_blocking, thread_create};
+use user_lib::{waittid, write, PollFd, POLLHUP, POLLIN, POLLNVAL, POLLOUT};
+
+// 理想结果：poll 在任一管道可读时返回并填写 revents，超时返回 0，无效 fd 报告 POLLNVAL，写端关闭报告 POLLHUP
+
+static mut WRITE_FD: usize = 0;
+
+unsafe fn late_writer() -> ! {
+    sleep_blocking(20);
+    write(WRITE_FD, b"x");
+    exit(0)
+}
+
+fn poll_fd(fd: usize, events: usize) -> PollFd {
+    PollFd {
+        fd,
+        events,
+        revents: 0,
+    }
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let mut a = [0usize; 2];
+    let mut b = [0usize; 2];
+    pipe(&mut a);
+    pipe(&mut b);
+
+    let mut fds = [poll_fd(a[0], POLLIN), poll_fd(b[0], POLLIN)];
+    assert_eq!(poll(&mut fds, 0), 0);
+    let mut out = [poll_fd(a[1], POLLOUT)];
+    assert_eq!(poll(&mut out, 0), 1);
+    assert_eq!(out[0].revents, POLLOUT);
+
+    let start = get_time();
+    assert_eq!(poll(&mut fds, 50), 0);
+    assert!(get_time() - start >= 50);
+
+    // woken by a write on the second pipe only
+    unsafe {
+        WRITE_FD = b[1];
+    }
+    let tid = thread_create(late_writer as usize, 0) as usize;
+    let start = get_time();
+    assert_eq!(poll(&mut fds, 1000), 1);
+    assert!(get_time() - start < 1000);
+    assert_eq!(fds[0].revents, 0);
+    assert_eq!(fds[1].revents, POLLIN);
+    waittid(tid);
+    let mut buf = [0u8; 1];
+    assert_eq!(read(b[0], &mut buf), 1);
+
+    let mut bad = [poll_fd(100, POLLIN)];
+    assert_eq!(poll(&mut bad, -1), 1);
+    assert_eq!(bad[0].revents, POLLNVAL);
+
+    close(b[1]);
+    assert_eq!(poll(&mut fds, -1), 1);
+    assert_eq!(fds[1].revents, POLLHUP);
+    println!("poll test passed!");
+    0
+}
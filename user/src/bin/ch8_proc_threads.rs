@@ -0,0 +1,63 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{exit, getpid, proc_threads, sleep_blocking, thread_create, waittid, yield_};
+use user_lib::{ThreadEntry, THREAD_BLOCKED, THREAD_READY, THREAD_RUNNING};
+
+// 理想结果：列出当前进程存活线程的 tid 与状态，缓冲区不足时返回所需数量；其他进程返回 -EPERM，不存在的 pid 返回 -1
+
+const EPERM: isize = 1;
+const INITPROC_PID: usize = 0;
+
+static mut STOP: bool = false;
+
+fn sleeper() -> ! {
+    sleep_blocking(100);
+    exit(0)
+}
+
+fn spinner() -> ! {
+    while unsafe { !core::ptr::read_volatile(&STOP) } {
+        yield_();
+    }
+    exit(0)
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let pid = getpid() as usize;
+    let sleeper_tid = thread_create(sleeper as usize, 0) as usize;
+    let spinner_tid = thread_create(spinner as usize, 0) as usize;
+    // let the sleeper block
+    for _ in 0..8 {
+        yield_();
+    }
+
+    let mut threads = [ThreadEntry::default(); 4];
+    assert_eq!(proc_threads(pid, &mut threads), 3);
+    let tids: [usize; 3] = [threads[0].tid, threads[1].tid, threads[2].tid];
+    assert_eq!(tids, [0, sleeper_tid, spinner_tid]);
+    assert_eq!(threads[0].state, THREAD_RUNNING);
+    assert_eq!(threads[1].state, THREAD_BLOCKED);
+    assert_eq!(threads[2].state, THREAD_READY);
+
+    // a short buffer gets the first threads and the count needed
+    let mut short = [ThreadEntry::default(); 1];
+    assert_eq!(proc_threads(pid, &mut short), 3);
+    assert_eq!(short[0].tid, 0);
+
+    assert_eq!(proc_threads(INITPROC_PID, &mut threads), -EPERM);
+    assert_eq!(proc_threads(100000, &mut threads), -1);
+
+    unsafe {
+        STOP = true;
+    }
+    waittid(sleeper_tid);
+    waittid(spinner_tid);
+    assert_eq!(proc_threads(pid, &mut threads), 1);
+    println!("proc threads test passed!");
+    0
+}
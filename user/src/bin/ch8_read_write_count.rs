@@ -0,0 +1,75 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+extern crate alloc;
+
+use alloc::vec::Vec;
+use user_lib::{close, open, pipe, pipe2, read, unlink, write, OpenFlags};
+
+// 理想结果：读写返回实际传输的字节数：读到文件末尾时只返回剩余部分，之后返回 0；
+// 向几乎满的非阻塞管道写入只写进剩余空间；无效 fd 或方向不对的读写返回 -EBADF
+
+const EBADF: isize = 9;
+const FILE: &str = "rw_count\0";
+const FILE_LEN: usize = 10;
+const CHUNK: usize = 4;
+const ROOM: usize = 2;
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let mut buf = [0u8; 64];
+    assert_eq!(read(100, &mut buf), -EBADF);
+    assert_eq!(write(100, &buf), -EBADF);
+
+    let data: Vec<u8> = (0..FILE_LEN as u8).collect();
+    let fd = open(FILE, OpenFlags::CREATE | OpenFlags::WRONLY) as usize;
+    assert_eq!(write(fd, &data), FILE_LEN as isize);
+    assert_eq!(read(fd, &mut buf), -EBADF);
+    close(fd);
+
+    // short read at the end of the file, then 0
+    let fd = open(FILE, OpenFlags::RDONLY) as usize;
+    assert_eq!(write(fd, &data), -EBADF);
+    let mut got = Vec::new();
+    loop {
+        let n = read(fd, &mut buf[..CHUNK]);
+        assert!(n >= 0 && n as usize <= CHUNK);
+        if n == 0 {
+            break;
+        }
+        got.extend_from_slice(&buf[..n as usize]);
+    }
+    assert_eq!(got, data);
+    assert_eq!(read(fd, &mut buf), 0);
+    close(fd);
+    assert_eq!(unlink(FILE), 0);
+
+    // partial write to a nearly full pipe
+    let mut pipe_fd = [0usize; 2];
+    assert_eq!(pipe2(&mut pipe_fd, OpenFlags::NONBLOCK), 0);
+    assert_eq!(read(pipe_fd[1], &mut buf), -EBADF);
+    assert_eq!(write(pipe_fd[0], &buf), -EBADF);
+    let fill: Vec<u8> = (0..buf.len() as u8).collect();
+    let capacity = write(pipe_fd[1], &fill) as usize;
+    assert!(capacity > ROOM && capacity < fill.len());
+    assert_eq!(read(pipe_fd[0], &mut buf[..ROOM]), ROOM as isize);
+    assert_eq!(&buf[..ROOM], &fill[..ROOM]);
+    assert_eq!(write(pipe_fd[1], &[0xffu8; 8]), ROOM as isize);
+    assert_eq!(read(pipe_fd[0], &mut buf), capacity as isize);
+    assert_eq!(&buf[..capacity - ROOM], &fill[ROOM..capacity]);
+    assert!(buf[capacity - ROOM..capacity].iter().all(|&b| b == 0xff));
+    close(pipe_fd[0]);
+    close(pipe_fd[1]);
+
+    // a blocking read returns what is left once the write end is closed
+    assert_eq!(pipe(&mut pipe_fd), 0);
+    assert_eq!(write(pipe_fd[1], &data[..CHUNK]), CHUNK as isize);
+    close(pipe_fd[1]);
+    assert_eq!(read(pipe_fd[0], &mut buf), CHUNK as isize);
+    assert_eq!(read(pipe_fd[0], &mut buf), 0);
+    close(pipe_fd[0]);
+    println!("read write count test passed!");
+    0
+}
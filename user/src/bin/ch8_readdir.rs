@@ -0,0 +1,36 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{close, open, readdir, DirEntry, OpenFlags};
+
+// 理想结果：新建的两个文件都出现在根目录的列表中，超长文件名被一致地截断
+
+const LONG_NAME: &str = "readdir_a_name_longer_than_the_limit";
+
+#[no_mangle]
+pub fn main() -> i32 {
+    for name in ["readdir_a\0", "readdir_a_name_longer_than_the_limit\0"] {
+        let fd = open(name, OpenFlags::CREATE | OpenFlags::WRONLY);
+        assert!(fd > 0);
+        close(fd as usize);
+    }
+    // the truncated name still finds the same file
+    let fd = open("readdir_a_name_longer_than_\0", OpenFlags::RDONLY);
+    assert!(fd > 0);
+    close(fd as usize);
+
+    let mut entries = [DirEntry::empty(); 128];
+    let n = readdir("/\0", &mut entries);
+    assert!(n > 0);
+    let entries = &entries[..n as usize];
+    assert!(entries.iter().any(|e| e.name() == "readdir_a"));
+    assert!(entries
+        .iter()
+        .any(|e| LONG_NAME.starts_with(e.name()) && e.name().len() == 27));
+    assert_eq!(readdir("readdir_a\0", &mut [DirEntry::empty(); 4]), -2);
+    println!("readdir test passed!");
+    0
+}
@@ -0,0 +1,54 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use core::ptr::{read_volatile, write_volatile};
+use user_lib::{exit, sched_info, sleep_blocking, thread_create, waittid, yield_, SchedInfo};
+
+// 理想结果：就绪线程计入 ready，睡眠线程同时计入 blocked 与 sleeping，睡眠数不超过阻塞数
+
+static mut STOP: bool = false;
+
+fn sleeper() -> ! {
+    sleep_blocking(100);
+    exit(0)
+}
+
+fn spinner() -> ! {
+    while unsafe { !read_volatile(&STOP) } {
+        yield_();
+    }
+    exit(0)
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let mut info = SchedInfo::default();
+    assert_eq!(sched_info(&mut info), 0);
+    assert!(info.sleeping <= info.blocked);
+
+    let sleeper_tid = thread_create(sleeper as usize, 0) as usize;
+    let spinner_tid = thread_create(spinner as usize, 0) as usize;
+    for _ in 0..8 {
+        yield_();
+    }
+    assert_eq!(sched_info(&mut info), 0);
+    // the spinner is ready whenever we run
+    assert!(info.ready >= 1);
+    assert!(info.sleeping >= 1);
+    assert!(info.blocked >= info.sleeping);
+    println!(
+        "ready: {}, blocked: {}, sleeping: {}",
+        info.ready, info.blocked, info.sleeping
+    );
+
+    unsafe {
+        write_volatile(&mut STOP, true);
+    }
+    waittid(sleeper_tid);
+    waittid(spinner_tid);
+    println!("sched info test passed!");
+    0
+}
@@ -0,0 +1,56 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{exit, sched_setpolicy, set_priority, thread_create, waittid, yield_};
+use user_lib::{SCHED_PRIORITY, SCHED_RR, SCHED_STRIDE};
+
+// 理想结果：优先级策略下高优先级线程让出后仍先运行，切换为轮转后其他线程立即得到运行，无效策略返回 -1
+
+const DEFAULT_PRIORITY: isize = 16;
+
+static mut COUNTER: usize = 0;
+static mut STOP: bool = false;
+
+unsafe fn worker() -> ! {
+    while !STOP {
+        COUNTER += 1;
+        yield_();
+    }
+    exit(0)
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    assert_eq!(sched_setpolicy(3), -1);
+    assert_eq!(set_priority(1000), 1000);
+    let from_stride = sched_setpolicy(SCHED_PRIORITY);
+    let tid = thread_create(worker as usize, 0) as usize;
+    for _ in 0..10 {
+        yield_();
+    }
+    // the worker has the default priority and never got the CPU
+    let starved = unsafe { COUNTER };
+
+    let from_priority = sched_setpolicy(SCHED_RR);
+    yield_();
+    let ran = unsafe { COUNTER };
+
+    // the policy is system-wide: restore it before any assert can fail
+    let from_rr = sched_setpolicy(SCHED_STRIDE);
+    set_priority(DEFAULT_PRIORITY);
+    unsafe {
+        STOP = true;
+    }
+    waittid(tid);
+
+    assert_eq!(from_stride, SCHED_STRIDE as isize);
+    assert_eq!(starved, 0);
+    assert_eq!(from_priority, SCHED_PRIORITY as isize);
+    assert!(ran > 0);
+    assert_eq!(from_rr, SCHED_RR as isize);
+    println!("sched policy test passed!");
+    0
+}
@@ -0,0 +1,63 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+extern crate alloc;
+
+use alloc::vec::Vec;
+use user_lib::{exit, sched_set_seed, sched_setpolicy, thread_create, waittid, yield_};
+use user_lib::{SCHED_SEEDED, SCHED_STRIDE};
+
+// 理想结果：相同种子下多个线程让出 CPU 时的交替顺序完全相同，可以重放；
+// 不同种子得到不同的顺序，普通的策略切换不能进入种子调度；
+// 内核未启用 sched-seed 特性时返回 -EPERM
+
+const EPERM: isize = 1;
+const THREAD_N: usize = 4;
+const STEPS: usize = 8;
+
+static mut LOG: [usize; THREAD_N * STEPS] = [0; THREAD_N * STEPS];
+static mut LEN: usize = 0;
+
+unsafe fn stepper(id: usize) -> ! {
+    for _ in 0..STEPS {
+        LOG[LEN] = id;
+        LEN += 1;
+        yield_();
+    }
+    exit(0)
+}
+
+fn interleaving(seed: usize) -> Vec<usize> {
+    unsafe {
+        LEN = 0;
+    }
+    sched_set_seed(seed);
+    let threads: Vec<_> = (0..THREAD_N)
+        .map(|id| thread_create(stepper as usize, id) as usize)
+        .collect();
+    for thread in threads {
+        waittid(thread);
+    }
+    // back to the default for the rest of the system before asserting
+    let prev = sched_setpolicy(SCHED_STRIDE);
+    assert_eq!(prev, SCHED_SEEDED as isize);
+    unsafe { LOG.to_vec() }
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    assert_eq!(sched_setpolicy(SCHED_SEEDED), -1);
+    if sched_set_seed(0) == -EPERM {
+        println!("seeded scheduler not built in, sched seed test passed!");
+        return 0;
+    }
+    assert_eq!(sched_setpolicy(SCHED_STRIDE), SCHED_SEEDED as isize);
+    let first = interleaving(7);
+    println!("seed 7: {:?}", first);
+    assert_eq!(interleaving(7), first);
+    assert!((8..12).any(|seed| interleaving(seed) != first));
+    println!("sched seed test passed!");
+    0
+}
@@ -0,0 +1,54 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{enable_sched_trace, exit, getpid, gettid, sched_trace, sleep_blocking};
+use user_lib::{thread_create, waittid, yield_, SchedEvent, SCHED_BLOCK, SCHED_EXIT, SCHED_YIELD};
+
+// 理想结果：记录让出、阻塞和退出三种切换原因，时间单调不减，短缓冲区得到最近的记录
+
+fn short_lived() -> ! {
+    exit(0)
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let pid = getpid() as usize;
+    let tid = gettid() as usize;
+    assert_eq!(enable_sched_trace(true), 0);
+    yield_();
+    sleep_blocking(10);
+    let child = thread_create(short_lived as usize, 0) as usize;
+    waittid(child);
+    assert_eq!(enable_sched_trace(false), 0);
+
+    let mut events = [SchedEvent::default(); 1024];
+    let n = sched_trace(&mut events) as usize;
+    let events = &events[..n];
+    let ours = |e: &&SchedEvent| e.from_pid == pid;
+    assert!(events
+        .iter()
+        .filter(ours)
+        .any(|e| e.from_tid == tid && e.reason == SCHED_YIELD));
+    assert!(events
+        .iter()
+        .filter(ours)
+        .any(|e| e.from_tid == tid && e.reason == SCHED_BLOCK));
+    assert!(events
+        .iter()
+        .filter(ours)
+        .any(|e| e.from_tid == child && e.reason == SCHED_EXIT));
+    for pair in events.windows(2) {
+        assert!(pair[0].tick <= pair[1].tick);
+    }
+
+    // tracing is off, the trace is kept as it was
+    let mut last = [SchedEvent::default(); 2];
+    assert_eq!(sched_trace(&mut last), 2);
+    assert_eq!(last[1].tick, events[n - 1].tick);
+    assert_eq!(last[0].tick, events[n - 2].tick);
+    println!("sched trace test passed!");
+    0
+}
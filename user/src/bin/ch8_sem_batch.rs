@@ -0,0 +1,79 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{exit, semaphore_create, semaphore_down_n, semaphore_getvalue, semaphore_up_n};
+use user_lib::{sleep_blocking, thread_create, waittid, yield_};
+
+// 理想结果：一次释放多个资源时按顺序唤醒能满足的等待者，大请求不会被后来的小请求越过；
+// 使计数溢出或超过上限的释放返回 -EINVAL 且计数不变
+
+const EINVAL: isize = 22;
+const SEM: usize = 0;
+const SEM_MAX_COUNT: usize = 0x7fff_ffff;
+
+static mut WOKEN: [bool; 4] = [false; 4];
+static mut DONE: bool = false;
+static NEEDS: [usize; 4] = [2, 1, 1, 1];
+
+unsafe fn waiter(id: usize) -> ! {
+    assert_eq!(semaphore_down_n(SEM, NEEDS[id]), 0);
+    WOKEN[id] = true;
+    // keep the resources, exiting would give them back
+    while !DONE {
+        yield_();
+    }
+    exit(0)
+}
+
+fn woken() -> [bool; 4] {
+    unsafe { WOKEN }
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    assert_eq!(semaphore_create(0) as usize, SEM);
+    let mut threads = [0; 4];
+    // queue: a two-unit request in front of three single-unit ones
+    for (id, thread) in threads.iter_mut().enumerate() {
+        *thread = thread_create(waiter as usize, id) as usize;
+        sleep_blocking(20);
+    }
+    assert_eq!(semaphore_getvalue(SEM), -5);
+
+    // one unit cannot serve the front waiter, nobody behind may overtake it
+    semaphore_up_n(SEM, 1);
+    sleep_blocking(20);
+    assert_eq!(woken(), [false; 4]);
+    assert_eq!(semaphore_getvalue(SEM), -4);
+
+    // three more: 2 for the front waiter, then 1 and 1
+    semaphore_up_n(SEM, 3);
+    sleep_blocking(20);
+    assert_eq!(woken(), [true, true, true, false]);
+    assert_eq!(semaphore_getvalue(SEM), -1);
+
+    semaphore_up_n(SEM, 1);
+    sleep_blocking(20);
+    assert_eq!(woken(), [true; 4]);
+    assert_eq!(semaphore_getvalue(SEM), 0);
+    unsafe {
+        DONE = true;
+    }
+    for thread in threads.iter() {
+        waittid(*thread);
+    }
+    // what the waiters held went back when they exited
+    assert_eq!(semaphore_getvalue(SEM), 5);
+
+    // a release that would wrap or pass the limit leaves the count alone
+    assert_eq!(semaphore_up_n(SEM, usize::MAX), -EINVAL);
+    assert_eq!(semaphore_up_n(SEM, SEM_MAX_COUNT - 4), -EINVAL);
+    assert_eq!(semaphore_getvalue(SEM), 5);
+    assert_eq!(semaphore_up_n(SEM, SEM_MAX_COUNT - 5), 0);
+    assert_eq!(semaphore_getvalue(SEM), SEM_MAX_COUNT as isize);
+    println!("sem batch test passed!");
+    0
+}
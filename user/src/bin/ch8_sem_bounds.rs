@@ -0,0 +1,25 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{semaphore_create, semaphore_down, semaphore_getvalue, semaphore_up};
+
+// 理想结果：初始值过大的信号量创建失败，上限以内的正常创建
+
+const SEM_MAX_COUNT: usize = 0x7fff_ffff;
+
+#[no_mangle]
+pub fn main() -> i32 {
+    assert_eq!(semaphore_create(usize::MAX), -1);
+    assert_eq!(semaphore_create(SEM_MAX_COUNT + 1), -1);
+    let sem = semaphore_create(SEM_MAX_COUNT);
+    assert!(sem >= 0);
+    let sem = sem as usize;
+    assert_eq!(semaphore_down(sem), 0);
+    assert_eq!(semaphore_getvalue(sem), SEM_MAX_COUNT as isize - 1);
+    semaphore_up(sem);
+    println!("sem bounds test passed!");
+    0
+}
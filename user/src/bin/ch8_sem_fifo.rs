@@ -0,0 +1,64 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+extern crate alloc;
+
+use alloc::vec::Vec;
+use user_lib::{exit, semaphore_create, semaphore_down, semaphore_up, yield_};
+use user_lib::{thread_create, waittid};
+
+// 理想结果：信号量按到达顺序唤醒，没有线程饿死
+
+const SEM_RES: usize = 0;
+const SEM_GUARD: usize = 1;
+const RES_NUM: usize = 2;
+const THREAD_N: usize = 5;
+const ROUNDS: usize = 20;
+const TOTAL: usize = THREAD_N * ROUNDS;
+
+static mut GRANTS: [usize; TOTAL] = [0; TOTAL];
+static mut GRANT_CNT: usize = 0;
+
+unsafe fn worker(id: *const usize) -> ! {
+    let id = *id;
+    for _ in 0..ROUNDS {
+        assert_eq!(semaphore_down(SEM_RES), 0);
+        semaphore_down(SEM_GUARD);
+        GRANTS[GRANT_CNT] = id;
+        GRANT_CNT += 1;
+        semaphore_up(SEM_GUARD);
+        yield_();
+        semaphore_up(SEM_RES);
+    }
+    exit(0)
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    assert_eq!(semaphore_create(RES_NUM) as usize, SEM_RES);
+    assert_eq!(semaphore_create(1) as usize, SEM_GUARD);
+    let ids: Vec<_> = (0..THREAD_N).collect();
+    let mut threads = Vec::new();
+    for i in 0..THREAD_N {
+        threads.push(thread_create(
+            worker as usize,
+            &ids.as_slice()[i] as *const _ as usize,
+        ));
+    }
+    for thread in threads.iter() {
+        waittid(*thread as usize);
+    }
+    // between two grants of the same thread, every other waiter is served
+    // at most about once, so the gap is bounded by the number of threads
+    let mut last = [None; THREAD_N];
+    for (i, &id) in unsafe { GRANTS.iter().enumerate() } {
+        if let Some(prev) = last[id] {
+            assert!(i - prev <= 2 * THREAD_N, "thread {} starved", id);
+        }
+        last[id] = Some(i);
+    }
+    println!("sem fifo test passed!");
+    0
+}
@@ -0,0 +1,40 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{
+    exit, semaphore_create, semaphore_down, semaphore_getvalue, semaphore_up, sleep_blocking,
+};
+use user_lib::{thread_create, waittid};
+
+// 理想结果：计数为正时返回可用资源数，没有资源时返回等待者个数的相反数
+
+const SEM: usize = 0;
+
+fn waiter() -> ! {
+    semaphore_down(SEM);
+    exit(0)
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    assert_eq!(semaphore_create(2) as usize, SEM);
+    assert_eq!(semaphore_getvalue(SEM), 2);
+    semaphore_down(SEM);
+    semaphore_down(SEM);
+    assert_eq!(semaphore_getvalue(SEM), 0);
+    let t1 = thread_create(waiter as usize, 0);
+    let t2 = thread_create(waiter as usize, 0);
+    sleep_blocking(50);
+    assert_eq!(semaphore_getvalue(SEM), -2);
+    semaphore_up(SEM);
+    semaphore_up(SEM);
+    waittid(t1 as usize);
+    waittid(t2 as usize);
+    assert_eq!(semaphore_getvalue(SEM), 0);
+    assert_eq!(semaphore_getvalue(100), isize::MIN);
+    println!("sem getvalue test passed!");
+    0
+}
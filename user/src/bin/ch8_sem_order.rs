@@ -0,0 +1,54 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+extern crate alloc;
+
+use alloc::vec::Vec;
+use core::ptr::read_volatile;
+use user_lib::{exit, gettid, semaphore_create, semaphore_down, semaphore_up, semaphore_waiters};
+use user_lib::{thread_create, waittid, yield_};
+
+// 理想结果：三个线程依次阻塞在资源数为 0 的信号量上，每次 up 唤醒等待最久的线程
+
+const THREAD_N: usize = 3;
+
+static mut SEM: usize = 0;
+static mut ORDER: [usize; THREAD_N] = [0; THREAD_N];
+static mut ORDER_CNT: usize = 0;
+
+unsafe fn waiter() -> ! {
+    semaphore_down(SEM);
+    ORDER[ORDER_CNT] = gettid() as usize;
+    ORDER_CNT += 1;
+    exit(0)
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let sem = semaphore_create(0) as usize;
+    unsafe {
+        SEM = sem;
+    }
+    let mut buf = [0usize; THREAD_N];
+    let mut threads = Vec::new();
+    for i in 0..THREAD_N {
+        threads.push(thread_create(waiter as usize, 0) as usize);
+        while semaphore_waiters(sem, &mut buf) as usize <= i {
+            yield_();
+        }
+    }
+    for i in 0..THREAD_N {
+        semaphore_up(sem);
+        while unsafe { read_volatile(&ORDER_CNT) } <= i {
+            yield_();
+        }
+        assert_eq!(unsafe { ORDER[i] }, threads[i]);
+    }
+    for thread in threads {
+        waittid(thread);
+    }
+    println!("sem order test passed!");
+    0
+}
@@ -0,0 +1,54 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+extern crate alloc;
+
+use alloc::vec::Vec;
+use user_lib::{enable_deadlock_detect, exit, semaphore_create, semaphore_down, semaphore_down_n};
+use user_lib::{semaphore_getvalue, semaphore_up, semaphore_up_n, thread_create, waittid, yield_};
+
+// 理想结果：开启死锁检测时并发地获取和释放信号量，所有线程按同一顺序加锁，从不误报死锁
+
+const THREAD_N: usize = 4;
+const ROUNDS: usize = 200;
+const POOL_SIZE: usize = 2;
+
+static mut POOL: usize = 0;
+static mut GATE: usize = 0;
+
+unsafe fn worker(id: usize) -> ! {
+    for round in 0..ROUNDS {
+        // some rounds take the whole pool so that waiters pile up behind
+        let n = if (round + id) % 3 == 0 { POOL_SIZE } else { 1 };
+        assert_eq!(semaphore_down_n(POOL, n), 0);
+        assert_eq!(semaphore_down(GATE), 0);
+        yield_();
+        semaphore_up(GATE);
+        yield_();
+        semaphore_up_n(POOL, n);
+    }
+    exit(0)
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    assert_eq!(enable_deadlock_detect(true), 0);
+    unsafe {
+        POOL = semaphore_create(POOL_SIZE) as usize;
+        GATE = semaphore_create(1) as usize;
+    }
+    let threads: Vec<_> = (0..THREAD_N)
+        .map(|id| thread_create(worker as usize, id))
+        .collect();
+    for thread in threads {
+        assert_eq!(waittid(thread as usize), 0);
+    }
+    unsafe {
+        assert_eq!(semaphore_getvalue(POOL), POOL_SIZE as isize);
+        assert_eq!(semaphore_getvalue(GATE), 1);
+    }
+    println!("sem stress test passed!");
+    0
+}
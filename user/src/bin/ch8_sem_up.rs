@@ -0,0 +1,32 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{enable_deadlock_detect, semaphore_create, semaphore_down, semaphore_up};
+
+// 理想结果：多余的 up 只增加资源数，之后的 down 均成功且不被误判为死锁
+
+const UP_N: usize = 3;
+
+#[no_mangle]
+pub fn main() -> i32 {
+    enable_deadlock_detect(true);
+    let sid = semaphore_create(0) as usize;
+    for _ in 0..UP_N {
+        semaphore_up(sid);
+    }
+    for _ in 0..UP_N {
+        assert_eq!(semaphore_down(sid), 0);
+    }
+    // return what we hold, then one more than we hold
+    for _ in 0..UP_N + 1 {
+        semaphore_up(sid);
+    }
+    for _ in 0..UP_N + 1 {
+        assert_eq!(semaphore_down(sid), 0);
+    }
+    println!("sem up test passed!");
+    0
+}
@@ -0,0 +1,61 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+extern crate alloc;
+
+use alloc::vec::Vec;
+use user_lib::{exit, semaphore_create, semaphore_down, semaphore_up, semaphore_waiters};
+use user_lib::{thread_create, waittid, yield_};
+
+// 理想结果：按阻塞先后顺序返回等待线程的 tid，缓冲区不足时只写入前几个，无效 id 返回 -1
+
+const THREAD_N: usize = 3;
+
+static mut SEM: usize = 0;
+
+unsafe fn waiter() -> ! {
+    semaphore_down(SEM);
+    exit(0)
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let sem = semaphore_create(0) as usize;
+    unsafe {
+        SEM = sem;
+    }
+    let mut buf = [0usize; THREAD_N + 1];
+    assert_eq!(semaphore_waiters(sem, &mut buf), 0);
+
+    // queue the threads one at a time so their order is known
+    let mut threads = Vec::new();
+    for i in 0..THREAD_N {
+        threads.push(thread_create(waiter as usize, 0) as usize);
+        while semaphore_waiters(sem, &mut buf) as usize <= i {
+            yield_();
+        }
+    }
+    assert_eq!(semaphore_waiters(sem, &mut buf), THREAD_N as isize);
+    assert_eq!(&buf[..THREAD_N], &threads[..]);
+
+    let mut short = [0usize; 2];
+    assert_eq!(semaphore_waiters(sem, &mut short), 2);
+    assert_eq!(&short[..], &threads[..2]);
+    assert_eq!(semaphore_waiters(sem + 100, &mut buf), -1);
+
+    // the oldest waiter leaves the queue first
+    semaphore_up(sem);
+    assert_eq!(semaphore_waiters(sem, &mut buf), THREAD_N as isize - 1);
+    assert_eq!(&buf[..THREAD_N - 1], &threads[1..]);
+    for _ in 1..THREAD_N {
+        semaphore_up(sem);
+    }
+    for thread in threads {
+        waittid(thread);
+    }
+    assert_eq!(semaphore_waiters(sem, &mut buf), 0);
+    println!("sem waiters test passed!");
+    0
+}
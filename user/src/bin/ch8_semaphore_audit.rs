@@ -0,0 +1,76 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+extern crate alloc;
+
+use alloc::vec::Vec;
+use core::ptr::{read_volatile, write_volatile};
+use user_lib::{exit, gettid, semaphore_audit, semaphore_create, semaphore_down};
+use user_lib::{semaphore_getvalue, semaphore_up, thread_create, waittid, yield_};
+
+// 理想结果：按 tid 报告每个线程持有的信号量资源数；
+// 所有线程归还后全部为 0，可用数恢复为初始值；无效 id 返回 -EINVAL
+
+const EINVAL: isize = 22;
+const PERMITS: usize = 3;
+const THREAD_N: usize = 2;
+
+static mut SEM: usize = 0;
+static mut HOLDING: usize = 0;
+static mut RELEASE: bool = false;
+
+unsafe fn holder() -> ! {
+    assert_eq!(semaphore_down(SEM), 0);
+    write_volatile(&mut HOLDING, read_volatile(&HOLDING) + 1);
+    while !read_volatile(&RELEASE) {
+        yield_();
+    }
+    semaphore_up(SEM);
+    exit(0)
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let sem = semaphore_create(PERMITS) as usize;
+    unsafe {
+        SEM = sem;
+    }
+    let mut held = [usize::MAX; 8];
+    assert_eq!(semaphore_audit(sem + 100, &mut held), -EINVAL);
+    assert_eq!(semaphore_audit(sem, &mut held), 1);
+    assert_eq!(held[0], 0);
+
+    let threads: Vec<_> = (0..THREAD_N)
+        .map(|_| thread_create(holder as usize, 0) as usize)
+        .collect();
+    unsafe {
+        while read_volatile(&HOLDING) < THREAD_N {
+            yield_();
+        }
+    }
+    assert_eq!(semaphore_down(sem), 0);
+    let n = semaphore_audit(sem, &mut held) as usize;
+    assert!(n > THREAD_N);
+    assert_eq!(held[gettid() as usize], 1);
+    for &tid in threads.iter() {
+        assert_eq!(held[tid], 1);
+    }
+    assert_eq!(held[..n].iter().sum::<usize>(), PERMITS);
+    // a short buffer only gets the first tids
+    assert_eq!(semaphore_audit(sem, &mut held[..1]), 1);
+
+    unsafe {
+        write_volatile(&mut RELEASE, true);
+    }
+    for tid in threads {
+        assert_eq!(waittid(tid), 0);
+    }
+    semaphore_up(sem);
+    let n = semaphore_audit(sem, &mut held) as usize;
+    assert!(held[..n].iter().all(|&permits| permits == 0));
+    assert_eq!(semaphore_getvalue(sem), PERMITS as isize);
+    println!("semaphore audit test passed!");
+    0
+}
@@ -0,0 +1,90 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{exit, semaphore_create, semaphore_down, semaphore_getvalue, semaphore_op};
+use user_lib::{semaphore_up, sleep_blocking, thread_create, waittid, SemOp};
+
+// 理想结果：同时获取两个信号量，等待期间不占用其中任何一个
+
+const EINVAL: isize = 22;
+const SEM_A: usize = 0;
+const SEM_B: usize = 1;
+
+static mut ACQUIRED: bool = false;
+
+fn both() -> ! {
+    let ops = [
+        SemOp {
+            sem_id: SEM_A,
+            delta: -1,
+        },
+        SemOp {
+            sem_id: SEM_B,
+            delta: -1,
+        },
+    ];
+    assert_eq!(semaphore_op(&ops), 0);
+    unsafe {
+        ACQUIRED = true;
+    }
+    // give both back at once
+    let ops = [
+        SemOp {
+            sem_id: SEM_A,
+            delta: 1,
+        },
+        SemOp {
+            sem_id: SEM_B,
+            delta: 1,
+        },
+    ];
+    assert_eq!(semaphore_op(&ops), 0);
+    exit(0)
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    assert_eq!(semaphore_create(1) as usize, SEM_A);
+    assert_eq!(semaphore_create(1) as usize, SEM_B);
+    let bad = [SemOp {
+        sem_id: 100,
+        delta: -1,
+    }];
+    assert_eq!(semaphore_op(&bad), -EINVAL);
+    // the downs of SEM_A sum past usize::MAX
+    let overflow = [
+        SemOp {
+            sem_id: SEM_A,
+            delta: isize::MIN,
+        },
+        SemOp {
+            sem_id: SEM_A,
+            delta: isize::MIN,
+        },
+        SemOp {
+            sem_id: SEM_A,
+            delta: -1,
+        },
+    ];
+    assert_eq!(semaphore_op(&overflow), -EINVAL);
+
+    semaphore_down(SEM_A);
+    let tid = thread_create(both as usize, 0) as usize;
+    sleep_blocking(30);
+    // waiting for A, the thread must not sit on B
+    assert!(!unsafe { ACQUIRED });
+    assert_eq!(semaphore_getvalue(SEM_B), 1);
+    semaphore_down(SEM_B);
+    semaphore_up(SEM_B);
+
+    semaphore_up(SEM_A);
+    waittid(tid);
+    assert!(unsafe { ACQUIRED });
+    assert_eq!(semaphore_getvalue(SEM_A), 1);
+    assert_eq!(semaphore_getvalue(SEM_B), 1);
+    println!("semop test passed!");
+    0
+}
@@ -0,0 +1,100 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+extern crate alloc;
+
+use alloc::vec::Vec;
+use user_lib::{exit, get_ticks, get_time, sched_info, sleep_blocking};
+use user_lib::{thread_create, waittid, yield_, SchedInfo};
+
+// 大量线程同时睡眠，统计唤醒延迟，并比较有无大量待定定时器时每个时钟中断的开销
+// 理想结果：没有线程被提前唤醒，且最大延迟较小；待定定时器不明显拖慢时钟中断
+
+const THREAD_N: usize = 200;
+/// Timer ticks a busy loop is measured over
+const MEASURE_TICKS: isize = 20;
+/// Long enough for the sleepers to stay pending while measuring
+const PARK_MS: usize = 1000;
+
+static mut LATENESS: [isize; THREAD_N] = [0; THREAD_N];
+
+fn sleep_ms_of(i: usize) -> usize {
+    10 + i * 37 % 500
+}
+
+unsafe fn sleeper(i: usize) -> ! {
+    let ms = sleep_ms_of(i);
+    let start = get_time();
+    sleep_blocking(ms);
+    LATENESS[i] = get_time() - start - ms as isize;
+    exit(0)
+}
+
+fn parked() -> ! {
+    sleep_blocking(PARK_MS);
+    exit(0)
+}
+
+/// Iterations of a busy loop over `MEASURE_TICKS` timer ticks; fewer means
+/// each tick costs more
+fn spins_per_window() -> usize {
+    let start = get_ticks();
+    while get_ticks() == start {}
+    let start = get_ticks();
+    let mut spins = 0;
+    while get_ticks() - start < MEASURE_TICKS {
+        spins += 1;
+    }
+    spins
+}
+
+/// Compare the cost of a tick with no pending timers and with `THREAD_N`
+fn measure_tick_cost() {
+    let idle = spins_per_window();
+    let threads: Vec<_> = (0..THREAD_N)
+        .map(|_| thread_create(parked as usize, 0))
+        .collect();
+    let mut info = SchedInfo::default();
+    loop {
+        sched_info(&mut info);
+        if info.sleeping >= THREAD_N {
+            break;
+        }
+        yield_();
+    }
+    let loaded = spins_per_window();
+    for thread in threads {
+        waittid(thread as usize);
+    }
+    println!(
+        "per {} ticks: {} spins with no pending timer, {} with {} pending",
+        MEASURE_TICKS, idle, loaded, THREAD_N
+    );
+    assert!(loaded * 2 > idle, "pending timers slow down every tick");
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    measure_tick_cost();
+    let start = get_time();
+    let mut threads = Vec::new();
+    for i in 0..THREAD_N {
+        threads.push(thread_create(sleeper as usize, i));
+    }
+    for thread in threads.iter() {
+        waittid(*thread as usize);
+    }
+    let elapsed = get_time() - start;
+    let lateness = unsafe { LATENESS };
+    assert!(lateness.iter().all(|&l| l >= 0), "woken up too early");
+    let max = lateness.iter().max().unwrap();
+    let avg = lateness.iter().sum::<isize>() / THREAD_N as isize;
+    println!(
+        "{} sleepers: elapsed = {}ms, max lateness = {}ms, avg lateness = {}ms",
+        THREAD_N, elapsed, max, avg
+    );
+    println!("sleep many test passed!");
+    0
+}
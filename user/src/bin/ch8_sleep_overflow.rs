@@ -0,0 +1,29 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{exit, get_time, sleep, sleep_blocking, thread_create, thread_sleep_remaining};
+
+// 理想结果：超长的睡眠时间不会回绕成立即到期，短睡眠按时到期
+
+fn sleeper() -> ! {
+    sleep_blocking(usize::MAX);
+    panic!("woke up from an endless sleep");
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let start = get_time();
+    sleep_blocking(30);
+    assert!(get_time() - start >= 30);
+
+    let tid = thread_create(sleeper as usize, 0) as usize;
+    sleep(30);
+    // still sleeping, with a deadline far away
+    assert!(thread_sleep_remaining(tid) > 1_000_000_000);
+    println!("sleep overflow test passed!");
+    // exiting the process ends the sleeper
+    exit(0)
+}
@@ -0,0 +1,54 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{exit, sleep, sleep_blocking, thread_create, thread_resume, thread_sleep_remaining};
+use user_lib::{thread_suspend, waittid};
+
+// 理想结果：睡眠线程的剩余时间逐渐减少，醒来后和不存在的线程都返回 -1；
+// 定时器已到期但线程还没有运行时返回 0
+
+const SLEEP_MS: usize = 300;
+const SHORT_MS: usize = 50;
+
+fn sleeper(ms: usize) -> ! {
+    sleep_blocking(ms);
+    exit(0)
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let tid = thread_create(sleeper as usize, SLEEP_MS) as usize;
+    // wait until the sleeper has registered its timer
+    let mut first = thread_sleep_remaining(tid);
+    while first < 0 {
+        sleep(1);
+        first = thread_sleep_remaining(tid);
+    }
+    assert!(first > 0 && first <= SLEEP_MS as isize);
+    println!("thread {} wakes in {}ms", tid, first);
+    sleep(100);
+    let second = thread_sleep_remaining(tid);
+    assert!(second >= 0 && second < first);
+    println!("thread {} wakes in {}ms", tid, second);
+    // the main thread is running, not sleeping
+    assert_eq!(thread_sleep_remaining(0), -1);
+    assert_eq!(thread_sleep_remaining(tid + 100), -1);
+    waittid(tid);
+    assert_eq!(thread_sleep_remaining(tid), -1);
+
+    // woken by its timer, but suspended before it could run
+    let tid = thread_create(sleeper as usize, SHORT_MS) as usize;
+    while thread_sleep_remaining(tid) < 0 {
+        sleep(1);
+    }
+    assert_eq!(thread_suspend(tid), 0);
+    sleep_blocking(SHORT_MS * 3);
+    assert_eq!(thread_sleep_remaining(tid), 0);
+    assert_eq!(thread_resume(tid), 0);
+    waittid(tid);
+    println!("sleep remaining test passed!");
+    0
+}
@@ -0,0 +1,38 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{exit, get_time, sleep_blocking, thread_create, waittid};
+
+// 理想结果：sleep(0) 立即让出 CPU，不必等到下一个时钟中断
+
+const ROUNDS: usize = 20;
+
+static mut RAN: bool = false;
+
+unsafe fn other() -> ! {
+    RAN = true;
+    exit(0)
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    // alone, every call returns at once instead of waiting for a tick
+    let start = get_time();
+    for _ in 0..ROUNDS {
+        sleep_blocking(0);
+    }
+    // a tick per call would take 10ms each
+    assert!(get_time() - start < 50);
+
+    // with another ready thread, it gets the CPU
+    let tid = thread_create(other as usize, 0) as usize;
+    while unsafe { !RAN } {
+        sleep_blocking(0);
+    }
+    waittid(tid);
+    println!("sleep zero test passed!");
+    0
+}
@@ -0,0 +1,61 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{exit, get_time, set_priority};
+use user_lib::{thread_create, waittid};
+
+// 理想结果：优先级为 8 的线程获得的 CPU 时间约为优先级为 2 的线程的 4 倍
+
+const RUN_TIME: isize = 2000;
+
+static mut DEADLINE: isize = 0;
+static mut COUNTS: [usize; 2] = [0; 2];
+
+fn spin_delay() {
+    let mut j = true;
+    for _ in 0..10 {
+        j = !j;
+    }
+}
+
+unsafe fn count_until_deadline(slot: usize, prio: isize) -> ! {
+    assert_eq!(set_priority(prio), prio);
+    let mut acc = 0;
+    loop {
+        spin_delay();
+        acc += 1;
+        if acc % 400 == 0 && get_time() > DEADLINE {
+            break;
+        }
+    }
+    COUNTS[slot] = acc;
+    exit(0)
+}
+
+fn low() -> ! {
+    unsafe { count_until_deadline(0, 2) }
+}
+
+fn high() -> ! {
+    unsafe { count_until_deadline(1, 8) }
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    unsafe {
+        DEADLINE = get_time() + RUN_TIME;
+    }
+    let low_tid = thread_create(low as usize, 0);
+    let high_tid = thread_create(high as usize, 0);
+    waittid(low_tid as usize);
+    waittid(high_tid as usize);
+    let (low_count, high_count) = unsafe { (COUNTS[0], COUNTS[1]) };
+    println!("prio 2 count = {}, prio 8 count = {}", low_count, high_count);
+    // allow some slack for timer granularity
+    assert!(high_count > low_count * 3 && high_count < low_count * 5);
+    println!("stride ratio test passed!");
+    0
+}
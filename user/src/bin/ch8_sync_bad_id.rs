@@ -0,0 +1,49 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{condvar_create, condvar_destroy, condvar_signal_n, condvar_signal_tid};
+use user_lib::{mutex_blocking_create, mutex_lock, semaphore_create, semaphore_down};
+use user_lib::{semaphore_down_n, semaphore_up_n, sys_condvar_signal, sys_condvar_wait};
+use user_lib::{sys_mutex_unlock, sys_semaphore_up};
+
+// 理想结果：向各个同步原语系统调用传入越界或已销毁的 id 时返回 -EINVAL，内核不会崩溃
+
+const EINVAL: isize = 22;
+
+const BAD_IDS: [usize; 3] = [100, 0x7fff_ffff, usize::MAX];
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let mutex = mutex_blocking_create() as usize;
+    let sem = semaphore_create(1) as usize;
+    let condvar = condvar_create() as usize;
+    for &id in BAD_IDS.iter() {
+        assert_eq!(mutex_lock(id), -EINVAL);
+        assert_eq!(sys_mutex_unlock(id), -EINVAL);
+        assert_eq!(sys_semaphore_up(id), -EINVAL);
+        assert_eq!(semaphore_up_n(id, 2), -EINVAL);
+        assert_eq!(semaphore_down(id), -EINVAL);
+        assert_eq!(semaphore_down_n(id, 2), -EINVAL);
+        assert_eq!(sys_condvar_signal(id), -EINVAL);
+        assert_eq!(condvar_signal_n(id, 1), -EINVAL);
+        assert_eq!(condvar_signal_tid(id, 0), -EINVAL);
+        assert_eq!(sys_condvar_wait(id, mutex), -EINVAL);
+        assert_eq!(sys_condvar_wait(condvar, id), -EINVAL);
+    }
+
+    // a destroyed condvar is refused the same way
+    assert_eq!(condvar_destroy(condvar), 0);
+    assert_eq!(mutex_lock(mutex), 0);
+    assert_eq!(sys_condvar_signal(condvar), -EINVAL);
+    assert_eq!(sys_condvar_wait(condvar, mutex), -EINVAL);
+
+    // the valid ids still work
+    assert_eq!(sys_mutex_unlock(mutex), 0);
+    assert_eq!(semaphore_down(sem), 0);
+    assert_eq!(sys_semaphore_up(sem), 0);
+    println!("sync bad id test passed!");
+    0
+}
@@ -0,0 +1,69 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{exit, semaphore_create, semaphore_down, semaphore_up};
+use user_lib::{mutex_create, mutex_lock, mutex_unlock, thread_create, waittid, yield_};
+use user_lib::{sync_checkpoint, sync_restore};
+
+// 理想结果：其他线程未阻塞时不能恢复，暂停后恢复到检查点的状态；
+// 持有者或计数与当前不同的状态被拒绝，恢复后解锁并退出不会使内核崩溃
+
+const EBUSY: isize = 16;
+const EINVAL: isize = 22;
+
+static mut STOP: bool = false;
+
+unsafe fn spinner() -> ! {
+    while !STOP {
+        yield_();
+    }
+    exit(0)
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let sem = semaphore_create(1) as usize;
+    let mutex = mutex_create() as usize;
+    let tid = thread_create(spinner as usize, 0) as usize;
+
+    let mut small = [0usize; 2];
+    assert_eq!(sync_checkpoint(&mut small), -EINVAL);
+    let mut saved = [0usize; 64];
+    let n = sync_checkpoint(&mut saved) as usize;
+    assert!(n > 3 && n <= saved.len());
+
+    semaphore_down(sem);
+    mutex_lock(mutex);
+    let mut changed = [0usize; 64];
+    assert_eq!(sync_checkpoint(&mut changed) as usize, n);
+    assert_ne!(changed[..n], saved[..n]);
+    mutex_unlock(mutex);
+
+    // the spinner is still runnable
+    assert_eq!(sync_restore(&saved[..n]), -EBUSY);
+    unsafe {
+        STOP = true;
+    }
+    waittid(tid);
+    assert_eq!(sync_restore(&saved[..n - 1]), -EINVAL);
+    // the semaphore is still held, unlike at the checkpoint
+    assert_eq!(sync_restore(&saved[..n]), -EINVAL);
+    semaphore_up(sem);
+    assert_eq!(sync_restore(&saved[..n]), 0);
+    let mut restored = [0usize; 64];
+    assert_eq!(sync_checkpoint(&mut restored) as usize, n);
+    assert_eq!(restored[..n], saved[..n]);
+
+    // a mutex owner that is not the real one is refused, so that exit does
+    // not unlock a mutex nobody holds
+    mutex_lock(mutex);
+    assert_eq!(sync_checkpoint(&mut changed) as usize, n);
+    assert_eq!(sync_restore(&changed[..n]), 0);
+    mutex_unlock(mutex);
+    assert_eq!(sync_restore(&changed[..n]), -EINVAL);
+    println!("sync checkpoint test passed!");
+    0
+}
@@ -0,0 +1,53 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{condvar_create, condvar_destroy, condvar_signal_n};
+use user_lib::{mutex_blocking_create, mutex_lock, mutex_unlock, sync_wait_time};
+use user_lib::{sys_condvar_signal, sys_condvar_wait, QUOTA_CONDVAR, SYNC_ID_GEN_SHIFT};
+
+// 理想结果：条件变量销毁后重新创建时复用槽位但 id 不同，持有旧 id 的调用返回 -EINVAL，
+// 不会误操作新的条件变量；新 id 的低位仍是槽位号
+
+const EINVAL: isize = 22;
+const ROUNDS: usize = 3;
+
+fn slot(id: usize) -> usize {
+    id & ((1 << SYNC_ID_GEN_SHIFT) - 1)
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let mutex = mutex_blocking_create() as usize;
+    let first = condvar_create() as usize;
+    // a fresh slot starts at generation 0, the id is the plain index
+    assert_eq!(slot(first), first);
+
+    let mut stale = first;
+    for _ in 0..ROUNDS {
+        assert_eq!(condvar_destroy(stale), 0);
+        let fresh = condvar_create();
+        assert!(fresh > 0);
+        let fresh = fresh as usize;
+        assert_eq!(slot(fresh), slot(first));
+        assert_ne!(fresh, stale);
+
+        assert_eq!(sys_condvar_signal(stale), -EINVAL);
+        assert_eq!(condvar_signal_n(stale, 1), -EINVAL);
+        assert_eq!(sync_wait_time(QUOTA_CONDVAR, stale), -1);
+        assert_eq!(condvar_destroy(stale), -1);
+        assert_eq!(mutex_lock(mutex), 0);
+        assert_eq!(sys_condvar_wait(stale, mutex), -EINVAL);
+        mutex_unlock(mutex);
+
+        assert_eq!(sys_condvar_signal(fresh), 0);
+        assert_eq!(sync_wait_time(QUOTA_CONDVAR, fresh), 0);
+        stale = fresh;
+    }
+    assert_eq!(condvar_destroy(first), -1);
+    assert_eq!(condvar_destroy(stale), 0);
+    println!("sync generation test passed!");
+    0
+}
@@ -0,0 +1,38 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{condvar_create, mutex_create, semaphore_create, set_sync_quota};
+use user_lib::{QUOTA_CONDVAR, QUOTA_MUTEX, QUOTA_SEMAPHORE};
+
+// 理想结果：每类同步对象超过配额后创建失败，调整配额后可以继续创建
+
+const DEFAULT_SYNC_QUOTA: usize = 128;
+
+#[no_mangle]
+pub fn main() -> i32 {
+    for i in 0..DEFAULT_SYNC_QUOTA {
+        assert_eq!(mutex_create(), i as isize);
+    }
+    assert_eq!(mutex_create(), -1);
+    assert_eq!(set_sync_quota(QUOTA_MUTEX, DEFAULT_SYNC_QUOTA + 2), 0);
+    assert!(mutex_create() >= 0);
+    assert!(mutex_create() >= 0);
+    assert_eq!(mutex_create(), -1);
+
+    assert_eq!(set_sync_quota(QUOTA_SEMAPHORE, 2), 0);
+    assert!(semaphore_create(1) >= 0);
+    assert!(semaphore_create(1) >= 0);
+    assert_eq!(semaphore_create(1), -1);
+
+    assert_eq!(set_sync_quota(QUOTA_CONDVAR, 0), 0);
+    assert_eq!(condvar_create(), -1);
+    assert_eq!(set_sync_quota(QUOTA_CONDVAR, 1), 0);
+    assert!(condvar_create() >= 0);
+
+    assert_eq!(set_sync_quota(3, 1), -1);
+    println!("sync quota test passed!");
+    0
+}
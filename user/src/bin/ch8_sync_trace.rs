@@ -0,0 +1,98 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+extern crate alloc;
+
+use alloc::vec::Vec;
+use user_lib::{condvar_create, condvar_signal, mutex_blocking_create, mutex_lock, mutex_unlock};
+use user_lib::{exit, fork, getpid, gettid, semaphore_create, semaphore_down, semaphore_up};
+use user_lib::{set_deadlock_checks, sync_trace_read, waitpid, SyncEvent, SYNC_TRACE};
+use user_lib::{SYNC_DOWN, SYNC_LOCK, SYNC_SIGNAL, SYNC_UNLOCK, SYNC_UP};
+
+// 理想结果：按顺序读到开启跟踪后的加锁事件，缓冲区满时只保留最近的事件；
+// 记录为所有进程共用，父进程能读到子进程的事件
+
+const TRACE_LEN: usize = 256;
+
+/// Drain the trace into `events`, keeping the events of process `pid`
+fn read_of(pid: usize, events: &mut [SyncEvent]) -> Vec<SyncEvent> {
+    let n = sync_trace_read(events) as usize;
+    events[..n]
+        .iter()
+        .filter(|e| e.pid == pid)
+        .copied()
+        .collect()
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let mutex = mutex_blocking_create() as usize;
+    let sem = semaphore_create(1) as usize;
+    let condvar = condvar_create() as usize;
+    let pid = getpid() as usize;
+    let tid = gettid() as usize;
+    let mut events = [SyncEvent::default(); 8];
+
+    // nothing is recorded until tracing is enabled
+    mutex_lock(mutex);
+    mutex_unlock(mutex);
+    assert!(read_of(pid, &mut events).is_empty());
+
+    assert_eq!(set_deadlock_checks(SYNC_TRACE), 0);
+    mutex_lock(mutex);
+    semaphore_down(sem);
+    condvar_signal(condvar);
+    semaphore_up(sem);
+    mutex_unlock(mutex);
+    let expected = [
+        (SYNC_LOCK, mutex),
+        (SYNC_DOWN, sem),
+        (SYNC_SIGNAL, condvar),
+        (SYNC_UP, sem),
+        (SYNC_UNLOCK, mutex),
+    ];
+    let ours = read_of(pid, &mut events);
+    assert_eq!(ours.len(), expected.len());
+    for (event, &(op, id)) in ours.iter().zip(expected.iter()) {
+        assert_eq!((event.tid, event.op, event.id), (tid, op, id));
+    }
+    for pair in ours.windows(2) {
+        assert!(pair[0].time_us <= pair[1].time_us);
+    }
+    // the buffer was drained
+    assert!(read_of(pid, &mut events).is_empty());
+
+    // overflow the ring, the most recent events survive in order
+    for _ in 0..TRACE_LEN {
+        mutex_lock(mutex);
+        mutex_unlock(mutex);
+    }
+    semaphore_down(sem);
+    semaphore_up(sem);
+    let ours = read_of(pid, &mut events);
+    assert!(ours.len() >= 4);
+    let last = &ours[ours.len() - 4..];
+    assert_eq!((last[0].op, last[1].op), (SYNC_LOCK, SYNC_UNLOCK));
+    assert_eq!((last[2].op, last[3].op), (SYNC_DOWN, SYNC_UP));
+    set_deadlock_checks(0);
+
+    // the log is shared, the parent reads what its child recorded
+    let child = fork();
+    if child == 0 {
+        let mutex = mutex_blocking_create() as usize;
+        assert_eq!(set_deadlock_checks(SYNC_TRACE), 0);
+        mutex_lock(mutex);
+        mutex_unlock(mutex);
+        exit(mutex as i32);
+    }
+    let mut child_mutex = 0;
+    assert_eq!(waitpid(child as usize, &mut child_mutex), child);
+    let theirs = read_of(child as usize, &mut events);
+    let ops: Vec<_> = theirs.iter().map(|e| (e.op, e.id)).collect();
+    let child_mutex = child_mutex as usize;
+    assert_eq!(ops, [(SYNC_LOCK, child_mutex), (SYNC_UNLOCK, child_mutex)]);
+    println!("sync trace test passed!");
+    0
+}
@@ -0,0 +1,69 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{exit, gettid, mutex_blocking_create, mutex_lock, mutex_unlock, semaphore_create};
+use user_lib::{semaphore_down, semaphore_up, sleep_blocking, sync_wait_time, task_stats};
+use user_lib::{thread_create, waittid, TaskStats, QUOTA_MSGQUEUE, QUOTA_MUTEX, QUOTA_SEMAPHORE};
+
+// 理想结果：等待互斥锁和信号量的阻塞时间分别计入线程和对应的同步对象，睡眠时间单独统计
+
+const HOLD_MS: usize = 50;
+/// Leave some slack for the timer granularity
+const MIN_WAIT_US: usize = HOLD_MS * 1000 * 4 / 5;
+
+static mut MUTEX: usize = 0;
+static mut SEM: usize = 0;
+static mut WAITER_STATS: TaskStats = TaskStats {
+    switch_count: 0,
+    run_time_us: 0,
+    blocked_time_us: 0,
+    sync_wait_us: 0,
+    sleep_us: 0,
+};
+
+unsafe fn waiter() -> ! {
+    assert_eq!(mutex_lock(MUTEX), 0);
+    mutex_unlock(MUTEX);
+    assert_eq!(semaphore_down(SEM), 0);
+    task_stats(gettid() as usize, &mut WAITER_STATS);
+    exit(0)
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let mutex = mutex_blocking_create() as usize;
+    let sem = semaphore_create(0) as usize;
+    unsafe {
+        MUTEX = mutex;
+        SEM = sem;
+    }
+    assert_eq!(sync_wait_time(QUOTA_MUTEX, mutex), 0);
+    assert_eq!(sync_wait_time(QUOTA_MUTEX, mutex + 100), -1);
+    assert_eq!(sync_wait_time(QUOTA_MSGQUEUE, 0), -1);
+
+    assert_eq!(mutex_lock(mutex), 0);
+    let thread = thread_create(waiter as usize, 0) as usize;
+    sleep_blocking(HOLD_MS);
+    mutex_unlock(mutex);
+    sleep_blocking(HOLD_MS);
+    semaphore_up(sem);
+    waittid(thread);
+
+    assert!(sync_wait_time(QUOTA_MUTEX, mutex) as usize >= MIN_WAIT_US);
+    assert!(sync_wait_time(QUOTA_SEMAPHORE, sem) as usize >= MIN_WAIT_US);
+    let waiter_stats = unsafe { &WAITER_STATS };
+    assert!(waiter_stats.sync_wait_us >= 2 * MIN_WAIT_US);
+    assert!(waiter_stats.sync_wait_us <= waiter_stats.blocked_time_us);
+    assert_eq!(waiter_stats.sleep_us, 0);
+
+    // the main thread only slept
+    let mut stats = TaskStats::default();
+    task_stats(gettid() as usize, &mut stats);
+    assert!(stats.sleep_us >= 2 * MIN_WAIT_US);
+    assert_eq!(stats.sync_wait_us, 0);
+    println!("sync wait time test passed!");
+    0
+}
@@ -0,0 +1,35 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{exit, gettid, sleep_blocking, task_stats, TaskStats};
+use user_lib::{thread_create, waittid};
+
+// 理想结果：睡眠中的线程累计了阻塞时间，当前线程累计了运行时间
+
+fn sleeper() -> ! {
+    sleep_blocking(200);
+    exit(0)
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let tid = thread_create(sleeper as usize, 0) as usize;
+    sleep_blocking(100);
+    let mut stats = TaskStats::default();
+    assert_eq!(task_stats(tid, &mut stats), 0);
+    assert!(stats.switch_count >= 1);
+    assert!(stats.blocked_time_us >= 50_000);
+
+    let mut own = TaskStats::default();
+    assert_eq!(task_stats(gettid() as usize, &mut own), 0);
+    assert!(own.switch_count >= 2);
+    assert!(own.run_time_us > 0);
+
+    assert_eq!(task_stats(100, &mut stats), -1);
+    waittid(tid);
+    println!("task stats test passed!");
+    0
+}
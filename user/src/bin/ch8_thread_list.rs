@@ -0,0 +1,38 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{exit, sleep_blocking, thread_count, thread_list};
+use user_lib::{thread_create, waittid};
+
+// 理想结果：只列出尚未退出的线程
+
+fn quick() -> ! {
+    exit(0)
+}
+
+fn slow() -> ! {
+    sleep_blocking(200);
+    exit(0)
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    assert_eq!(thread_count(), 1);
+    let quick_tid = thread_create(quick as usize, 0) as usize;
+    let slow_tid = thread_create(slow as usize, 0) as usize;
+    // let the quick thread exit, it stays unreaped until waittid
+    sleep_blocking(50);
+    assert_eq!(thread_count(), 2);
+    let mut tids = [usize::MAX; 4];
+    assert_eq!(thread_list(&mut tids), 2);
+    assert_eq!(&tids[..2], &[0, slow_tid]);
+    assert_eq!(thread_list(&mut tids[..1]), 1);
+    waittid(quick_tid);
+    waittid(slow_tid);
+    assert_eq!(thread_count(), 1);
+    println!("thread list test passed!");
+    0
+}
@@ -0,0 +1,57 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use core::ptr::{read_volatile, write_volatile};
+use user_lib::{exit, thread_create_with_stack, waittid, yield_};
+
+// 理想结果：8KiB 与 64KiB 栈的线程都能用满各自的栈且互不覆盖，过大的栈返回 -EINVAL
+
+const EINVAL: isize = 22;
+const KIB: usize = 1024;
+const MAX_USER_STACK_SIZE: usize = 256 * KIB;
+
+/// Fill `buf` on the stack with `seed`, let the others run, check it
+fn fill_and_check(buf: &mut [u8], seed: usize) {
+    for (i, byte) in buf.iter_mut().enumerate() {
+        unsafe { write_volatile(byte, (seed + i) as u8) };
+    }
+    for _ in 0..4 {
+        yield_();
+    }
+    for (i, byte) in buf.iter().enumerate() {
+        assert_eq!(unsafe { read_volatile(byte) }, (seed + i) as u8);
+    }
+}
+
+fn small(seed: usize) -> ! {
+    fill_and_check(&mut [0u8; 6 * KIB], seed);
+    exit(0)
+}
+
+fn large(seed: usize) -> ! {
+    fill_and_check(&mut [0u8; 56 * KIB], seed);
+    exit(0)
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let threads = [
+        thread_create_with_stack(small as usize, 1, 8 * KIB),
+        thread_create_with_stack(large as usize, 2, 64 * KIB),
+        thread_create_with_stack(large as usize, 3, 63 * KIB),
+        thread_create_with_stack(small as usize, 4, 8 * KIB),
+    ];
+    for tid in threads {
+        assert!(tid > 0);
+        assert_eq!(waittid(tid as usize), 0);
+    }
+    assert_eq!(
+        thread_create_with_stack(small as usize, 0, MAX_USER_STACK_SIZE + 1),
+        -EINVAL
+    );
+    println!("thread stack test passed!");
+    0
+}
@@ -0,0 +1,90 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use core::ptr::{read_volatile, write_volatile};
+use user_lib::{exit, semaphore_create, semaphore_down, semaphore_up, sleep_blocking};
+use user_lib::{thread_create, thread_resume, thread_suspend, waittid, yield_};
+
+// 理想结果：挂起正在空转的线程后它不再前进，resume 后继续；
+// 挂起阻塞中的线程要等它被唤醒后才生效，resume 之前不会运行；无效 tid 返回 -EINVAL
+
+const EINVAL: isize = 22;
+const PAUSE_MS: usize = 20;
+
+static mut COUNT: usize = 0;
+static mut STOP: bool = false;
+static mut SEM: usize = 0;
+static mut WOKEN: bool = false;
+
+unsafe fn spinner() -> ! {
+    while !read_volatile(&STOP) {
+        write_volatile(&mut COUNT, read_volatile(&COUNT) + 1);
+        yield_();
+    }
+    exit(0)
+}
+
+unsafe fn sleeper() -> ! {
+    assert_eq!(semaphore_down(SEM), 0);
+    write_volatile(&mut WOKEN, true);
+    exit(0)
+}
+
+fn count() -> usize {
+    unsafe { read_volatile(&COUNT) }
+}
+
+/// Give other threads every chance to run for a while
+fn pause() {
+    for _ in 0..10 {
+        yield_();
+    }
+    sleep_blocking(PAUSE_MS);
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    assert_eq!(thread_suspend(100), -EINVAL);
+    assert_eq!(thread_resume(100), -EINVAL);
+
+    let thread = thread_create(spinner as usize, 0) as usize;
+    while count() == 0 {
+        yield_();
+    }
+    assert_eq!(thread_suspend(thread), 0);
+    // suspending twice is the same as once
+    assert_eq!(thread_suspend(thread), 0);
+    let frozen = count();
+    pause();
+    assert_eq!(count(), frozen);
+    assert_eq!(thread_resume(thread), 0);
+    while count() == frozen {
+        yield_();
+    }
+    unsafe {
+        write_volatile(&mut STOP, true);
+    }
+    assert_eq!(waittid(thread), 0);
+    assert_eq!(thread_suspend(thread), -EINVAL);
+
+    // blocked, it is only suspended once woken
+    unsafe {
+        SEM = semaphore_create(0) as usize;
+    }
+    let thread = thread_create(sleeper as usize, 0) as usize;
+    pause();
+    assert_eq!(thread_suspend(thread), 0);
+    unsafe {
+        semaphore_up(SEM);
+    }
+    pause();
+    assert!(unsafe { !read_volatile(&WOKEN) });
+    assert_eq!(thread_resume(thread), 0);
+    assert_eq!(waittid(thread), 0);
+    assert!(unsafe { read_volatile(&WOKEN) });
+    println!("thread suspend test passed!");
+    0
+}
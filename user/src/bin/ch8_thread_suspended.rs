@@ -0,0 +1,42 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use core::ptr::{read_volatile, write_volatile};
+use user_lib::{exit, getpid, proc_threads, thread_create_suspended, thread_resume, waittid};
+use user_lib::{yield_, ThreadEntry, THREAD_BLOCKED};
+
+// 理想结果：挂起创建的线程在 resume 之前不会运行，resume 正在运行的线程返回 0，无效 tid 返回 -EINVAL
+
+const EINVAL: isize = 22;
+
+static mut RAN: bool = false;
+
+fn child() -> ! {
+    unsafe { write_volatile(&mut RAN, true) };
+    exit(7)
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let tid = thread_create_suspended(child as usize, 0) as usize;
+    for _ in 0..16 {
+        yield_();
+    }
+    assert!(unsafe { !read_volatile(&RAN) });
+    let mut threads = [ThreadEntry::default(); 2];
+    assert_eq!(proc_threads(getpid() as usize, &mut threads), 2);
+    assert_eq!((threads[1].tid, threads[1].state), (tid, THREAD_BLOCKED));
+
+    assert_eq!(thread_resume(tid), 0);
+    // already resumed, nothing happens
+    assert_eq!(thread_resume(tid), 0);
+    assert_eq!(thread_resume(0), 0);
+    assert_eq!(waittid(tid), 7);
+    assert!(unsafe { read_volatile(&RAN) });
+    assert_eq!(thread_resume(tid + 100), -EINVAL);
+    println!("thread suspended test passed!");
+    0
+}
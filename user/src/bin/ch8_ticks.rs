@@ -0,0 +1,30 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{get_ticks, get_time, TICKS_PER_SEC};
+
+// 理想结果：计数单调不减，忙等一段时间后增加的中断次数不少于按 TICKS_PER_SEC 换算的一半
+
+const SPIN_MS: isize = 200;
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let start_ticks = get_ticks();
+    assert!(start_ticks >= 0);
+    let start = get_time();
+    let mut last = start_ticks;
+    while get_time() - start < SPIN_MS {
+        let now = get_ticks();
+        assert!(now >= last);
+        last = now;
+    }
+    // running user code all along, the timer fires at least once a tick
+    let expected = SPIN_MS as usize * TICKS_PER_SEC / 1000;
+    let taken = (get_ticks() - start_ticks) as usize;
+    assert!(taken >= expected / 2, "{} ticks in {} ms", taken, SPIN_MS);
+    println!("ticks test passed!");
+    0
+}
@@ -0,0 +1,44 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{get_time, gettid, set_timeslice, task_stats, yield_, TaskStats};
+
+// 理想结果：时间片长度被限制在合法范围内；长时间片内忙等不被抢占，短时间片下被多次抢占
+
+const DEFAULT_TIMESLICE: usize = 10;
+const SPIN_MS: isize = 200;
+
+/// Busy-wait for `SPIN_MS` from the start of a fresh quantum, return how
+/// many times the thread was switched out meanwhile
+fn switches_while_spinning() -> usize {
+    let tid = gettid() as usize;
+    let mut stats = TaskStats::default();
+    yield_();
+    task_stats(tid, &mut stats);
+    let before = stats.switch_count;
+    let start = get_time();
+    while get_time() - start < SPIN_MS {}
+    task_stats(tid, &mut stats);
+    stats.switch_count - before
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    assert_eq!(set_timeslice(50), 50);
+    assert_eq!(set_timeslice(0), 1);
+    assert_eq!(set_timeslice(usize::MAX), 1000);
+
+    // the whole spin fits in one 1000ms quantum
+    let long = switches_while_spinning();
+    assert_eq!(set_timeslice(1), 1);
+    let short = switches_while_spinning();
+    assert_eq!(set_timeslice(DEFAULT_TIMESLICE), DEFAULT_TIMESLICE as isize);
+    assert_eq!(long, 0);
+    // preempted every millisecond or so, allow for a coarse timer
+    assert!(short >= 10);
+    println!("timeslice test passed!");
+    0
+}
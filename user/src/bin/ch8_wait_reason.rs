@@ -0,0 +1,75 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+extern crate alloc;
+
+use alloc::vec;
+use alloc::vec::Vec;
+use user_lib::{enable_sched_trace, exit, getpid, mutex_blocking_create, mutex_lock};
+use user_lib::{mutex_unlock, sched_trace, semaphore_create, semaphore_down, semaphore_up};
+use user_lib::{sleep_blocking, thread_blocked_on, thread_create, thread_set_waitreason, waittid};
+use user_lib::{yield_, SchedEvent, BLOCKED_ON_SEMAPHORE, SCHED_BLOCK, WAIT_REASON_LEN};
+
+// 理想结果：线程阻塞前设置的等待原因出现在调度记录中，被唤醒后自动清除；
+// 过长或含 NUL 的原因返回 -EINVAL
+
+const EINVAL: isize = 22;
+const REASON: &str = "waiting for DB lock";
+
+static mut MUTEX: usize = 0;
+static mut SEM: usize = 0;
+
+unsafe fn waiter() -> ! {
+    assert_eq!(thread_set_waitreason(REASON), 0);
+    assert_eq!(mutex_lock(MUTEX), 0);
+    mutex_unlock(MUTEX);
+    // woken, the reason is gone for this wait
+    assert_eq!(semaphore_down(SEM), 0);
+    exit(0)
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let too_long = [b'x'; WAIT_REASON_LEN + 1];
+    let too_long = core::str::from_utf8(&too_long).unwrap();
+    assert_eq!(thread_set_waitreason(too_long), -EINVAL);
+    assert_eq!(thread_set_waitreason(&too_long[1..]), 0);
+    assert_eq!(thread_set_waitreason("a\0b"), -EINVAL);
+    assert_eq!(thread_set_waitreason(""), 0);
+
+    let (mutex, sem) = unsafe {
+        MUTEX = mutex_blocking_create() as usize;
+        SEM = semaphore_create(0) as usize;
+        (MUTEX, SEM)
+    };
+    let pid = getpid() as usize;
+    assert_eq!(enable_sched_trace(true), 0);
+    assert_eq!(mutex_lock(mutex), 0);
+    let thread = thread_create(waiter as usize, 0) as usize;
+    sleep_blocking(10);
+    mutex_unlock(mutex);
+    while thread_blocked_on(thread) != BLOCKED_ON_SEMAPHORE | sem as isize {
+        yield_();
+    }
+    assert_eq!(enable_sched_trace(false), 0);
+    semaphore_up(sem);
+    assert_eq!(waittid(thread), 0);
+
+    let mut events = vec![SchedEvent::default(); 64];
+    let n = sched_trace(&mut events) as usize;
+    let reasons: Vec<&str> = events[..n]
+        .iter()
+        .filter(|e| e.from_pid == pid && e.from_tid == thread && e.reason == SCHED_BLOCK)
+        .map(|e| e.wait_reason())
+        .collect();
+    assert_eq!(reasons, [REASON, ""]);
+    // the main thread set none
+    assert!(events[..n]
+        .iter()
+        .filter(|e| e.from_pid == pid && e.from_tid != thread)
+        .all(|e| e.wait_reason().is_empty()));
+    println!("wait reason test passed!");
+    0
+}
@@ -0,0 +1,49 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use core::ptr::{read_volatile, write_volatile};
+use user_lib::{exit, sched_info, sleep_blocking, thread_create, waittid, yield_, SchedInfo};
+
+// 理想结果：唯一可运行的线程不断让出 CPU 等待睡眠线程设置标志时，CPU 在时钟中断之间空闲，
+// 让出次数与经过的时钟节拍数同一量级，空闲时间接近睡眠时长
+
+const SLEEP_MS: usize = 100;
+/// At most a few yields per tick, rather than one per trip through the kernel
+const MAX_YIELDS: usize = SLEEP_MS;
+
+static mut DONE: bool = false;
+
+fn sleeper() -> ! {
+    sleep_blocking(SLEEP_MS);
+    unsafe {
+        write_volatile(&mut DONE, true);
+    }
+    exit(0)
+}
+
+fn idle_us() -> usize {
+    let mut info = SchedInfo::default();
+    assert_eq!(sched_info(&mut info), 0);
+    info.idle_us
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let start_idle_us = idle_us();
+    let thread = thread_create(sleeper as usize, 0) as usize;
+    let mut yields = 0;
+    while unsafe { !read_volatile(&DONE) } {
+        yield_();
+        yields += 1;
+    }
+    waittid(thread);
+    let idle_us = idle_us() - start_idle_us;
+    println!("{} yields, idle for {} us", yields, idle_us);
+    assert!(yields <= MAX_YIELDS);
+    assert!(idle_us >= SLEEP_MS * 1000 / 2);
+    println!("yield idle test passed!");
+    0
+}
@@ -0,0 +1,33 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{exit, gettid, yield_to};
+use user_lib::{thread_create, waittid};
+
+// 理想结果：yield_to 让目标线程先于调用者运行，目标无效时返回 -1
+
+static mut RAN: bool = false;
+
+fn target() -> ! {
+    unsafe {
+        RAN = true;
+    }
+    exit(0)
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let tid = thread_create(target as usize, 0) as usize;
+    assert_eq!(yield_to(tid), 0);
+    assert!(unsafe { RAN });
+    waittid(tid);
+    // an exited thread, the caller itself and a missing tid are not runnable
+    assert_eq!(yield_to(tid), -1);
+    assert_eq!(yield_to(gettid() as usize), -1);
+    assert_eq!(yield_to(100), -1);
+    println!("yield_to test passed!");
+    0
+}
@@ -14,6 +14,7 @@ extern crate core;
 extern crate bitflags;
 
 use alloc::vec::Vec;
+use core::sync::atomic::AtomicU32;
 use buddy_system_allocator::LockedHeap;
 pub use console::{flush, STDIN, STDOUT};
 pub use syscall::*;
@@ -82,6 +83,11 @@ bitflags! {
         const RDWR = 1 << 1;
         const CREATE = 1 << 9;
         const TRUNC = 1 << 10;
+        const APPEND = 1 << 11;
+        /// Only taken by `pipe2`
+        const NONBLOCK = 1 << 12;
+        /// With `CREATE`, fail with -EEXIST if the file already exists
+        const EXCL = 1 << 13;
     }
 }
 
@@ -131,6 +137,144 @@ impl TaskInfo {
     }
 }
 
+#[repr(C)]
+#[derive(Debug, Default)]
+pub struct TaskStats {
+    pub switch_count: usize,
+    pub run_time_us: usize,
+    pub blocked_time_us: usize,
+    pub sync_wait_us: usize,
+    pub sleep_us: usize,
+}
+
+pub const THREAD_RUNNING: u8 = 0;
+pub const THREAD_READY: u8 = 1;
+pub const THREAD_BLOCKED: u8 = 2;
+
+/// A live thread of a process, filled by [`proc_threads`]
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ThreadEntry {
+    pub tid: usize,
+    pub state: u8,
+}
+
+/// One operation of [`semaphore_op`], `delta` < 0 acquires and > 0 releases
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct SemOp {
+    pub sem_id: usize,
+    pub delta: isize,
+}
+
+/// One lock event read by [`sync_trace_read`], of any process
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SyncEvent {
+    pub time_us: usize,
+    pub pid: usize,
+    pub tid: usize,
+    pub op: usize,
+    pub id: usize,
+}
+
+pub const SYNC_LOCK: usize = 1;
+pub const SYNC_UNLOCK: usize = 2;
+pub const SYNC_DOWN: usize = 3;
+pub const SYNC_UP: usize = 4;
+pub const SYNC_SIGNAL: usize = 5;
+pub const SYNC_WAIT: usize = 6;
+
+/// One fd of [`poll`]
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct PollFd {
+    pub fd: usize,
+    pub events: usize,
+    pub revents: usize,
+}
+
+pub const POLLIN: usize = 0x1;
+pub const POLLOUT: usize = 0x4;
+pub const POLLERR: usize = 0x8;
+pub const POLLHUP: usize = 0x10;
+pub const POLLNVAL: usize = 0x20;
+
+/// One context switch read by [`sched_trace`]
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SchedEvent {
+    pub tick: usize,
+    pub from_pid: usize,
+    pub from_tid: usize,
+    pub to_pid: usize,
+    pub to_tid: usize,
+    pub reason: usize,
+    wait_reason: [u8; WAIT_REASON_LEN],
+}
+
+impl SchedEvent {
+    /// Wait reason the thread leaving had set when it blocked, empty if none
+    pub fn wait_reason(&self) -> &str {
+        let reason = &self.wait_reason;
+        let len = reason.iter().position(|&b| b == 0).unwrap_or(reason.len());
+        core::str::from_utf8(&reason[..len]).unwrap()
+    }
+}
+
+pub const SCHED_YIELD: usize = 1;
+pub const SCHED_BLOCK: usize = 2;
+pub const SCHED_PREEMPT: usize = 3;
+pub const SCHED_EXIT: usize = 4;
+pub const SCHED_NONE: usize = usize::MAX;
+
+/// Work done by the deadlock detector, filled by [`deadlock_stats`]
+#[repr(C)]
+#[derive(Debug, Default)]
+pub struct DeadlockStats {
+    pub runs: usize,
+    pub deadlocks: usize,
+    pub nodes_visited: usize,
+    /// Deadlocks not printed by the kernel, being the same as the last one
+    pub reports_suppressed: usize,
+    /// Lock order warnings printed by the kernel in `LOCKDEP` mode
+    pub lockdep_warnings: usize,
+}
+
+/// Contention of a mutex, filled by [`mutex_stats`]
+#[repr(C)]
+#[derive(Debug, Default)]
+pub struct MutexStats {
+    /// Locks that found it free
+    pub immediate: usize,
+    /// Locks that had to wait for it
+    pub blocked: usize,
+    /// Threads waiting for it now
+    pub waiters: usize,
+}
+
+/// System-wide scheduler load, filled by [`sched_info`]
+#[repr(C)]
+#[derive(Debug, Default)]
+pub struct SchedInfo {
+    pub ready: usize,
+    pub blocked: usize,
+    /// Blocked threads a timer will wake
+    pub sleeping: usize,
+    /// Microseconds the CPU idled since boot
+    pub idle_us: usize,
+}
+
+#[repr(C)]
+#[derive(Debug, Default)]
+pub struct RUsage {
+    pub utime_us: usize,
+    pub nvcsw: usize,
+    pub nivcsw: usize,
+    pub page_faults: usize,
+    pub max_frames: usize,
+}
+
 #[repr(C)]
 #[derive(Debug)]
 pub struct Stat {
@@ -142,8 +286,10 @@ pub struct Stat {
     pub mode: StatMode,
     /// number of hard links
     pub nlink: u32,
+    /// size of the file in bytes
+    pub size: u64,
     /// unused pad
-    pad: [u64; 7],
+    pad: [u64; 6],
 }
 
 impl Stat {
@@ -153,7 +299,8 @@ impl Stat {
             ino: 0,
             mode: StatMode::NULL,
             nlink: 0,
-            pad: [0; 7],
+            size: 0,
+            pad: [0; 6],
         }
     }
 }
@@ -171,6 +318,31 @@ bitflags! {
         const DIR   = 0o040000;
         /// ordinary regular file
         const FILE  = 0o100000;
+        /// pipe
+        const FIFO  = 0o010000;
+        /// character device, i.e. the console
+        const CHR   = 0o020000;
+    }
+}
+
+/// A directory entry filled by `readdir`
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct DirEntry {
+    name: [u8; 28],
+    pub inode: u32,
+}
+
+impl DirEntry {
+    pub fn empty() -> Self {
+        Self {
+            name: [0; 28],
+            inode: 0,
+        }
+    }
+    pub fn name(&self) -> &str {
+        let len = self.name.iter().position(|&b| b == 0).unwrap_or(self.name.len());
+        core::str::from_utf8(&self.name[..len]).unwrap()
     }
 }
 
@@ -207,6 +379,10 @@ pub fn fstat(fd: usize, st: &Stat) -> isize {
     sys_fstat(fd, st)
 }
 
+pub fn readdir(path: &str, entries: &mut [DirEntry]) -> isize {
+    sys_readdir(path, entries)
+}
+
 pub fn mail_read(buf: &mut [u8]) -> isize {
     sys_mail_read(buf)
 }
@@ -219,11 +395,20 @@ pub fn exit(exit_code: i32) -> ! {
     console::flush();
     sys_exit(exit_code);
 }
+/// End every thread of the process, which exits with `exit_code`
+pub fn exit_group(exit_code: i32) -> ! {
+    console::flush();
+    sys_exit_group(exit_code);
+}
 
 pub fn yield_() -> isize {
     sys_yield()
 }
 
+pub fn yield_to(tid: usize) -> isize {
+    sys_yield_to(tid)
+}
+
 pub fn get_time() -> isize {
     let time = TimeVal::new();
     match sys_get_time(&time, 0) {
@@ -232,10 +417,35 @@ pub fn get_time() -> isize {
     }
 }
 
+/// Timer interrupts per second while user code keeps running
+pub const TICKS_PER_SEC: usize = 100;
+
+/// Timer interrupts taken since boot, at least one every `1 / TICKS_PER_SEC`
+/// seconds of user code, more when time slices end sooner
+pub fn get_ticks() -> isize {
+    sys_get_ticks()
+}
+
 pub fn getpid() -> isize {
     sys_getpid()
 }
 
+pub const SIGINT: usize = 2;
+pub const SIGKILL: usize = 9;
+pub const SIGUSR1: usize = 10;
+
+pub fn kill(pid: usize, signum: usize) -> isize {
+    sys_kill(pid, signum)
+}
+
+pub fn sigpending() -> isize {
+    sys_sigpending()
+}
+
+pub fn getppid() -> isize {
+    sys_getppid()
+}
+
 pub fn fork() -> isize {
     sys_fork()
 }
@@ -248,6 +458,31 @@ pub fn set_priority(prio: isize) -> isize {
     sys_set_priority(prio)
 }
 
+pub fn set_timeslice(ms: usize) -> isize {
+    sys_set_timeslice(ms)
+}
+
+pub fn set_aging_interval(ticks: usize) -> isize {
+    sys_set_aging_interval(ticks)
+}
+
+pub const SCHED_STRIDE: usize = 0;
+pub const SCHED_RR: usize = 1;
+pub const SCHED_PRIORITY: usize = 2;
+/// The seeded test scheduler, only entered with [`sched_set_seed`]
+pub const SCHED_SEEDED: usize = 3;
+
+/// Switch the system-wide scheduling policy, return the previous one
+pub fn sched_setpolicy(policy: usize) -> isize {
+    sys_sched_setpolicy(policy)
+}
+/// Pick among equal priority threads pseudo-randomly from `seed`, so that
+/// an interleaving can be replayed. Return the previous policy, or -EPERM
+/// unless the kernel was built with the `sched-seed` feature.
+pub fn sched_set_seed(seed: usize) -> isize {
+    sys_sched_set_seed(seed)
+}
+
 pub fn wait(exit_code: &mut i32) -> isize {
     loop {
         match sys_waitpid(-1, exit_code as *mut _) {
@@ -278,6 +513,10 @@ pub fn sleep_blocking(sleep_ms: usize) {
     sys_sleep(sleep_ms);
 }
 
+pub fn sleep_until(abs_ms: usize) {
+    sys_sleep_until(abs_ms);
+}
+
 pub fn sleep(period_ms: usize) {
     let start = get_time();
     while get_time() < start + period_ms as isize {
@@ -292,6 +531,11 @@ pub fn munmap(start: usize, len: usize) -> isize {
     sys_munmap(start, len)
 }
 
+/// Map a private read-only copy of `len` bytes of `fd` from `offset`
+pub fn mmap_file(start: usize, len: usize, prot: usize, fd: usize, offset: usize) -> isize {
+    sys_mmap_file(start, len, prot, fd, offset)
+}
+
 pub fn spawn(path: &str) -> isize {
     sys_spawn(path)
 }
@@ -299,16 +543,54 @@ pub fn spawn(path: &str) -> isize {
 pub fn dup(fd: usize) -> isize {
     sys_dup(fd)
 }
+pub fn dup2(old_fd: usize, new_fd: usize) -> isize {
+    sys_dup2(old_fd, new_fd)
+}
 pub fn pipe(pipe_fd: &mut [usize]) -> isize {
     sys_pipe(pipe_fd)
 }
+/// Wait until one of `fds` is ready or `timeout_ms` has elapsed, forever if
+/// negative, return how many are ready
+pub fn poll(fds: &mut [PollFd], timeout_ms: isize) -> isize {
+    sys_poll(fds, timeout_ms)
+}
+/// Like [`pipe`], `OpenFlags::NONBLOCK` makes both ends return -EAGAIN
+/// instead of blocking
+pub fn pipe2(pipe_fd: &mut [usize], flags: OpenFlags) -> isize {
+    sys_pipe2(pipe_fd, flags.bits)
+}
+
+pub const SEEK_SET: usize = 0;
+pub const SEEK_CUR: usize = 1;
+pub const SEEK_END: usize = 2;
+
+pub fn lseek(fd: usize, offset: isize, whence: usize) -> isize {
+    sys_lseek(fd, offset, whence)
+}
 
 pub fn task_info(info: &TaskInfo) -> isize {
     sys_task_info(info)
 }
 
 pub fn thread_create(entry: usize, arg: usize) -> isize {
-    sys_thread_create(entry, arg)
+    sys_thread_create(entry, arg, 0)
+}
+/// Create a thread with a user stack of `stack_size` bytes, rounded up to
+/// whole pages
+pub fn thread_create_with_stack(entry: usize, arg: usize, stack_size: usize) -> isize {
+    sys_thread_create(entry, arg, stack_size)
+}
+/// Create a thread that only starts running once [`thread_resume`] is called
+pub fn thread_create_suspended(entry: usize, arg: usize) -> isize {
+    sys_thread_create_suspended(entry, arg, 0)
+}
+/// Stop the thread from running until [`thread_resume`], a blocked thread
+/// once it is woken
+pub fn thread_suspend(tid: usize) -> isize {
+    sys_thread_suspend(tid)
+}
+pub fn thread_resume(tid: usize) -> isize {
+    sys_thread_resume(tid)
 }
 pub fn gettid() -> isize {
     sys_gettid()
@@ -324,36 +606,318 @@ pub fn waittid(tid: usize) -> isize {
     }
 }
 
+pub const MUTEX_SPIN: usize = 0;
+pub const MUTEX_BLOCKING: usize = 1;
+pub const MUTEX_ADAPTIVE: usize = 2;
+/// Blocking, but unlock does not hand the lock to the woken waiter
+pub const MUTEX_BARGING: usize = 3;
+
 pub fn mutex_create() -> isize {
-    sys_mutex_create(false)
+    sys_mutex_create(MUTEX_SPIN)
 }
 pub fn mutex_blocking_create() -> isize {
-    sys_mutex_create(true)
+    sys_mutex_create(MUTEX_BLOCKING)
+}
+pub fn mutex_adaptive_create() -> isize {
+    sys_mutex_create(MUTEX_ADAPTIVE)
+}
+pub fn mutex_barging_create() -> isize {
+    sys_mutex_create(MUTEX_BARGING)
+}
+/// Kind a mutex was created with, one of `MUTEX_*`, or -1 for a bad id
+pub fn mutex_kind(mutex_id: usize) -> isize {
+    sys_mutex_kind(mutex_id)
 }
 pub fn mutex_lock(mutex_id: usize) -> isize {
     sys_mutex_lock(mutex_id)
 }
+/// Try to lock the mutex at most `max_spins + 1` times, yielding in between,
+/// return 1 if it is still held by another thread, without ever blocking
+pub fn mutex_spinlock(mutex_id: usize, max_spins: usize) -> isize {
+    sys_mutex_spinlock(mutex_id, max_spins)
+}
+/// Lock the mutex without blocking: 0 once locked, 1 if held by another
+/// thread, `-0xdead` if waiting for it would deadlock (detection enabled)
+pub fn mutex_trylock_checked(mutex_id: usize) -> isize {
+    sys_mutex_trylock_checked(mutex_id)
+}
+/// Zero the counters of [`mutex_stats`] once read
+pub const MUTEX_STATS_RESET: usize = 1;
+
+pub fn mutex_stats(mutex_id: usize, stats: &mut MutexStats, flags: usize) -> isize {
+    sys_mutex_stats(mutex_id, stats, flags)
+}
 pub fn mutex_unlock(mutex_id: usize) {
     sys_mutex_unlock(mutex_id);
 }
+/// Unlock `mutex_id`, handing it to thread `tid` if it waits for it
+pub fn mutex_unlock_to(mutex_id: usize, tid: usize) {
+    sys_mutex_unlock_to(mutex_id, tid);
+}
 pub fn semaphore_create(res_count: usize) -> isize {
     sys_semaphore_create(res_count)
 }
 pub fn semaphore_up(sem_id: usize) {
     sys_semaphore_up(sem_id);
 }
+pub const DEADLOCK_DETECT: usize = 1;
+pub const LOCK_ORDER_CHECK: usize = 2;
+pub const LOCKDEP: usize = 4;
+pub const SYNC_TRACE: usize = 8;
+pub const DEADLOCK_CODES: usize = 16;
+pub const CONDVAR_STALL: usize = 32;
+pub const DEADLOCK_ABORT: usize = 64;
+
+/// Deadlock results, semaphores only use their own with `DEADLOCK_CODES`
+pub const DEADLOCK_MUTEX: isize = -0xdead;
+pub const DEADLOCK_SEMAPHORE: isize = -0xdeae;
+
 pub fn enable_deadlock_detect(enabled: bool) -> isize {
     sys_enable_deadlock_detect(enabled as usize)
 }
+pub fn set_deadlock_checks(flags: usize) -> isize {
+    sys_enable_deadlock_detect(flags)
+}
+pub fn deadlock_stats(stats: &mut DeadlockStats) -> isize {
+    sys_deadlock_stats(stats)
+}
+/// Number of threads deadlocked right now, without trying to lock anything
+pub fn check_deadlock() -> isize {
+    sys_check_deadlock()
+}
+pub fn sync_trace_read(events: &mut [SyncEvent]) -> isize {
+    sys_sync_trace_read(events)
+}
+pub const QUOTA_MUTEX: usize = 0;
+pub const QUOTA_SEMAPHORE: usize = 1;
+pub const QUOTA_CONDVAR: usize = 2;
+pub const QUOTA_MSGQUEUE: usize = 3;
+/// Condvar ids hold the slot index below this bit and the generation of the
+/// slot from it up
+pub const SYNC_ID_GEN_SHIFT: usize = 32;
+
+pub fn set_sync_quota(kind: usize, n: usize) -> isize {
+    sys_set_sync_quota(kind, n)
+}
+/// Microseconds spent blocked waiting for sync object `id` of a `QUOTA_*`
+/// kind, over all threads
+pub fn sync_wait_time(kind: usize, id: usize) -> isize {
+    sys_sync_wait_time(kind, id)
+}
+pub fn mutex_set_rank(mutex_id: usize, rank: usize) -> isize {
+    sys_mutex_set_rank(mutex_id, rank)
+}
+/// Run threads holding `mutex_id` at least at priority `prio`
+pub fn mutex_set_ceiling(mutex_id: usize, prio: usize) -> isize {
+    sys_mutex_set_ceiling(mutex_id, prio)
+}
 pub fn semaphore_down(sem_id: usize) -> isize {
     sys_semaphore_down(sem_id)
 }
+/// Release `n` resources at once, waking queued waiters in order while
+/// their requests fit
+pub fn semaphore_up_n(sem_id: usize, n: usize) -> isize {
+    sys_semaphore_up_n(sem_id, n)
+}
+/// Acquire `n` resources at once
+pub fn semaphore_down_n(sem_id: usize, n: usize) -> isize {
+    sys_semaphore_down_n(sem_id, n)
+}
+/// Apply all `ops` at once, waiting until every acquisition can be made
+pub fn semaphore_op(ops: &[SemOp]) -> isize {
+    sys_semaphore_op(ops)
+}
+pub fn semaphore_getvalue(sem_id: usize) -> isize {
+    sys_semaphore_getvalue(sem_id)
+}
+/// Tids of the threads blocked on `sem_id`, in the order they will be served
+pub fn semaphore_waiters(sem_id: usize, buf: &mut [usize]) -> isize {
+    sys_semaphore_waiters(sem_id, buf)
+}
+/// Permits of `sem_id` held by each thread, indexed by tid
+pub fn semaphore_audit(sem_id: usize, buf: &mut [usize]) -> isize {
+    sys_semaphore_audit(sem_id, buf)
+}
+/// Save the deadlock detector's view of this process into `buf`, return the
+/// number of words used or a negative error if `buf` is too small.
+pub fn sync_checkpoint(buf: &mut [usize]) -> isize {
+    let len = buf.len() * core::mem::size_of::<usize>();
+    let bytes = unsafe { core::slice::from_raw_parts_mut(buf.as_mut_ptr() as *mut u8, len) };
+    match sys_sync_checkpoint(bytes) {
+        n if n < 0 => n,
+        n => n / core::mem::size_of::<usize>() as isize,
+    }
+}
+/// Roll the requests the detector sees back to a checkpoint, all other
+/// threads must be blocked or exited and the owners and counts unchanged.
+pub fn sync_restore(state: &[usize]) -> isize {
+    let len = state.len() * core::mem::size_of::<usize>();
+    sys_sync_restore(unsafe { core::slice::from_raw_parts(state.as_ptr() as *const u8, len) })
+}
+pub const CONDVAR_CLASSIC: usize = 0;
+pub const CONDVAR_STICKY: usize = 1;
+
 pub fn condvar_create() -> isize {
-    sys_condvar_create(0)
+    sys_condvar_create(CONDVAR_CLASSIC)
+}
+/// A condvar remembering one signal sent while nobody waits
+pub fn condvar_sticky_create() -> isize {
+    sys_condvar_create(CONDVAR_STICKY)
+}
+/// Free a condvar nobody waits on, its id may be handed out again
+pub fn condvar_destroy(condvar_id: usize) -> isize {
+    sys_condvar_destroy(condvar_id)
 }
 pub fn condvar_signal(condvar_id: usize) {
     sys_condvar_signal(condvar_id);
 }
-pub fn condvar_wait(condvar_id: usize, mutex_id: usize) {
-    sys_condvar_wait(condvar_id, mutex_id);
+/// Wake up to `n` waiters, return how many were woken
+pub fn condvar_signal_n(condvar_id: usize, n: usize) -> isize {
+    sys_condvar_signal_n(condvar_id, n)
+}
+/// Wake thread `tid` if it waits on `condvar_id`, -1 if it does not
+pub fn condvar_signal_tid(condvar_id: usize, tid: usize) -> isize {
+    sys_condvar_signal_tid(condvar_id, tid)
+}
+/// Block while `word` holds `expected`, until a `futex_wake` on it
+pub fn futex_wait(word: &AtomicU32, expected: u32) -> isize {
+    sys_futex_wait(word as *const _ as usize, expected)
+}
+/// Wake up to `n` waiters of `word`, return how many were woken
+pub fn futex_wake(word: &AtomicU32, n: usize) -> isize {
+    sys_futex_wake(word as *const _ as usize, n)
+}
+/// Set `word` to `new` if it holds `expected`, return the value it held
+pub fn cas(word: &AtomicU32, expected: u32, new: u32) -> isize {
+    sys_cas(word as *const _ as usize, expected, new)
+}
+/// Also make every other thread of the process pass a full barrier
+pub const MEMBARRIER_GLOBAL: usize = 1;
+/// Order the memory accesses of the caller before the call before those
+/// after it, with `MEMBARRIER_GLOBAL` those of all threads of the process
+pub fn membarrier(flags: usize) -> isize {
+    sys_membarrier(flags)
+}
+pub fn condvar_wait(condvar_id: usize, mutex_id: usize) -> isize {
+    sys_condvar_wait(condvar_id, mutex_id)
+}
+/// Wait at most `timeout_ms` milliseconds, return 0 if signalled,
+/// -ETIMEDOUT if the time ran out and -EINTR if interrupted
+pub fn condvar_timedwait(condvar_id: usize, mutex_id: usize, timeout_ms: usize) -> isize {
+    sys_condvar_timedwait(condvar_id, mutex_id, timeout_ms)
+}
+/// Wait on `condvar_id` while `*pred == expected`, `pred` being protected
+/// by `mutex_id`, which the caller holds
+pub fn condvar_wait_while(
+    condvar_id: usize,
+    mutex_id: usize,
+    pred: &usize,
+    expected: usize,
+) -> isize {
+    sys_condvar_wait_while(condvar_id, mutex_id, pred, expected)
+}
+/// Create a queue of at most `capacity` messages of `msg_size` bytes
+pub fn mq_create(capacity: usize, msg_size: usize) -> isize {
+    sys_mq_create(capacity, msg_size)
+}
+/// Send `msg`, waiting while the queue is full. Return -EINVAL unless it is
+/// `msg_size` bytes long.
+pub fn mq_send(mq_id: usize, msg: &[u8]) -> isize {
+    sys_mq_send(mq_id, msg)
+}
+/// Receive the oldest message into `msg`, waiting while the queue is empty.
+/// Return -EINVAL unless it is `msg_size` bytes long.
+pub fn mq_recv(mq_id: usize, msg: &mut [u8]) -> isize {
+    sys_mq_recv(mq_id, msg)
+}
+/// Free a queue nobody is sending or receiving on
+pub fn mq_destroy(mq_id: usize) -> isize {
+    sys_mq_destroy(mq_id)
+}
+/// Send `value` on a queue of `size_of::<usize>()` byte messages, return
+/// -EINVAL for a queue of another message size
+pub fn mq_send_value(mq_id: usize, value: usize) -> isize {
+    sys_mq_send(mq_id, &value.to_ne_bytes())
+}
+/// Receive a value sent with [`mq_send_value`] into `value`, return -EINVAL
+/// for a queue of another message size
+pub fn mq_recv_value(mq_id: usize, value: &mut usize) -> isize {
+    let mut msg = [0u8; core::mem::size_of::<usize>()];
+    let ret = sys_mq_recv(mq_id, &mut msg);
+    if ret == 0 {
+        *value = usize::from_ne_bytes(msg);
+    }
+    ret
+}
+pub fn tls_set(key: usize, value: usize) -> isize {
+    sys_tls_set(key, value)
+}
+pub fn tls_get(key: usize) -> isize {
+    sys_tls_get(key)
+}
+
+/// Longest wait reason [`thread_set_waitreason`] accepts, in bytes
+pub const WAIT_REASON_LEN: usize = 32;
+
+/// Say what the current thread is about to wait for, shown in the scheduler
+/// trace and deadlock reports until it is woken, "" clears it
+pub fn thread_set_waitreason(reason: &str) -> isize {
+    sys_thread_set_waitreason(reason.as_bytes())
+}
+
+pub const BLOCKED_ON_MUTEX: isize = 1 << 32;
+pub const BLOCKED_ON_SEMAPHORE: isize = 2 << 32;
+
+pub fn thread_blocked_on(tid: usize) -> isize {
+    sys_thread_blocked_on(tid)
+}
+
+pub fn thread_sleep_remaining(tid: usize) -> isize {
+    sys_thread_sleep_remaining(tid)
+}
+
+pub fn set_cpu_limit(tid: usize, ms: usize) -> isize {
+    sys_set_cpu_limit(tid, ms)
+}
+
+pub fn clear_cpu_limit(tid: usize) -> isize {
+    sys_clear_cpu_limit(tid)
+}
+
+pub fn set_affinity(tid: usize, cpu_mask: usize) -> isize {
+    sys_set_affinity(tid, cpu_mask)
+}
+
+pub fn get_affinity(tid: usize) -> isize {
+    sys_get_affinity(tid)
+}
+
+pub fn task_stats(tid: usize, stats: &mut TaskStats) -> isize {
+    sys_task_stats(tid, stats)
+}
+pub fn getrusage(usage: &mut RUsage) -> isize {
+    sys_getrusage(usage)
+}
+pub fn sched_info(info: &mut SchedInfo) -> isize {
+    sys_sched_info(info)
+}
+pub fn enable_sched_trace(enabled: bool) -> isize {
+    sys_enable_sched_trace(enabled as usize)
+}
+pub fn sched_trace(events: &mut [SchedEvent]) -> isize {
+    sys_sched_trace(events)
+}
+
+pub fn thread_count() -> isize {
+    sys_thread_count()
+}
+
+pub fn thread_list(buf: &mut [usize]) -> isize {
+    sys_thread_list(buf)
+}
+
+/// List the live threads of process `pid` into `buf`, return how many there
+/// are, possibly more than fit
+pub fn proc_threads(pid: usize, buf: &mut [ThreadEntry]) -> isize {
+    sys_proc_threads(pid, buf)
 }
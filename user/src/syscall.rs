@@ -1,4 +1,5 @@
-use crate::TaskInfo;
+use crate::{DeadlockStats, DirEntry, PollFd, RUsage, SchedEvent, SchedInfo, SemOp, SyncEvent};
+use crate::{MutexStats, TaskInfo, TaskStats, ThreadEntry};
 
 use super::{Stat, TimeVal};
 
@@ -10,10 +11,14 @@ pub const SYSCALL_UNLINKAT: usize = 35;
 pub const SYSCALL_LINKAT: usize = 37;
 pub const SYSCALL_FSTAT: usize = 80;
 pub const SYSCALL_EXIT: usize = 93;
+pub const SYSCALL_EXIT_GROUP: usize = 94;
 pub const SYSCALL_SLEEP: usize = 101;
 pub const SYSCALL_YIELD: usize = 124;
+pub const SYSCALL_KILL: usize = 129;
+pub const SYSCALL_SIGPENDING: usize = 136;
 pub const SYSCALL_GETTIMEOFDAY: usize = 169;
 pub const SYSCALL_GETPID: usize = 172;
+pub const SYSCALL_GETPPID: usize = 173;
 pub const SYSCALL_GETTID: usize = 178;
 pub const SYSCALL_FORK: usize = 220;
 pub const SYSCALL_EXEC: usize = 221;
@@ -26,6 +31,7 @@ pub const SYSCALL_MAIL_READ: usize = 401;
 pub const SYSCALL_MAIL_WRITE: usize = 402;
 pub const SYSCALL_DUP: usize = 24;
 pub const SYSCALL_PIPE: usize = 59;
+pub const SYSCALL_LSEEK: usize = 62;
 pub const SYSCALL_TASK_INFO: usize = 410;
 pub const SYSCALL_THREAD_CREATE: usize = 460;
 pub const SYSCALL_WAITTID: usize = 462;
@@ -39,6 +45,71 @@ pub const SYSCALL_SEMAPHORE_DOWN: usize = 470;
 pub const SYSCALL_CONDVAR_CREATE: usize = 471;
 pub const SYSCALL_CONDVAR_SIGNAL: usize = 472;
 pub const SYSCALL_CONDVAR_WAIT: usize = 473;
+pub const SYSCALL_TLS_SET: usize = 474;
+pub const SYSCALL_TLS_GET: usize = 475;
+pub const SYSCALL_THREAD_BLOCKED_ON: usize = 476;
+pub const SYSCALL_SLEEP_UNTIL: usize = 477;
+pub const SYSCALL_SET_TIMESLICE: usize = 478;
+pub const SYSCALL_TASK_STATS: usize = 479;
+pub const SYSCALL_THREAD_COUNT: usize = 480;
+pub const SYSCALL_THREAD_LIST: usize = 481;
+pub const SYSCALL_YIELD_TO: usize = 482;
+pub const SYSCALL_SEMAPHORE_GETVALUE: usize = 483;
+pub const SYSCALL_READDIR: usize = 484;
+pub const SYSCALL_MUTEX_SET_RANK: usize = 485;
+pub const SYSCALL_DUP2: usize = 486;
+pub const SYSCALL_SET_AGING_INTERVAL: usize = 487;
+pub const SYSCALL_SYNC_CHECKPOINT: usize = 488;
+pub const SYSCALL_SYNC_RESTORE: usize = 489;
+pub const SYSCALL_MUTEX_KIND: usize = 490;
+pub const SYSCALL_MMAP_FILE: usize = 491;
+pub const SYSCALL_SEMAPHORE_UP_N: usize = 492;
+pub const SYSCALL_SEMAPHORE_DOWN_N: usize = 493;
+pub const SYSCALL_GETRUSAGE: usize = 494;
+pub const SYSCALL_SEMAPHORE_OP: usize = 495;
+pub const SYSCALL_THREAD_SLEEP_REMAINING: usize = 496;
+pub const SYSCALL_SYNC_TRACE_READ: usize = 497;
+pub const SYSCALL_CONDVAR_WAIT_WHILE: usize = 498;
+pub const SYSCALL_SET_CPU_LIMIT: usize = 499;
+pub const SYSCALL_CLEAR_CPU_LIMIT: usize = 500;
+pub const SYSCALL_CONDVAR_SIGNAL_N: usize = 501;
+pub const SYSCALL_FUTEX_WAIT: usize = 502;
+pub const SYSCALL_FUTEX_WAKE: usize = 503;
+pub const SYSCALL_SET_SYNC_QUOTA: usize = 504;
+pub const SYSCALL_DEADLOCK_STATS: usize = 505;
+pub const SYSCALL_CONDVAR_SIGNAL_TID: usize = 506;
+pub const SYSCALL_ENABLE_SCHED_TRACE: usize = 507;
+pub const SYSCALL_SCHED_TRACE: usize = 508;
+pub const SYSCALL_MUTEX_UNLOCK_TO: usize = 509;
+pub const SYSCALL_PIPE2: usize = 510;
+pub const SYSCALL_SCHED_SETPOLICY: usize = 511;
+pub const SYSCALL_POLL: usize = 512;
+pub const SYSCALL_MUTEX_SET_CEILING: usize = 513;
+pub const SYSCALL_SEMAPHORE_WAITERS: usize = 514;
+pub const SYSCALL_PROC_THREADS: usize = 515;
+pub const SYSCALL_SCHED_INFO: usize = 516;
+pub const SYSCALL_SEMAPHORE_AUDIT: usize = 517;
+pub const SYSCALL_THREAD_CREATE_SUSPENDED: usize = 518;
+pub const SYSCALL_THREAD_RESUME: usize = 519;
+pub const SYSCALL_CONDVAR_DESTROY: usize = 520;
+pub const SYSCALL_MQ_CREATE: usize = 521;
+pub const SYSCALL_MQ_SEND: usize = 522;
+pub const SYSCALL_MQ_RECV: usize = 523;
+pub const SYSCALL_GET_TICKS: usize = 524;
+pub const SYSCALL_SYNC_WAIT_TIME: usize = 525;
+pub const SYSCALL_MUTEX_SPINLOCK: usize = 526;
+pub const SYSCALL_MUTEX_STATS: usize = 527;
+pub const SYSCALL_SET_AFFINITY: usize = 528;
+pub const SYSCALL_GET_AFFINITY: usize = 529;
+pub const SYSCALL_MQ_DESTROY: usize = 530;
+pub const SYSCALL_CHECK_DEADLOCK: usize = 531;
+pub const SYSCALL_SCHED_SET_SEED: usize = 532;
+pub const SYSCALL_CONDVAR_TIMEDWAIT: usize = 533;
+pub const SYSCALL_MEMBARRIER: usize = 534;
+pub const SYSCALL_MUTEX_TRYLOCK_CHECKED: usize = 535;
+pub const SYSCALL_THREAD_SET_WAITREASON: usize = 536;
+pub const SYSCALL_THREAD_SUSPEND: usize = 537;
+pub const SYSCALL_CAS: usize = 538;
 
 pub fn syscall(id: usize, args: [usize; 3]) -> isize {
     let mut ret: isize;
@@ -146,14 +217,31 @@ pub fn sys_exit(exit_code: i32) -> ! {
     panic!("sys_exit never returns!");
 }
 
+pub fn sys_exit_group(exit_code: i32) -> ! {
+    syscall(SYSCALL_EXIT_GROUP, [exit_code as usize, 0, 0]);
+    panic!("sys_exit_group never returns!");
+}
+
 pub fn sys_sleep(sleep_ms: usize) -> isize {
     syscall(SYSCALL_SLEEP, [sleep_ms, 0, 0])
 }
 
+pub fn sys_sleep_until(abs_ms: usize) -> isize {
+    syscall(SYSCALL_SLEEP_UNTIL, [abs_ms, 0, 0])
+}
+
 pub fn sys_yield() -> isize {
     syscall(SYSCALL_YIELD, [0, 0, 0])
 }
 
+pub fn sys_kill(pid: usize, signum: usize) -> isize {
+    syscall(SYSCALL_KILL, [pid, signum, 0])
+}
+
+pub fn sys_sigpending() -> isize {
+    syscall(SYSCALL_SIGPENDING, [0, 0, 0])
+}
+
 pub fn sys_get_time(time: &TimeVal, tz: usize) -> isize {
     syscall(SYSCALL_GETTIMEOFDAY, [time as *const _ as usize, tz, 0])
 }
@@ -162,6 +250,10 @@ pub fn sys_getpid() -> isize {
     syscall(SYSCALL_GETPID, [0, 0, 0])
 }
 
+pub fn sys_getppid() -> isize {
+    syscall(SYSCALL_GETPPID, [0, 0, 0])
+}
+
 pub fn sys_fork() -> isize {
     syscall(SYSCALL_FORK, [0, 0, 0])
 }
@@ -181,6 +273,10 @@ pub fn sys_set_priority(prio: isize) -> isize {
     syscall(SYSCALL_SET_PRIORITY, [prio as usize, 0, 0])
 }
 
+pub fn sys_set_timeslice(ms: usize) -> isize {
+    syscall(SYSCALL_SET_TIMESLICE, [ms, 0, 0])
+}
+
 pub fn sys_mmap(start: usize, len: usize, prot: usize) -> isize {
     syscall(SYSCALL_MMAP, [start, len, prot])
 }
@@ -189,6 +285,10 @@ pub fn sys_munmap(start: usize, len: usize) -> isize {
     syscall(SYSCALL_MUNMAP, [start, len, 0])
 }
 
+pub fn sys_mmap_file(start: usize, len: usize, prot: usize, fd: usize, offset: usize) -> isize {
+    syscall6(SYSCALL_MMAP_FILE, [start, len, prot, fd, offset, 0])
+}
+
 pub fn sys_spawn(path: &str) -> isize {
     syscall(SYSCALL_SPAWN, [path.as_ptr() as usize, 0, 0])
 }
@@ -201,12 +301,16 @@ pub fn sys_pipe(pipe: &mut [usize]) -> isize {
     syscall(SYSCALL_PIPE, [pipe.as_mut_ptr() as usize, 0, 0])
 }
 
+pub fn sys_lseek(fd: usize, offset: isize, whence: usize) -> isize {
+    syscall(SYSCALL_LSEEK, [fd, offset as usize, whence])
+}
+
 pub fn sys_task_info(info: &TaskInfo) -> isize {
     syscall(SYSCALL_TASK_INFO, [info as *const _ as usize, 0, 0])
 }
 
-pub fn sys_thread_create(entry: usize, arg: usize) -> isize {
-    syscall(SYSCALL_THREAD_CREATE, [entry, arg, 0])
+pub fn sys_thread_create(entry: usize, arg: usize, stack_size: usize) -> isize {
+    syscall(SYSCALL_THREAD_CREATE, [entry, arg, stack_size])
 }
 
 pub fn sys_gettid() -> isize {
@@ -217,8 +321,8 @@ pub fn sys_waittid(tid: usize) -> isize {
     syscall(SYSCALL_WAITTID, [tid, 0, 0])
 }
 
-pub fn sys_mutex_create(blocking: bool) -> isize {
-    syscall(SYSCALL_MUTEX_CREATE, [blocking as usize, 0, 0])
+pub fn sys_mutex_create(kind: usize) -> isize {
+    syscall(SYSCALL_MUTEX_CREATE, [kind, 0, 0])
 }
 
 pub fn sys_mutex_lock(id: usize) -> isize {
@@ -237,6 +341,14 @@ pub fn sys_semaphore_up(sem_id: usize) -> isize {
     syscall(SYSCALL_SEMAPHORE_UP, [sem_id, 0, 0])
 }
 
+pub fn sys_set_sync_quota(kind: usize, n: usize) -> isize {
+    syscall(SYSCALL_SET_SYNC_QUOTA, [kind, n, 0])
+}
+
+pub fn sys_deadlock_stats(stats: &mut DeadlockStats) -> isize {
+    syscall(SYSCALL_DEADLOCK_STATS, [stats as *mut _ as usize, 0, 0])
+}
+
 pub fn sys_enable_deadlock_detect(enabled: usize) -> isize {
     syscall(SYSCALL_ENABLE_DEADLOCK_DETECT, [enabled, 0, 0])
 }
@@ -245,14 +357,293 @@ pub fn sys_semaphore_down(sem_id: usize) -> isize {
     syscall(SYSCALL_SEMAPHORE_DOWN, [sem_id, 0, 0])
 }
 
-pub fn sys_condvar_create(_arg: usize) -> isize {
-    syscall(SYSCALL_CONDVAR_CREATE, [_arg, 0, 0])
+pub fn sys_condvar_create(kind: usize) -> isize {
+    syscall(SYSCALL_CONDVAR_CREATE, [kind, 0, 0])
 }
 
 pub fn sys_condvar_signal(condvar_id: usize) -> isize {
     syscall(SYSCALL_CONDVAR_SIGNAL, [condvar_id, 0, 0])
 }
 
+pub fn sys_condvar_signal_n(condvar_id: usize, n: usize) -> isize {
+    syscall(SYSCALL_CONDVAR_SIGNAL_N, [condvar_id, n, 0])
+}
+
+pub fn sys_futex_wait(addr: usize, expected: u32) -> isize {
+    syscall(SYSCALL_FUTEX_WAIT, [addr, expected as usize, 0])
+}
+
+pub fn sys_futex_wake(addr: usize, n: usize) -> isize {
+    syscall(SYSCALL_FUTEX_WAKE, [addr, n, 0])
+}
+
+pub fn sys_condvar_signal_tid(condvar_id: usize, tid: usize) -> isize {
+    syscall(SYSCALL_CONDVAR_SIGNAL_TID, [condvar_id, tid, 0])
+}
+
 pub fn sys_condvar_wait(condvar_id: usize, mutex_id: usize) -> isize {
     syscall(SYSCALL_CONDVAR_WAIT, [condvar_id, mutex_id, 0])
 }
+
+pub fn sys_condvar_wait_while(
+    condvar_id: usize,
+    mutex_id: usize,
+    pred: &usize,
+    expected: usize,
+) -> isize {
+    let pred = pred as *const _ as usize;
+    syscall6(
+        SYSCALL_CONDVAR_WAIT_WHILE,
+        [condvar_id, mutex_id, pred, expected, 0, 0],
+    )
+}
+
+pub fn sys_tls_set(key: usize, value: usize) -> isize {
+    syscall(SYSCALL_TLS_SET, [key, value, 0])
+}
+
+pub fn sys_tls_get(key: usize) -> isize {
+    syscall(SYSCALL_TLS_GET, [key, 0, 0])
+}
+
+pub fn sys_thread_blocked_on(tid: usize) -> isize {
+    syscall(SYSCALL_THREAD_BLOCKED_ON, [tid, 0, 0])
+}
+
+pub fn sys_thread_sleep_remaining(tid: usize) -> isize {
+    syscall(SYSCALL_THREAD_SLEEP_REMAINING, [tid, 0, 0])
+}
+
+pub fn sys_set_cpu_limit(tid: usize, ms: usize) -> isize {
+    syscall(SYSCALL_SET_CPU_LIMIT, [tid, ms, 0])
+}
+
+pub fn sys_clear_cpu_limit(tid: usize) -> isize {
+    syscall(SYSCALL_CLEAR_CPU_LIMIT, [tid, 0, 0])
+}
+
+pub fn sys_task_stats(tid: usize, stats: &mut TaskStats) -> isize {
+    syscall(SYSCALL_TASK_STATS, [tid, stats as *mut _ as usize, 0])
+}
+
+pub fn sys_getrusage(usage: &mut RUsage) -> isize {
+    syscall(SYSCALL_GETRUSAGE, [usage as *mut _ as usize, 0, 0])
+}
+
+pub fn sys_thread_count() -> isize {
+    syscall(SYSCALL_THREAD_COUNT, [0, 0, 0])
+}
+
+pub fn sys_thread_list(buf: &mut [usize]) -> isize {
+    syscall(SYSCALL_THREAD_LIST, [buf.as_mut_ptr() as usize, buf.len(), 0])
+}
+
+pub fn sys_yield_to(tid: usize) -> isize {
+    syscall(SYSCALL_YIELD_TO, [tid, 0, 0])
+}
+
+pub fn sys_semaphore_getvalue(sem_id: usize) -> isize {
+    syscall(SYSCALL_SEMAPHORE_GETVALUE, [sem_id, 0, 0])
+}
+
+pub fn sys_readdir(path: &str, entries: &mut [DirEntry]) -> isize {
+    syscall(
+        SYSCALL_READDIR,
+        [
+            path.as_ptr() as usize,
+            entries.as_mut_ptr() as usize,
+            entries.len(),
+        ],
+    )
+}
+
+pub fn sys_mutex_set_rank(mutex_id: usize, rank: usize) -> isize {
+    syscall(SYSCALL_MUTEX_SET_RANK, [mutex_id, rank, 0])
+}
+
+pub fn sys_dup2(old_fd: usize, new_fd: usize) -> isize {
+    syscall(SYSCALL_DUP2, [old_fd, new_fd, 0])
+}
+
+pub fn sys_set_aging_interval(ticks: usize) -> isize {
+    syscall(SYSCALL_SET_AGING_INTERVAL, [ticks, 0, 0])
+}
+
+pub fn sys_sync_checkpoint(buf: &mut [u8]) -> isize {
+    syscall(
+        SYSCALL_SYNC_CHECKPOINT,
+        [buf.as_mut_ptr() as usize, buf.len(), 0],
+    )
+}
+
+pub fn sys_sync_restore(buf: &[u8]) -> isize {
+    syscall(SYSCALL_SYNC_RESTORE, [buf.as_ptr() as usize, buf.len(), 0])
+}
+
+pub fn sys_mutex_kind(mutex_id: usize) -> isize {
+    syscall(SYSCALL_MUTEX_KIND, [mutex_id, 0, 0])
+}
+
+pub fn sys_semaphore_up_n(sem_id: usize, n: usize) -> isize {
+    syscall(SYSCALL_SEMAPHORE_UP_N, [sem_id, n, 0])
+}
+
+pub fn sys_semaphore_down_n(sem_id: usize, n: usize) -> isize {
+    syscall(SYSCALL_SEMAPHORE_DOWN_N, [sem_id, n, 0])
+}
+
+pub fn sys_semaphore_op(ops: &[SemOp]) -> isize {
+    syscall(SYSCALL_SEMAPHORE_OP, [ops.as_ptr() as usize, ops.len(), 0])
+}
+
+pub fn sys_sync_trace_read(events: &mut [SyncEvent]) -> isize {
+    syscall(
+        SYSCALL_SYNC_TRACE_READ,
+        [events.as_mut_ptr() as usize, events.len(), 0],
+    )
+}
+
+pub fn sys_enable_sched_trace(enabled: usize) -> isize {
+    syscall(SYSCALL_ENABLE_SCHED_TRACE, [enabled, 0, 0])
+}
+
+pub fn sys_sched_trace(events: &mut [SchedEvent]) -> isize {
+    syscall(
+        SYSCALL_SCHED_TRACE,
+        [events.as_mut_ptr() as usize, events.len(), 0],
+    )
+}
+
+pub fn sys_mutex_unlock_to(mutex_id: usize, tid: usize) -> isize {
+    syscall(SYSCALL_MUTEX_UNLOCK_TO, [mutex_id, tid, 0])
+}
+
+pub fn sys_pipe2(pipe: &mut [usize], flags: u32) -> isize {
+    syscall(SYSCALL_PIPE2, [pipe.as_mut_ptr() as usize, flags as usize, 0])
+}
+
+pub fn sys_sched_setpolicy(policy: usize) -> isize {
+    syscall(SYSCALL_SCHED_SETPOLICY, [policy, 0, 0])
+}
+
+pub fn sys_poll(fds: &mut [PollFd], timeout_ms: isize) -> isize {
+    let fds_ptr = fds.as_mut_ptr() as usize;
+    syscall(SYSCALL_POLL, [fds_ptr, fds.len(), timeout_ms as usize])
+}
+
+pub fn sys_mutex_set_ceiling(mutex_id: usize, prio: usize) -> isize {
+    syscall(SYSCALL_MUTEX_SET_CEILING, [mutex_id, prio, 0])
+}
+
+pub fn sys_semaphore_waiters(sem_id: usize, buf: &mut [usize]) -> isize {
+    let buf_ptr = buf.as_mut_ptr() as usize;
+    syscall(SYSCALL_SEMAPHORE_WAITERS, [sem_id, buf_ptr, buf.len()])
+}
+
+pub fn sys_proc_threads(pid: usize, buf: &mut [ThreadEntry]) -> isize {
+    let buf_ptr = buf.as_mut_ptr() as usize;
+    syscall(SYSCALL_PROC_THREADS, [pid, buf_ptr, buf.len()])
+}
+
+pub fn sys_sched_info(info: &mut SchedInfo) -> isize {
+    syscall(SYSCALL_SCHED_INFO, [info as *mut _ as usize, 0, 0])
+}
+
+pub fn sys_semaphore_audit(sem_id: usize, buf: &mut [usize]) -> isize {
+    let buf_ptr = buf.as_mut_ptr() as usize;
+    syscall(SYSCALL_SEMAPHORE_AUDIT, [sem_id, buf_ptr, buf.len()])
+}
+
+pub fn sys_thread_create_suspended(entry: usize, arg: usize, stack_size: usize) -> isize {
+    syscall(SYSCALL_THREAD_CREATE_SUSPENDED, [entry, arg, stack_size])
+}
+
+pub fn sys_thread_resume(tid: usize) -> isize {
+    syscall(SYSCALL_THREAD_RESUME, [tid, 0, 0])
+}
+
+pub fn sys_condvar_destroy(condvar_id: usize) -> isize {
+    syscall(SYSCALL_CONDVAR_DESTROY, [condvar_id, 0, 0])
+}
+
+pub fn sys_mq_create(capacity: usize, msg_size: usize) -> isize {
+    syscall(SYSCALL_MQ_CREATE, [capacity, msg_size, 0])
+}
+
+pub fn sys_mq_send(mq_id: usize, msg: &[u8]) -> isize {
+    syscall(SYSCALL_MQ_SEND, [mq_id, msg.as_ptr() as usize, msg.len()])
+}
+
+pub fn sys_mq_recv(mq_id: usize, msg: &mut [u8]) -> isize {
+    let msg_ptr = msg.as_mut_ptr() as usize;
+    syscall(SYSCALL_MQ_RECV, [mq_id, msg_ptr, msg.len()])
+}
+
+pub fn sys_get_ticks() -> isize {
+    syscall(SYSCALL_GET_TICKS, [0, 0, 0])
+}
+
+pub fn sys_sync_wait_time(kind: usize, id: usize) -> isize {
+    syscall(SYSCALL_SYNC_WAIT_TIME, [kind, id, 0])
+}
+
+pub fn sys_mutex_spinlock(mutex_id: usize, max_spins: usize) -> isize {
+    syscall(SYSCALL_MUTEX_SPINLOCK, [mutex_id, max_spins, 0])
+}
+
+pub fn sys_mutex_stats(mutex_id: usize, stats: &mut MutexStats, flags: usize) -> isize {
+    syscall(
+        SYSCALL_MUTEX_STATS,
+        [mutex_id, stats as *mut _ as usize, flags],
+    )
+}
+
+pub fn sys_set_affinity(tid: usize, cpu_mask: usize) -> isize {
+    syscall(SYSCALL_SET_AFFINITY, [tid, cpu_mask, 0])
+}
+
+pub fn sys_get_affinity(tid: usize) -> isize {
+    syscall(SYSCALL_GET_AFFINITY, [tid, 0, 0])
+}
+
+pub fn sys_mq_destroy(mq_id: usize) -> isize {
+    syscall(SYSCALL_MQ_DESTROY, [mq_id, 0, 0])
+}
+
+pub fn sys_check_deadlock() -> isize {
+    syscall(SYSCALL_CHECK_DEADLOCK, [0, 0, 0])
+}
+
+pub fn sys_sched_set_seed(seed: usize) -> isize {
+    syscall(SYSCALL_SCHED_SET_SEED, [seed, 0, 0])
+}
+
+pub fn sys_condvar_timedwait(condvar_id: usize, mutex_id: usize, timeout_ms: usize) -> isize {
+    syscall(
+        SYSCALL_CONDVAR_TIMEDWAIT,
+        [condvar_id, mutex_id, timeout_ms],
+    )
+}
+
+pub fn sys_membarrier(flags: usize) -> isize {
+    syscall(SYSCALL_MEMBARRIER, [flags, 0, 0])
+}
+
+pub fn sys_mutex_trylock_checked(mutex_id: usize) -> isize {
+    syscall(SYSCALL_MUTEX_TRYLOCK_CHECKED, [mutex_id, 0, 0])
+}
+
+pub fn sys_thread_set_waitreason(reason: &[u8]) -> isize {
+    syscall(
+        SYSCALL_THREAD_SET_WAITREASON,
+        [reason.as_ptr() as usize, reason.len(), 0],
+    )
+}
+
+pub fn sys_thread_suspend(tid: usize) -> isize {
+    syscall(SYSCALL_THREAD_SUSPEND, [tid, 0, 0])
+}
+
+pub fn sys_cas(addr: usize, expected: u32, new: u32) -> isize {
+    syscall(SYSCALL_CAS, [addr, expected as usize, new as usize])
+}